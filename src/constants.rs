@@ -3,12 +3,21 @@
 */
 
 // Proof length is approx. (security_parameter/log(blowup_factor))log^2(k) hash values,
-// i.e. 64log^2(k) hash values
+// i.e. 64log^2(k) hash values. fri::FriConfig estimates the bits of security of a configuration.
 
 // FRI_BLOWUP_FACTOR must be a power of two for this impl
 pub const FRI_BLOWUP_FACTOR: usize = 4;
 pub const FRI_BLOWUP_LOG: usize = 2;
 
+// Number of random bytes prepended to every Merkle leaf when committing in zero-knowledge mode
+pub const SALT_SIZE: usize = 32;
+
+// Inputs from this size on are transformed by the multithreaded FFT
+pub const MT_FFT_THRESHOLD: usize = 1 << 14;
+
+// and from this size on by the four-step FFT, whose sub-FFTs fit in cache
+pub const FOUR_STEP_FFT_THRESHOLD: usize = 1 << 22;
+
 pub const ZERO_BYTES: [u8; 32] = 
 [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -0,0 +1,332 @@
+use ff::Field;
+
+use crate::{
+    field::m31::M31,
+    utils::batch_inverse,
+    error::SynthesisError,
+};
+
+/*
+ * The circle group over M31: points (x, y) with x^2 + y^2 = 1 under
+ *
+ *     (x0, y0) * (x1, y1) = (x0 * x1 - y0 * y1, x0 * y1 + y0 * x1)
+ *
+ * i.e. multiplication of x + y * i in CM31 restricted to elements of norm one. For p = 2^31 - 1 the
+ * group has p + 1 = 2^31 elements, so it has subgroups of every power of two order. These play the
+ * part of the roots of unity, which M31 lacks, for FFTs and FRI over Mersenne-31 (Circle STARKs).
+ *
+ * The group is written additively below, to match the usual notation for the circle FFT.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CirclePoint<F: Field> {
+    pub x: F,
+    pub y: F,
+}
+
+impl<F: Field> CirclePoint<F> {
+
+    pub fn identity() -> Self {
+        Self { x: F::ONE, y: F::ZERO }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Self {
+            x: self.x * other.x - self.y * other.y,
+            y: self.x * other.y + self.y * other.x,
+        }
+    }
+
+    // Doubling only depends on x for the x-coordinate, x -> 2x^2 - 1, which is the map circle FRI
+    // folds with.
+    pub fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    pub fn repeated_double(&self, count: u32) -> Self {
+        (0..count).fold(*self, |acc, _| acc.double())
+    }
+
+    // The inverse of (x, y) is its conjugate (x, -y).
+    pub fn conjugate(&self) -> Self {
+        Self { x: self.x, y: -self.y }
+    }
+
+    // (-x, -y), the point plus the element of order 2
+    pub fn antipode(&self) -> Self {
+        Self { x: -self.x, y: -self.y }
+    }
+
+    pub fn mul(&self, mut scalar: u64) -> Self {
+        let mut result = Self::identity();
+        let mut base = *self;
+        while scalar > 0 {
+            if scalar & 1 == 1 {
+                result = result.add(&base);
+            }
+            base = base.double();
+            scalar >>= 1;
+        }
+        result
+    }
+
+    pub fn is_on_circle(&self) -> bool {
+        self.x.square() + self.y.square() == F::ONE
+    }
+}
+
+// The circle group over M31 has order 2^31.
+pub const CIRCLE_LOG_ORDER: u32 = 31;
+
+impl CirclePoint<M31> {
+
+    // Generator of the whole group.
+    pub fn generator() -> Self {
+        Self { x: M31::new(2), y: M31::new(1268011823) }
+    }
+
+    // Generator of the subgroup of order 2^log_order.
+    pub fn subgroup_generator(log_order: u32) -> Self {
+        assert!(log_order <= CIRCLE_LOG_ORDER, "The circle group has order 2^31.");
+        Self::generator().repeated_double(CIRCLE_LOG_ORDER - log_order)
+    }
+}
+
+/*
+ * Evaluation domain of size n = 2^k, the coset G_2n + <G_n> of the subgroup of order n, where G_m
+ * generates the subgroup of order m. Its points are the odd multiples of G_2n, so the domain is
+ * closed under conjugation (the circle analogue of x -> -x), and doubling maps it two-to-one onto
+ * the domain of size n/2, which is what lets FRI fold it.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircleDomain {
+    pub log_size: u32,
+    pub shift: CirclePoint<M31>,
+    pub step: CirclePoint<M31>,
+}
+
+impl CircleDomain {
+
+    pub fn new(log_size: u32) -> Self {
+        assert!(log_size < CIRCLE_LOG_ORDER, "Domain is larger than the circle group allows.");
+        Self {
+            log_size,
+            shift: CirclePoint::subgroup_generator(log_size + 1),
+            step: CirclePoint::subgroup_generator(log_size),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.log_size
+    }
+
+    // shift + i * step
+    pub fn element(&self, index: usize) -> CirclePoint<M31> {
+        self.shift.add(&self.step.mul(index as u64))
+    }
+
+    pub fn elements(&self) -> Vec<CirclePoint<M31>> {
+        let mut point = self.shift;
+        (0..self.size()).map(|_| {
+            let current = point;
+            point = point.add(&self.step);
+            current
+        }).collect()
+    }
+
+    // The domain the x-coordinates of this one fold into.
+    pub fn halve(&self) -> Self {
+        Self::new(self.log_size - 1)
+    }
+
+    // Coefficients in the circle basis (see below) of the polynomial taking `values` on the
+    // elements of the domain, in the order of `elements`.
+    pub fn interpolate(&self, values: &[M31]) -> Result<Vec<M31>, SynthesisError> {
+        self.check_len(values.len())?;
+        if values.len() == 1 { return Ok(values.to_vec()); }
+
+        let (ys, lines) = self.twiddles();
+        let inverses = batch_inverse(&ys.iter().map(|y| y.double()).collect::<Vec<_>>())
+            .expect("Only the points of order two have y = 0, and they are not in a domain of size two or more.");
+        let line_inverses = lines.iter().map(|xs| {
+            batch_inverse(&xs[..xs.len() / 2].iter().map(|x| x.double()).collect::<Vec<_>>())
+                .expect("Only the points of order four have x = 0, and they are only split off in a domain of size one.")
+        }).collect::<Vec<_>>();
+
+        let half = ys.len();
+        let n = values.len();
+        let two_inv = M31::new(2).invert().unwrap();
+        let (even, odd): (Vec<_>, Vec<_>) = (0..half).map(|i| {
+            let (a, b) = (values[i], values[n - 1 - i]);
+            ((a + b) * two_inv, (a - b) * inverses[i])
+        }).unzip();
+
+        Ok(interleave(interpolate_line(even, &line_inverses, two_inv), interpolate_line(odd, &line_inverses, two_inv)))
+    }
+
+    // Inverse of `interpolate`, the values of the polynomial with the given circle coefficients on
+    // the elements of the domain.
+    pub fn evaluate(&self, coefficients: &[M31]) -> Result<Vec<M31>, SynthesisError> {
+        self.check_len(coefficients.len())?;
+        if coefficients.len() == 1 { return Ok(coefficients.to_vec()); }
+
+        let (ys, lines) = self.twiddles();
+        let (even, odd) = deinterleave(coefficients);
+        let (even, odd) = (evaluate_line(&even, &lines), evaluate_line(&odd, &lines));
+
+        let n = coefficients.len();
+        let mut values = vec![M31::ZERO; n];
+        for (i, y) in ys.iter().enumerate() {
+            values[i] = even[i] + *y * odd[i];
+            values[n - 1 - i] = even[i] - *y * odd[i];
+        }
+        Ok(values)
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), SynthesisError> {
+        if len != self.size() {
+            return Err(SynthesisError::LengthMismatch(self.size(), len));
+        }
+        Ok(())
+    }
+
+    // The y-coordinates of the first half of the domain, and the x-coordinates every layer after the
+    // first splits on. Element n - 1 - i is the conjugate of element i, and on the x-coordinates of
+    // the first half, x_(m - 1 - i) = -x_i. Doubling maps element i to element i of the halved
+    // domain, so x -> 2x^2 - 1 takes the first half of one layer to the next layer.
+    fn twiddles(&self) -> (Vec<M31>, Vec<Vec<M31>>) {
+        let half = &self.elements()[..self.size() / 2];
+        let mut lines = vec![half.iter().map(|p| p.x).collect::<Vec<_>>()];
+        while lines.last().unwrap().len() > 1 {
+            let xs = lines.last().unwrap();
+            let next = xs[..xs.len() / 2].iter().map(|x| x.square().double() - M31::ONE).collect();
+            lines.push(next);
+        }
+        (half.iter().map(|p| p.y).collect(), lines)
+    }
+}
+
+/*
+ * Circle FFT. Circle polynomials are written in the basis
+ *
+ *     b_j(x, y) = y^(j_0) * x^(j_1) * pi(x)^(j_2) * pi(pi(x))^(j_3) * ...
+ *
+ * where j_0, j_1, ... are the bits of j and pi(x) = 2x^2 - 1 is the x-coordinate of the double of
+ * a point. The first layer splits f(x, y) = f_0(x) + y * f_1(x) using the conjugate pairs (x, y)
+ * and (x, -y), every later layer splits g(x) = g_0(pi(x)) + x * g_1(pi(x)) using the pairs x and
+ * -x, exactly like the radix-2 FFT with x -> x^2 replaced by pi.
+*/
+
+// The value of the polynomial with circle coefficients `coefficients` at `point`.
+pub fn evaluate_at_point(coefficients: &[M31], point: &CirclePoint<M31>) -> M31 {
+    let mut factors = vec![point.y, point.x];
+    while (1 << factors.len()) < coefficients.len() {
+        let x = *factors.last().unwrap();
+        factors.push(x.square().double() - M31::ONE);
+    }
+    coefficients.iter().enumerate().map(|(j, c)| {
+        factors.iter().enumerate().filter(|(bit, _)| j >> bit & 1 == 1).fold(*c, |acc, (_, f)| acc * f)
+    }).sum()
+}
+
+fn interpolate_line(values: Vec<M31>, inverses: &[Vec<M31>], two_inv: M31) -> Vec<M31> {
+    let m = values.len();
+    if m == 1 { return values; }
+    let (even, odd): (Vec<_>, Vec<_>) = (0..m / 2).map(|i| {
+        let (a, b) = (values[i], values[m - 1 - i]);
+        ((a + b) * two_inv, (a - b) * inverses[0][i])
+    }).unzip();
+    interleave(interpolate_line(even, &inverses[1..], two_inv), interpolate_line(odd, &inverses[1..], two_inv))
+}
+
+fn evaluate_line(coefficients: &[M31], lines: &[Vec<M31>]) -> Vec<M31> {
+    let m = coefficients.len();
+    if m == 1 { return coefficients.to_vec(); }
+    let (even, odd) = deinterleave(coefficients);
+    let (even, odd) = (evaluate_line(&even, &lines[1..]), evaluate_line(&odd, &lines[1..]));
+    let mut values = vec![M31::ZERO; m];
+    for (i, x) in lines[0][..m / 2].iter().enumerate() {
+        values[i] = even[i] + *x * odd[i];
+        values[m - 1 - i] = even[i] - *x * odd[i];
+    }
+    values
+}
+
+fn interleave(even: Vec<M31>, odd: Vec<M31>) -> Vec<M31> {
+    even.into_iter().zip(odd).flat_map(|(a, b)| [a, b]).collect()
+}
+
+fn deinterleave(coefficients: &[M31]) -> (Vec<M31>, Vec<M31>) {
+    (coefficients.iter().step_by(2).copied().collect(), coefficients.iter().skip(1).step_by(2).copied().collect())
+}
+
+#[cfg(test)]
+mod circle_tests {
+
+    use super::*;
+
+    #[test]
+    fn group_structure() {
+        let g = CirclePoint::<M31>::generator();
+        assert!(g.is_on_circle());
+        assert_eq!(g.repeated_double(CIRCLE_LOG_ORDER), CirclePoint::identity());
+        assert_ne!(g.repeated_double(CIRCLE_LOG_ORDER - 1), CirclePoint::identity());
+        assert_eq!(g.add(&g.conjugate()), CirclePoint::identity());
+        assert_eq!(g.mul(5), g.double().double().add(&g));
+
+        let half = CirclePoint::<M31>::subgroup_generator(1);
+        assert_eq!(half, CirclePoint { x: -M31::ONE, y: M31::ZERO });
+        assert_eq!(g.add(&half), g.antipode());
+    }
+
+    #[test]
+    fn domains() {
+        let domain = CircleDomain::new(5);
+        let mut points = domain.elements();
+        assert_eq!(points.len(), 32);
+        assert_eq!(points[7], domain.element(7));
+        assert!(points.iter().all(|p| p.is_on_circle()));
+
+        // Closed under conjugation
+        for point in &points {
+            assert!(points.contains(&point.conjugate()));
+        }
+
+        // Doubling covers the halved domain twice
+        let halved = domain.halve().elements();
+        for point in &points {
+            assert!(halved.contains(&point.double()));
+        }
+
+        points.sort_by_key(|p| (p.x, p.y));
+        points.dedup();
+        assert_eq!(points.len(), 32);
+    }
+
+    #[test]
+    fn circle_fft() {
+        let rng = rand::thread_rng();
+        for log_size in 0..8 {
+            let domain = CircleDomain::new(log_size);
+            let values = (0..domain.size()).map(|_| M31::random(rng.clone())).collect::<Vec<_>>();
+
+            let coefficients = domain.interpolate(&values).unwrap();
+            for (point, value) in domain.elements().iter().zip(&values) {
+                assert_eq!(evaluate_at_point(&coefficients, point), *value);
+            }
+            assert_eq!(domain.evaluate(&coefficients).unwrap(), values);
+        }
+
+        // A polynomial in fewer basis elements than the domain size extends to a larger domain
+        let coefficients = (0..8).map(|_| M31::random(rng.clone())).collect::<Vec<_>>();
+        let mut padded = coefficients.clone();
+        padded.resize(32, M31::ZERO);
+        let large = CircleDomain::new(5);
+        for (point, value) in large.elements().iter().zip(large.evaluate(&padded).unwrap()) {
+            assert_eq!(evaluate_at_point(&coefficients, point), value);
+        }
+
+        assert_eq!(large.interpolate(&coefficients), Err(SynthesisError::LengthMismatch(32, 8)));
+    }
+}
@@ -1,20 +1,35 @@
 use ff::PrimeField;
 
+use std::sync::Arc;
+
+use crate::{
+    fft::{FftPlan, mixed_radix, coset::scale_by_powers},
+    utils::batch_inverse,
+};
+
+pub mod circle;
+
+pub use crate::error::SynthesisError;
+
 /*
  * I copied this from Matter Labs
  * https://github.com/matter-labs/hodor/blob/master/src/domains/mod.rs
+ *
+ * A domain is the coset offset * H of the subgroup H of order `size` generated by `generator`,
+ * with elements x_i = offset * generator^i. The offset is one unless the domain was shifted with
+ * `with_offset`, in which case it misses H entirely and its vanishing polynomial is
+ * x^size - offset^size.
 */
 
-#[derive(Debug)]
-pub enum SynthesisError {
-    Error,
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Domain<F: PrimeField> {
     pub size: u64,
     pub power_of_two: u64,
     pub generator: F,
+    pub group_gen_inv: F,
+    pub size_inv: F,
+    pub offset: F,
+    pub offset_inv: F,
 }
 
 impl<F: PrimeField> Domain<F> {
@@ -28,23 +43,19 @@ impl<F: PrimeField> Domain<F> {
         }
         let max_power_of_two = F::S;
         let mut g = F::ROOT_OF_UNITY;
-        for _ in log_n..(max_power_of_two-1) {
+        for _ in log_n..max_power_of_two {
             g = g.square();
         }
         g
     }
 
     pub fn new_for_size(size: u64) -> Result<Self, SynthesisError> {
-        let size = size.next_power_of_two();
-        let mut power_of_two = 0;
-        let mut k = size;
-        while k != 1 {
-            k >>= 1;
-            power_of_two += 1;
-        }
+        let requested = size;
+        let size = size.checked_next_power_of_two().ok_or(SynthesisError::DomainTooLarge(requested))?;
+        let power_of_two = size.trailing_zeros() as u64;
         let max_power_of_two = F::S as u64;
         if power_of_two > max_power_of_two {
-            return Err(SynthesisError::Error);
+            return Err(SynthesisError::DomainTooLarge(requested));
         }
 
         let mut generator = F::ROOT_OF_UNITY;
@@ -52,10 +63,334 @@ impl<F: PrimeField> Domain<F> {
             generator = generator.square();
         }
 
-        Ok(Self {
+        Ok(Self::from_generator(size, generator))
+    }
+
+    // The subgroup of the given size generated by `generator`, which must have that order.
+    fn from_generator(size: u64, generator: F) -> Self {
+        Self {
             size,
-            power_of_two,
+            power_of_two: size.trailing_zeros() as u64,
             generator,
+            group_gen_inv: generator.invert().unwrap(),
+            size_inv: F::from_u128(size as u128).invert().unwrap(),
+            offset: F::ONE,
+            offset_inv: F::ONE,
+        }
+    }
+
+    // The smallest domain of size at least `size` of the form 2^k * 3^a * 5^b the field supports.
+    // Its generator raised to the odd part of the size is the generator of the power-of-two domain
+    // of size 2^k, and it is that generator when the size is a power of two.
+    pub fn new_for_smooth_size(size: u64) -> Result<Self, SynthesisError> {
+        let size = size.max(1);
+        let mut best: Option<(u64, u32)> = None;
+        // Odd parts up to 2 * size - 1, written as odd / 2 < size so that sizes from 2^63 on do not
+        // overflow. Candidates that do not fit in a u64 are skipped.
+        let mut odd = Some(1u64);
+        while let Some(current) = odd.filter(|o| o / 2 < size) {
+            let mut odd_five = Some(current);
+            while let Some(current_five) = odd_five.filter(|o| o / 2 < size) {
+                let power_of_two = size.div_ceil(current_five).checked_next_power_of_two().map(|p| p.trailing_zeros());
+                let candidate = power_of_two.and_then(|p| current_five.checked_mul(1 << p));
+                if let (Some(power_of_two), Some(candidate)) = (power_of_two, candidate) {
+                    if power_of_two <= F::S && best.is_none_or(|(n, _)| candidate < n) && Self::odd_root(current_five).is_some() {
+                        best = Some((candidate, power_of_two));
+                    }
+                }
+                odd_five = current_five.checked_mul(5);
+            }
+            odd = current.checked_mul(3);
+        }
+
+        let (size, power_of_two) = best.ok_or(SynthesisError::DomainTooLarge(size))?;
+        let odd = size >> power_of_two;
+        let root_two = Self::root_with_order_unchecked(1 << power_of_two);
+        // Inverse of the odd part modulo 2^64 by Newton's iteration, which is also its inverse
+        // modulo 2^power_of_two
+        let mut inverse = odd;
+        for _ in 0..5 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(odd.wrapping_mul(inverse)));
+        }
+        let exponent = if power_of_two == 0 { 0 } else { inverse & (u64::MAX >> (64 - power_of_two)) };
+
+        Ok(Self::from_generator(size, root_two.pow([exponent]) * Self::odd_root(odd).unwrap()))
+    }
+
+    // g^((p - 1)/order) for g the multiplicative generator, if the order divides p - 1.
+    fn odd_root(order: u64) -> Option<F> {
+        // p - 1 in little-endian bytes, divided by the order from the most significant byte on
+        let group_order = (-F::ONE).to_repr();
+        let mut quotient = group_order.as_ref().to_vec();
+        let mut remainder = 0u128;
+        for byte in quotient.iter_mut().rev() {
+            let current = (remainder << 8) | *byte as u128;
+            *byte = (current / order as u128) as u8;
+            remainder = current % order as u128;
+        }
+        if remainder != 0 {
+            return None;
+        }
+
+        let exponent = quotient
+            .chunks(8)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(bytes)
+            })
+            .collect::<Vec<_>>();
+        Some(F::MULTIPLICATIVE_GENERATOR.pow(exponent))
+    }
+
+    // The same subgroup shifted by `offset`, DivisionByZero if the offset is zero.
+    pub fn with_offset(&self, offset: F) -> Result<Self, SynthesisError> {
+        let offset_inv = Option::from(offset.invert()).ok_or(SynthesisError::DivisionByZero)?;
+        Ok(Self {
+            offset,
+            offset_inv,
+            ..*self
         })
     }
+
+    // The domain of size `size / factor` generated by generator^factor, with the same offset.
+    pub fn subdomain(&self, factor: u64) -> Result<Self, SynthesisError> {
+        if factor == 0 || !self.size.is_multiple_of(factor) {
+            return Err(SynthesisError::UnsupportedFactor(factor));
+        }
+        Self::from_generator(self.size / factor, self.generator.pow([factor])).with_offset(self.offset)
+    }
+
+    // The domain of size `size * factor` whose generator raised to `factor` is this domain's
+    // generator, with the same offset. This always exists for power-of-two domains and factors
+    // the field supports.
+    pub fn superdomain(&self, factor: u64) -> Result<Self, SynthesisError> {
+        let size = self.size.checked_mul(factor).ok_or(SynthesisError::DomainTooLarge(u64::MAX))?;
+        let domain = Self::new_for_smooth_size(size)?;
+        if domain.size != size || domain.generator.pow([factor]) != self.generator {
+            return Err(SynthesisError::UnsupportedDomainSize(size));
+        }
+        domain.with_offset(self.offset)
+    }
+
+    // offset * generator^i
+    pub fn element(&self, i: u64) -> F {
+        self.offset * self.generator.pow([i % self.size])
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = F> {
+        let generator = self.generator;
+        (0..self.size).scan(self.offset, move |x, _| {
+            let current = *x;
+            *x *= generator;
+            Some(current)
+        })
+    }
+
+    // x^size - offset^size, zero exactly on the domain.
+    pub fn evaluate_vanishing(&self, x: F) -> F {
+        x.pow([self.size]) - self.offset.pow([self.size])
+    }
+
+    // L_0(x), ..., L_(size-1)(x) for L_i the Lagrange polynomial that is one at x_i and zero on the
+    // rest of the domain. Off the domain, with Z the vanishing polynomial,
+    //
+    //     L_i(x) = Z(x) / (Z'(x_i) * (x - x_i)) = Z(x) * x_i / (size * offset^size * (x - x_i))
+    pub fn evaluate_all_lagrange_coefficients(&self, x: F) -> Vec<F> {
+        let vanishing = self.evaluate_vanishing(x);
+        if bool::from(vanishing.is_zero()) {
+            return self.elements().map(|e| if e == x { F::ONE } else { F::ZERO }).collect();
+        }
+
+        let differences = self.elements().map(|e| x - e).collect::<Vec<F>>();
+        let inverses = batch_inverse(&differences).expect("x is not in the domain");
+        let factor = vanishing * self.size_inv * self.offset_inv.pow([self.size]);
+        self.elements().zip(inverses).map(|(e, inverse)| factor * e * inverse).collect()
+    }
+
+    // L_0(x), the Lagrange polynomial that is one at the offset.
+    pub fn first_lagrange(&self, x: F) -> F {
+        if x == self.offset {
+            return F::ONE;
+        }
+        let vanishing = self.evaluate_vanishing(x);
+        if bool::from(vanishing.is_zero()) {
+            return F::ZERO;
+        }
+        vanishing * self.offset * self.size_inv * self.offset_inv.pow([self.size]) * (x - self.offset).invert().unwrap()
+    }
+
+    // Coefficients to evaluations at the elements of the domain. The input must have the size of
+    // the domain.
+    pub fn fft(&self, a: &mut [F]) -> Result<(), SynthesisError> {
+        self.check_len(a.len())?;
+        if self.offset != F::ONE {
+            scale_by_powers(a, self.offset);
+        }
+        if self.size.is_power_of_two() {
+            self.plan().fft(a);
+        } else {
+            mixed_radix::mixed_radix_fft(a, &self.generator);
+        }
+        Ok(())
+    }
+
+    pub fn ifft(&self, a: &mut [F]) -> Result<(), SynthesisError> {
+        self.check_len(a.len())?;
+        if self.size.is_power_of_two() {
+            self.plan().ifft(a);
+        } else {
+            mixed_radix::mixed_radix_ifft(a, &self.generator);
+        }
+        if self.offset != F::ONE {
+            scale_by_powers(a, self.offset_inv);
+        }
+        Ok(())
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), SynthesisError> {
+        if len as u64 != self.size {
+            return Err(SynthesisError::LengthMismatch(self.size as usize, len));
+        }
+        Ok(())
+    }
+
+    fn plan(&self) -> Arc<FftPlan<F>> {
+        FftPlan::for_size(self.size as usize).expect("The field has roots of unity for every power-of-two domain.")
+    }
+}
+
+#[cfg(test)]
+mod domain_tests {
+
+    use super::*;
+    use ff::Field;
+    use crate::{
+        field::{goldilocks::Goldilocks, babybear::BabyBear, Fp},
+        fft::recursive::naive_dft,
+        polynomial::Polynomial,
+    };
+
+    #[test]
+    fn smooth_domains() {
+        // p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537
+        for (size, expected) in [(1, 1), (3, 3), (7, 8), (9, 10), (17, 20), (31, 32), (33, 40), (100, 120), (1000, 1024)] {
+            let domain = Domain::<Goldilocks>::new_for_smooth_size(size).unwrap();
+            assert_eq!(domain.size, expected);
+            assert_eq!(domain.generator.pow([domain.size]), Goldilocks::ONE);
+            for prime in [2, 3, 5] {
+                if domain.size.is_multiple_of(prime) {
+                    assert_ne!(domain.generator.pow([domain.size / prime]), Goldilocks::ONE);
+                }
+            }
+            let odd = domain.size >> domain.power_of_two;
+            assert_eq!(domain.generator.pow([odd]), Domain::root_with_order_unchecked(1 << domain.power_of_two));
+        }
+        assert_eq!(Domain::<Goldilocks>::new_for_smooth_size(64).unwrap(), Domain::new_for_size(64).unwrap());
+        assert_eq!(Domain::<BabyBear>::new_for_smooth_size(50).unwrap().size, 60);
+
+        // p - 1 has a factor of 5 but none of 3 for Fp
+        assert_eq!(Domain::<Fp>::new_for_smooth_size(3).unwrap().size, 4);
+        assert_eq!(Domain::<Fp>::new_for_smooth_size(17).unwrap().size, 20);
+
+        // Sizes where 2 * size overflows a u64
+        for size in [1 << 63, (1 << 63) + 1, u64::MAX] {
+            assert_eq!(Domain::<Goldilocks>::new_for_smooth_size(size), Err(SynthesisError::DomainTooLarge(size)));
+        }
+    }
+
+    fn check_ffts<F: PrimeField>(sizes: &[u64]) {
+        let rng = rand::thread_rng();
+        for &size in sizes {
+            let domain = Domain::<F>::new_for_smooth_size(size).unwrap();
+            assert_eq!(domain.size, size);
+            let a = (0..size).map(|_| F::random(rng.clone())).collect::<Vec<_>>();
+            let mut result = a.clone();
+            domain.fft(result.as_mut_slice()).unwrap();
+            assert_eq!(result, naive_dft(&a, &domain.generator), "size {}", size);
+            domain.ifft(result.as_mut_slice()).unwrap();
+            assert_eq!(result, a);
+        }
+    }
+
+    #[test]
+    fn cosets_and_lagrange() {
+        let rng = rand::thread_rng();
+        let shift = Goldilocks::MULTIPLICATIVE_GENERATOR;
+        let domain = Domain::<Goldilocks>::new_for_size(16).unwrap();
+        let coset = domain.with_offset(shift).unwrap();
+
+        assert_eq!(domain.generator * domain.group_gen_inv, Goldilocks::ONE);
+        assert_eq!(domain.size_inv * Goldilocks::from(16), Goldilocks::ONE);
+        for d in [domain, coset] {
+            let elements = d.elements().collect::<Vec<_>>();
+            assert_eq!(elements.len(), 16);
+            for (i, e) in elements.iter().enumerate() {
+                assert_eq!(*e, d.element(i as u64));
+                assert_eq!(d.evaluate_vanishing(*e), Goldilocks::ZERO);
+            }
+            assert_ne!(d.evaluate_vanishing(d.offset * shift), Goldilocks::ZERO);
+
+            // sum_i L_i(x) y_i interpolates the y_i, at points on and off the domain
+            let coefficients = (0..16).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+            let f_x = Polynomial::from_vec(coefficients.clone());
+            let mut evaluations = coefficients;
+            d.fft(evaluations.as_mut_slice()).unwrap();
+            assert_eq!(evaluations, elements.iter().map(|e| f_x.eval_single(e)).collect::<Vec<_>>());
+            for x in [Goldilocks::random(rng.clone()), elements[3]] {
+                let lagrange = d.evaluate_all_lagrange_coefficients(x);
+                let interpolated = lagrange.iter().zip(&evaluations).fold(Goldilocks::ZERO, |acc, (l, y)| acc + *l * y);
+                assert_eq!(interpolated, f_x.eval_single(&x));
+                assert_eq!(d.first_lagrange(x), lagrange[0]);
+            }
+            d.ifft(evaluations.as_mut_slice()).unwrap();
+            assert_eq!(Polynomial::from_vec(evaluations), f_x);
+        }
+
+        let sub = coset.subdomain(4).unwrap();
+        assert_eq!((sub.size, sub.power_of_two, sub.offset), (4, 2, shift));
+        assert!(sub.elements().all(|e| coset.elements().any(|c| c == e)));
+        let sup = domain.superdomain(4).unwrap();
+        assert_eq!(sup, Domain::new_for_size(64).unwrap());
+        assert_eq!(sup.subdomain(4).unwrap(), domain);
+        assert_eq!(domain.subdomain(3), Err(SynthesisError::UnsupportedFactor(3)));
+        assert_eq!(domain.subdomain(0), Err(SynthesisError::UnsupportedFactor(0)));
+        assert_eq!(Domain::<Goldilocks>::new_for_size(1 << 33), Err(SynthesisError::DomainTooLarge(1 << 33)));
+        // Sizes above 2^63 have no power of two in a u64 to round up to
+        for size in [(1 << 63) + 1, u64::MAX] {
+            assert_eq!(Domain::<Goldilocks>::new_for_size(size), Err(SynthesisError::DomainTooLarge(size)));
+        }
+
+        // Inputs the domain cannot take are errors, not panics
+        assert_eq!(domain.with_offset(Goldilocks::ZERO), Err(SynthesisError::DivisionByZero));
+        let mut short = vec![Goldilocks::ONE; 8];
+        assert_eq!(domain.fft(short.as_mut_slice()), Err(SynthesisError::LengthMismatch(16, 8)));
+        assert_eq!(coset.ifft(short.as_mut_slice()), Err(SynthesisError::LengthMismatch(16, 8)));
+    }
+
+    #[test]
+    fn mixed_radix_ffts() {
+        check_ffts::<Goldilocks>(&[1, 2, 3, 5, 6, 10, 15, 16, 24, 30, 40, 60, 96, 15 << 7]);
+        check_ffts::<BabyBear>(&[3, 5, 15, 30, 120]);
+        check_ffts::<Fp>(&[5, 20, 80]);
+    }
+
+    #[test]
+    fn goldilocks_generator_and_roots() {
+        // p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537, so g generates the multiplicative group if and
+        // only if g^((p - 1)/q) is not one for any of these primes q
+        let p_minus_one: u64 = 0xffff_ffff_0000_0000;
+        let g = Goldilocks::MULTIPLICATIVE_GENERATOR;
+        assert_eq!(g, Goldilocks::from(7));
+        for q in [2, 3, 5, 17, 257, 65537] {
+            assert_ne!(g.pow([p_minus_one / q]), Goldilocks::ONE);
+        }
+
+        // The root for order 2^k has order exactly 2^k
+        for log_n in 1..=Goldilocks::S {
+            let root = Domain::<Goldilocks>::root_with_order_unchecked(1 << log_n);
+            assert_eq!(root.pow([1u64 << log_n]), Goldilocks::ONE);
+            assert_ne!(root.pow([1u64 << (log_n - 1)]), Goldilocks::ONE);
+        }
+    }
 }
@@ -0,0 +1,131 @@
+use core::fmt;
+
+use crate::plonk::Protocol;
+
+/*
+ * Errors of every fallible operation in the crate. Anything a caller can cause with its inputs
+ * (sizes the field has no domain for, polynomials of the wrong length, zero divisors, points that
+ * are not where they should be, proofs that do not parse) is reported through this type instead
+ * of a panic. Panics are left for broken internal invariants.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SynthesisError {
+    // The field has no subgroup of (at least) the requested size
+    DomainTooLarge(u64),
+    // The field has no domain of exactly this size, e.g. a superdomain that does not nest
+    UnsupportedDomainSize(u64),
+    // A subdomain was requested with a factor that does not divide the domain size
+    UnsupportedFactor(u64),
+    // An FFT-based operation got a length that is not a power of two
+    NonPowerOfTwo(usize),
+    // Polynomials that have to share a domain have different lengths, expected and found
+    LengthMismatch(usize, usize),
+    // Evaluations over different domains were combined
+    DomainMismatch,
+    // A polynomial has more coefficients than the scheme was set up for, found and maximum
+    PolynomialTooLarge(usize, usize),
+    DivisionByZero,
+    // A point was expected to lie in an evaluation domain but does not
+    PointNotInDomain,
+    // Parameters that no protocol can run with, e.g. a FRI folding arity below two
+    InvalidConfiguration(&'static str),
+    // A batch operation was given nothing to work on
+    EmptyBatch,
+    // A proof that was rejected, with the check that failed
+    VerificationFailed(RejectionReason),
+}
+
+impl fmt::Display for SynthesisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SynthesisError::DomainTooLarge(size) => write!(f, "the field has no domain of size {}", size),
+            SynthesisError::UnsupportedDomainSize(size) => write!(f, "no domain of size {} is supported", size),
+            SynthesisError::UnsupportedFactor(factor) => write!(f, "the domain size is not divisible by {}", factor),
+            SynthesisError::NonPowerOfTwo(len) => write!(f, "length {} is not a power of two", len),
+            SynthesisError::LengthMismatch(expected, found) => write!(f, "expected length {}, found {}", expected, found),
+            SynthesisError::DomainMismatch => write!(f, "evaluations over different domains"),
+            SynthesisError::PolynomialTooLarge(len, max) => write!(f, "polynomial has {} coefficients, at most {} are supported", len, max),
+            SynthesisError::DivisionByZero => write!(f, "division by zero"),
+            SynthesisError::PointNotInDomain => write!(f, "point is not in the evaluation domain"),
+            SynthesisError::InvalidConfiguration(reason) => write!(f, "invalid configuration: {}", reason),
+            SynthesisError::EmptyBatch => write!(f, "empty batch"),
+            SynthesisError::VerificationFailed(reason) => write!(f, "verification failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SynthesisError {}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VerificationResult {
+    ValidProof,
+    InvalidProof(RejectionReason),
+}
+
+// The first check a rejected proof failed. Layers count the Merkle trees of a FRI proof, layer 0
+// being the committed polynomial and layer i + 1 its i-th fold.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RejectionReason {
+    // The parts of the proof do not fit together, e.g. lists of different lengths
+    MalformedProof(&'static str),
+    // An authentication path does not lead to the committed root
+    MerkleRootMismatch { layer: usize },
+    // A queried value is not in the leaf opened for it
+    QueryValueMismatch { layer: usize },
+    // Folding the queried values does not end in the committed constant function. This also
+    // rejects a last layer of too high a degree: the verifier recomputes the root of the constant
+    // function, which a non-constant last layer cannot match.
+    FoldInconsistency,
+    // The opening proof for the index-th point or commitment does not check out
+    InvalidOpening { index: usize },
+    // The index-th opened value differs from the claimed evaluation
+    EvaluationMismatch { index: usize },
+    // The evaluations of a batch do not combine to the one the batched proof opened
+    BatchingInconsistency,
+    // A claimed evaluation of a protocol, named after its field in the proof, is not backed by
+    // its commitment
+    InvalidEvaluation { protocol: Protocol, evaluation: &'static str },
+    // The accumulator of a product check does not end at one
+    ProductNotOne(Protocol),
+    // The evaluations do not satisfy the identity the protocol checks at the random point
+    IdentityCheckFailed(Protocol),
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::MalformedProof(reason) => write!(f, "malformed proof: {}", reason),
+            RejectionReason::MerkleRootMismatch { layer } => write!(f, "Merkle root mismatch at layer {}", layer),
+            RejectionReason::QueryValueMismatch { layer } => write!(f, "query value mismatch at layer {}", layer),
+            RejectionReason::FoldInconsistency => write!(f, "fold does not end in the committed constant"),
+            RejectionReason::InvalidOpening { index } => write!(f, "invalid opening {}", index),
+            RejectionReason::EvaluationMismatch { index } => write!(f, "evaluation {} does not match", index),
+            RejectionReason::BatchingInconsistency => write!(f, "batched evaluations do not match the combined proof"),
+            RejectionReason::InvalidEvaluation { protocol, evaluation } => write!(f, "{:?}: invalid evaluation {}", protocol, evaluation),
+            RejectionReason::ProductNotOne(protocol) => write!(f, "{:?}: product is not one", protocol),
+            RejectionReason::IdentityCheckFailed(protocol) => write!(f, "{:?}: identity check failed", protocol),
+        }
+    }
+}
+
+impl VerificationResult {
+    pub fn is_valid(&self) -> bool {
+        *self == VerificationResult::ValidProof
+    }
+
+    pub fn rejection_reason(&self) -> Option<RejectionReason> {
+        match self {
+            VerificationResult::ValidProof => None,
+            VerificationResult::InvalidProof(reason) => Some(*reason),
+        }
+    }
+
+    // For callers that propagate rejections with `?`.
+    pub fn into_result(self) -> Result<(), SynthesisError> {
+        match self {
+            VerificationResult::ValidProof => Ok(()),
+            VerificationResult::InvalidProof(reason) => Err(SynthesisError::VerificationFailed(reason)),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::thread;
+
+use ff::PrimeField;
+
+use super::{four_step::transpose, plan::{FftPlan, available_threads}};
+use crate::constants::MT_FFT_THRESHOLD;
+
+/*
+ * FFTs of many polynomials of the same size, e.g. the wire, sigma and selector columns of a
+ * circuit or the columns of a trace. The polynomials come as one matrix with a column per
+ * polynomial, stored either way, and the evaluations come back row-major, so that row i holds
+ * every polynomial evaluated at w^i and can be hashed as a Merkle leaf as it is.
+ *
+ * All columns share the twiddles of one plan. Columns below MT_FFT_THRESHOLD are spread across
+ * threads, larger ones are transformed one after another, each split across threads itself.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixLayout {
+    // Column j is matrix[j*n..(j+1)*n]
+    ColumnMajor,
+    // Row i is matrix[i*width..(i+1)*width]
+    RowMajor,
+}
+
+pub(crate) fn batch_fft<F: PrimeField>(plan: &FftPlan<F>, matrix: &[F], width: usize, layout: MatrixLayout) -> Vec<F> {
+    let n = plan.size();
+    assert_eq!(matrix.len(), n * width, "Matrix must have one column of size {} per polynomial.", n);
+    if width == 0 {
+        return vec![];
+    }
+
+    let mut columns = match layout {
+        MatrixLayout::ColumnMajor => matrix.to_vec(),
+        MatrixLayout::RowMajor => {
+            let mut columns = vec![F::ZERO; matrix.len()];
+            transpose(matrix, &mut columns, n, width);
+            columns
+        }
+    };
+
+    if n >= MT_FFT_THRESHOLD {
+        for column in columns.chunks_exact_mut(n) {
+            plan.fft(column);
+        }
+    } else {
+        let columns_per_thread = width.div_ceil(available_threads() as usize);
+        thread::scope(|scope| {
+            for chunk in columns.chunks_mut(columns_per_thread * n) {
+                scope.spawn(move || {
+                    for column in chunk.chunks_exact_mut(n) {
+                        plan.fft(column);
+                    }
+                });
+            }
+        });
+    }
+
+    let mut rows = vec![F::ZERO; columns.len()];
+    transpose(&columns, &mut rows, width, n);
+    rows
+}
@@ -0,0 +1,90 @@
+use ff::PrimeField;
+
+use std::sync::Arc;
+
+use super::FftPlan;
+use crate::{polynomial::Polynomial, error::SynthesisError};
+
+/*
+ * Transforms over a coset shift * H of the subgroup H of size n. With f_i the coefficients of f,
+ *
+ *     f(shift * w^j) = sum_i (f_i * shift^i) * w^(ij)
+ *
+ * so the coset FFT is the subgroup FFT of the coefficients scaled by the powers of the shift, and
+ * the coset IFFT undoes the scaling after the subgroup IFFT. Evaluating on a coset that misses H
+ * keeps the vanishing polynomial x^n - 1 of H away from zero, which is what quotients need.
+*/
+
+// The plan for transforms of length `len`, NonPowerOfTwo or DomainTooLarge if the field has no
+// subgroup of that size, and DivisionByZero for a zero shift, which is not a coset. Checked
+// before anything is written.
+fn plan_for<F: PrimeField>(len: usize, shift: F) -> Result<Arc<FftPlan<F>>, SynthesisError> {
+    if bool::from(shift.is_zero()) {
+        return Err(SynthesisError::DivisionByZero);
+    }
+    FftPlan::for_size(len)
+}
+
+pub(crate) fn scale_by_powers<F: PrimeField>(a: &mut [F], shift: F) {
+    let mut power = F::ONE;
+    for item in a.iter_mut() {
+        *item *= power;
+        power *= shift;
+    }
+}
+
+// Coefficients to evaluations at shift * w^0, ..., shift * w^(n-1).
+pub fn coset_fft<F: PrimeField>(a: &mut [F], shift: F) -> Result<(), SynthesisError> {
+    let plan = plan_for(a.len(), shift)?;
+    if shift != F::ONE {
+        scale_by_powers(a, shift);
+    }
+    plan.fft(a);
+    Ok(())
+}
+
+pub fn coset_ifft<F: PrimeField>(a: &mut [F], shift: F) -> Result<(), SynthesisError> {
+    let plan = plan_for(a.len(), shift)?;
+    plan.ifft(a);
+    if shift != F::ONE {
+        scale_by_powers(a, shift.invert().unwrap());
+    }
+    Ok(())
+}
+
+// As above with the evaluations in bit-reversed order, element i being the evaluation at
+// shift * w^bitreverse(i).
+pub fn coset_fft_bit_reversed<F: PrimeField>(a: &mut [F], shift: F) -> Result<(), SynthesisError> {
+    let plan = plan_for(a.len(), shift)?;
+    if shift != F::ONE {
+        scale_by_powers(a, shift);
+    }
+    plan.fft_bit_reversed(a);
+    Ok(())
+}
+
+pub fn coset_ifft_bit_reversed<F: PrimeField>(a: &mut [F], shift: F) -> Result<(), SynthesisError> {
+    let plan = plan_for(a.len(), shift)?;
+    plan.ifft_bit_reversed(a);
+    if shift != F::ONE {
+        scale_by_powers(a, shift.invert().unwrap());
+    }
+    Ok(())
+}
+
+// Low degree extension: the evaluations of the polynomial over shift * H for the subgroup H of
+// size blowup times its (power of two) length. NonPowerOfTwo for any other blowup.
+pub fn lde<F: PrimeField>(poly: &Polynomial<F>, blowup: usize, shift: F) -> Result<Vec<F>, SynthesisError> {
+    if !blowup.is_power_of_two() {
+        return Err(SynthesisError::NonPowerOfTwo(blowup));
+    }
+    let size = poly.len().next_power_of_two().checked_mul(blowup).ok_or(SynthesisError::DomainTooLarge(u64::MAX))?;
+    let plan = plan_for(size, shift)?;
+    let mut evaluations = poly.coefficients();
+    evaluations.resize(size, F::ZERO);
+    if shift != F::ONE {
+        scale_by_powers(evaluations.as_mut_slice(), shift);
+    }
+    plan.fft(evaluations.as_mut_slice());
+    Ok(evaluations)
+}
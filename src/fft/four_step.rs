@@ -0,0 +1,99 @@
+use std::thread;
+
+use ff::PrimeField;
+
+use super::serial::{serial_fft_with_twiddles, layer_twiddles};
+
+/*
+ * The four-step (Bailey) FFT for inputs too large for the butterfly network to stay in cache.
+ * With n = n1 * n2, input index j = j1 + n1 * j2 and output index k = k2 + n2 * k1,
+ *
+ *     X[k2 + n2 k1] = sum_j1 w_n1^(j1 k1) * w_n^(j1 k2) * sum_j2 a[j1 + n1 j2] * w_n2^(j2 k2)
+ *
+ * so the transform is n1 FFTs of size n2, a multiplication by the twiddles w_n^(j1 k2) and n2 FFTs
+ * of size n1. Every sub-FFT is about sqrt(n) elements and runs in cache. The input is transposed
+ * so that the inner sums are contiguous rows, and transposed again between and after the two
+ * rounds of row FFTs (the "six-step" layout), the rows of each round being split across threads.
+ *
+ * No twiddles are computed per transform. The layer twiddles of w_m for m < n are the first m - 1
+ * twiddles of w_n, since layer i of both holds the powers of the same 2^(i+1)-th root of unity,
+ * and the last layer of w_n holds w_n^e for e < n/2, which with w_n^(n/2) = -1 gives every
+ * w_n^(j1 k2).
+*/
+
+// Side of the square tiles the transpositions copy at a time
+const TILE: usize = 32;
+
+pub fn four_step_fft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32, threads: u16) {
+    four_step_fft_with_twiddles(a, &layer_twiddles(omega, log_n), log_n, threads);
+}
+
+// The four-step FFT with the layer twiddles of w_n, e.g. those of an FftPlan.
+pub(crate) fn four_step_fft_with_twiddles<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32, threads: u16) {
+    assert_eq!(a.len(), 1 << log_n);
+    if log_n == 0 { return; }
+
+    let log_n1 = log_n / 2;
+    let log_n2 = log_n - log_n1;
+    let (n1, n2) = (1 << log_n1, 1 << log_n2);
+    let threads = threads.max(1) as usize;
+
+    // Row j1 holds a[j1 + n1 j2] for every j2
+    let mut rows = vec![F::ZERO; a.len()];
+    transpose(a, &mut rows, n2, n1);
+
+    // w_n^e for e < n/2
+    let half = a.len() / 2;
+    let powers = &twiddles[half - 1..];
+    row_ffts(&mut rows, &twiddles[..n2 - 1], log_n2, threads, |j1, row| {
+        for (k2, item) in row.iter_mut().enumerate() {
+            let e = j1 * k2;
+            *item *= if e < half { powers[e] } else { -powers[e - half] };
+        }
+    });
+
+    transpose(&rows, a, n1, n2);
+    row_ffts(a, &twiddles[..n1 - 1], log_n1, threads, |_, _| ());
+
+    // Row k2 now holds X[k2 + n2 k1] for every k1
+    transpose(a, &mut rows, n2, n1);
+    a.copy_from_slice(&rows);
+}
+
+pub fn four_step_ifft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32, threads: u16) {
+    let invlen = F::from_u128(a.len() as u128).invert().unwrap();
+    four_step_fft(a, &omega.invert().unwrap(), log_n, threads);
+    for item in a.iter_mut() {
+        *item *= invlen;
+    }
+}
+
+// FFTs of every row of size 2^log_len, each followed by `post(row index, row)`.
+fn row_ffts<F: PrimeField>(a: &mut [F], twiddles: &[F], log_len: u32, threads: usize, post: impl Fn(usize, &mut [F]) + Sync) {
+    let len = 1 << log_len;
+    let rows_per_thread = (a.len() / len).div_ceil(threads);
+    let (twiddles, post) = (twiddles, &post);
+    thread::scope(|scope| {
+        for (chunk_index, chunk) in a.chunks_mut(rows_per_thread * len).enumerate() {
+            scope.spawn(move || {
+                for (i, row) in chunk.chunks_exact_mut(len).enumerate() {
+                    serial_fft_with_twiddles(row, twiddles, log_len);
+                    post(chunk_index * rows_per_thread + i, row);
+                }
+            });
+        }
+    });
+}
+
+// dst = src^T for src a row-major matrix with the given number of rows and columns.
+pub(crate) fn transpose<F: Copy>(src: &[F], dst: &mut [F], rows: usize, cols: usize) {
+    for r0 in (0..rows).step_by(TILE) {
+        for c0 in (0..cols).step_by(TILE) {
+            for r in r0..(r0 + TILE).min(rows) {
+                for c in c0..(c0 + TILE).min(cols) {
+                    dst[c * rows + r] = src[r * cols + c];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use ff::PrimeField;
+
+use super::serial::serial_fft;
+
+/*
+ * FFTs over domains of size n = 3^a * 5^b * 2^k, for fields whose multiplicative group has the odd
+ * factors (Goldilocks and BabyBear have 3 * 5, a single factor of each). The odd radices are
+ * split off first, decimating in time: with r the radix, m = n / r and A_j the FFT of size m of
+ * a[j], a[j + r], a[j + 2r], ...,
+ *
+ *     X[k] = sum_{j < r} (w^k)^j * A_j[k mod m]
+ *
+ * which costs r multiplications per output and layer. What remains is a power of two, handed to
+ * the radix-2 FFT with the root w^(n / 2^k).
+*/
+
+// The radices of n, odd ones first, or None if n has a prime factor other than 2, 3 and 5.
+pub fn radices(n: usize) -> Option<(Vec<usize>, u32)> {
+    if n == 0 {
+        return None;
+    }
+    let mut odd = Vec::new();
+    let mut m = n;
+    for r in [5, 3] {
+        while m.is_multiple_of(r) {
+            odd.push(r);
+            m /= r;
+        }
+    }
+    m.is_power_of_two().then(|| (odd, m.trailing_zeros()))
+}
+
+// Evaluations at w^0, ..., w^(n-1) for w of order n = a.len().
+pub fn mixed_radix_fft<F: PrimeField>(a: &mut [F], omega: &F) {
+    let (odd, log_two) = radices(a.len()).expect("Size must be of the form 2^k * 3^a * 5^b.");
+    let result = fft_recursive(a, omega, &odd, log_two);
+    a.copy_from_slice(&result);
+}
+
+pub fn mixed_radix_ifft<F: PrimeField>(a: &mut [F], omega: &F) {
+    let invlen = F::from_u128(a.len() as u128).invert().unwrap();
+    mixed_radix_fft(a, &omega.invert().unwrap());
+    for item in a.iter_mut() {
+        *item *= invlen;
+    }
+}
+
+fn fft_recursive<F: PrimeField>(a: &[F], omega: &F, odd: &[usize], log_two: u32) -> Vec<F> {
+    let Some((&r, rest)) = odd.split_first() else {
+        let mut result = a.to_vec();
+        serial_fft(result.as_mut_slice(), omega, log_two);
+        return result;
+    };
+
+    let n = a.len();
+    let m = n / r;
+    let omega_r = omega.pow([r as u64]);
+    let parts = (0..r)
+        .map(|j| {
+            let part = a.iter().skip(j).step_by(r).copied().collect::<Vec<_>>();
+            fft_recursive(&part, &omega_r, rest, log_two)
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::with_capacity(n);
+    let mut w_k = F::ONE;
+    for k in 0..n {
+        // Horner's rule in w^k over the parts
+        let evaluation = parts.iter().rev().fold(F::ZERO, |acc, part| acc * w_k + part[k % m]);
+        result.push(evaluation);
+        w_k *= omega;
+    }
+    result
+}
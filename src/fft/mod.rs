@@ -1,16 +1,350 @@
-pub(crate) mod serial;
+pub mod serial;
+pub mod mt;
+pub mod plan;
+pub mod coset;
+pub mod radix4;
+pub mod four_step;
+pub mod batch;
+pub mod recursive;
+pub mod mixed_radix;
+
+pub use plan::{FftPlan, FftKernel};
+pub use batch::MatrixLayout;
+pub use coset::{coset_fft, coset_ifft, coset_fft_bit_reversed, coset_ifft_bit_reversed, lde};
+pub use serial::bitreverse_permutation;
 
 #[cfg(test)]
 mod fft_tests {
 
     use super::*;
-    use ff::Field;
+    use ff::{Field, PrimeField};
+    use std::sync::Arc;
     use crate::{
-        field::goldilocks::Goldilocks,
-        domains::Domain,
+        field::{goldilocks::Goldilocks, Fp},
+        polynomial::Polynomial,
+        domains::{Domain, SynthesisError},
         constants::*,
     };
 
+    fn check_against_evaluation<F: PrimeField>() {
+        let rng = rand::thread_rng();
+        for log_n in 0..8 {
+            let coefficients = (0..1 << log_n).map(|_| F::random(rng.clone())).collect::<Vec<_>>();
+            let f_x = Polynomial::from_vec(coefficients.clone());
+            let omega: F = Domain::root_with_order_unchecked(1 << log_n);
+
+            let mut evaluations = coefficients.clone();
+            serial::serial_fft(evaluations.as_mut_slice(), &omega, log_n);
+            for (i, evaluation) in evaluations.iter().enumerate() {
+                assert_eq!(*evaluation, f_x.eval_single(&omega.pow([i as u64])));
+            }
+
+            serial::serial_ifft(evaluations.as_mut_slice(), &omega, log_n);
+            assert_eq!(evaluations, coefficients);
+        }
+    }
+
+    #[test]
+    fn serial_fft_matches_evaluation() {
+        // Goldilocks runs on the packed butterflies, Fp element by element
+        check_against_evaluation::<Goldilocks>();
+        check_against_evaluation::<Fp>();
+    }
+
+    // The threaded FFT has to reproduce the serial one exactly, including the non-canonical
+    // representation of Goldilocks elements.
+    #[test]
+    fn mt_fft_matches_serial() {
+        let rng = rand::thread_rng();
+        for log_n in [9, 11, 13] {
+            let a = (0..1 << log_n).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+            let omega: Goldilocks = Domain::root_with_order_unchecked(1 << log_n);
+            let mut expected = a.clone();
+            serial::serial_fft(expected.as_mut_slice(), &omega, log_n);
+            for threads in [1, 2, 3, 4, 8, 16] {
+                let mut result = a.clone();
+                mt::mt_fft(result.as_mut_slice(), &omega, log_n, threads);
+                assert!(result.iter().zip(&expected).all(|(r, e)| r.to_noncanonical_u64() == e.to_noncanonical_u64()));
+                mt::mt_ifft(result.as_mut_slice(), &omega, log_n, threads);
+                assert_eq!(result, a);
+            }
+        }
+
+        let a = (0..1 << 12).map(|_| Fp::random(rng.clone())).collect::<Vec<_>>();
+        let omega: Fp = Domain::root_with_order_unchecked(1 << 12);
+        let mut expected = a.clone();
+        serial::serial_fft(expected.as_mut_slice(), &omega, 12);
+        let mut result = a;
+        mt::mt_fft(result.as_mut_slice(), &omega, 12, 4);
+        assert_eq!(result, expected);
+    }
+
+    type Transform<'a, F> = (&'static str, Box<dyn Fn(&mut [F]) + 'a>);
+
+    // Every FFT in the module against the recursive reference, itself checked against the naive
+    // DFT, for all sizes up to 2^12.
+    fn check_against_reference<F: PrimeField>() {
+        let rng = rand::thread_rng();
+        let shift = F::MULTIPLICATIVE_GENERATOR;
+        for log_n in 0..=12 {
+            let plan = FftPlan::<F>::for_log_size(log_n).unwrap();
+            let omega = plan.omega();
+            let a = (0..1 << log_n).map(|_| F::random(rng.clone())).collect::<Vec<_>>();
+            let expected = recursive::recursive_fft(a.clone(), &omega);
+            if log_n <= 8 {
+                assert_eq!(expected, recursive::naive_dft(&a, &omega), "size 2^{}", log_n);
+            }
+            assert_eq!(recursive::recursive_ifft(expected.clone(), &omega), a);
+
+            let transforms: Vec<Transform<F>> = vec![
+                ("serial", Box::new(|a| serial::serial_fft(a, &omega, log_n))),
+                ("mt", Box::new(|a| mt::mt_fft(a, &omega, log_n, 4))),
+                ("four-step", Box::new(|a| four_step::four_step_fft(a, &omega, log_n, 4))),
+                ("radix-2", Box::new(|a| plan.fft_with(a, FftKernel::Radix2))),
+                ("radix-4 DIT", Box::new(|a| plan.fft_with(a, FftKernel::Radix4Dit))),
+                ("radix-4 DIF", Box::new(|a| plan.fft_with(a, FftKernel::Radix4Dif))),
+                ("bit-reversed", Box::new(|a| {
+                    plan.fft_bit_reversed(a);
+                    bitreverse_permutation(a, log_n);
+                })),
+                ("batch", Box::new(|a| {
+                    let rows = plan.batch_fft(a, 1, MatrixLayout::ColumnMajor);
+                    a.copy_from_slice(&rows);
+                })),
+            ];
+            for (name, fft) in &transforms {
+                let mut result = a.clone();
+                fft(result.as_mut_slice());
+                assert_eq!(result, expected, "{}, size 2^{}", name, log_n);
+            }
+
+            let inverses: Vec<Transform<F>> = vec![
+                ("serial", Box::new(|a| serial::serial_ifft(a, &omega, log_n))),
+                ("mt", Box::new(|a| mt::mt_ifft(a, &omega, log_n, 4))),
+                ("four-step", Box::new(|a| four_step::four_step_ifft(a, &omega, log_n, 4))),
+                ("plan", Box::new(|a| plan.ifft(a))),
+                ("bit-reversed", Box::new(|a| {
+                    bitreverse_permutation(a, log_n);
+                    plan.ifft_bit_reversed(a);
+                })),
+            ];
+            for (name, ifft) in &inverses {
+                let mut result = expected.clone();
+                ifft(result.as_mut_slice());
+                assert_eq!(result, a, "{} inverse, size 2^{}", name, log_n);
+            }
+
+            // Evaluations over the coset are those of the polynomial with scaled coefficients
+            let mut power = F::ONE;
+            let scaled = a.iter().map(|c| {
+                let scaled = *c * power;
+                power *= shift;
+                scaled
+            }).collect::<Vec<_>>();
+            let mut result = a.clone();
+            coset_fft(result.as_mut_slice(), shift).unwrap();
+            assert_eq!(result, recursive::recursive_fft(scaled, &omega), "coset, size 2^{}", log_n);
+        }
+    }
+
+    #[test]
+    fn every_variant_matches_reference() {
+        check_against_reference::<Goldilocks>();
+        check_against_reference::<Fp>();
+    }
+
+    #[test]
+    fn plans() {
+        let rng = rand::thread_rng();
+        let plan = FftPlan::<Goldilocks>::for_size(1 << 6).unwrap();
+        assert!(Arc::ptr_eq(&plan, &FftPlan::for_log_size(6).unwrap()));
+        assert!(!Arc::ptr_eq(&plan, &FftPlan::for_log_size(7).unwrap()));
+        assert_eq!(plan.size(), 64);
+        assert_eq!(plan.omega(), Domain::root_with_order_unchecked(64));
+        assert_eq!(plan.omega() * plan.omega_inv(), Goldilocks::ONE);
+
+        let a = (0..64).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+        let mut expected = a.clone();
+        serial::serial_fft(expected.as_mut_slice(), &plan.omega(), 6);
+        let mut result = a.clone();
+        plan.fft(result.as_mut_slice());
+        assert!(result.iter().zip(&expected).all(|(r, e)| r.to_noncanonical_u64() == e.to_noncanonical_u64()));
+        plan.ifft(result.as_mut_slice());
+        assert_eq!(result, a);
+
+        // Plans of different fields with the same size are kept apart
+        let fp_plan = FftPlan::<Fp>::for_log_size(6).unwrap();
+        assert_eq!(fp_plan.omega(), Domain::root_with_order_unchecked(64));
+
+        // Large enough to take the threaded path
+        let plan = FftPlan::<Goldilocks>::for_size(MT_FFT_THRESHOLD).unwrap();
+        let a = (0..MT_FFT_THRESHOLD).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+        let mut result = a.clone();
+        plan.fft(result.as_mut_slice());
+        plan.ifft(result.as_mut_slice());
+        assert_eq!(result, a);
+
+        // Sizes without a plan are errors, not panics
+        assert_eq!(FftPlan::<Goldilocks>::new(33).unwrap_err(), SynthesisError::DomainTooLarge(1 << 33));
+        assert_eq!(FftPlan::<Goldilocks>::new(64).unwrap_err(), SynthesisError::DomainTooLarge(u64::MAX));
+        assert_eq!(FftPlan::<Goldilocks>::for_size(48).unwrap_err(), SynthesisError::NonPowerOfTwo(48));
+        assert_eq!(FftPlan::<Goldilocks>::for_size(0).unwrap_err(), SynthesisError::NonPowerOfTwo(0));
+    }
+
+    // Radix-4 DIT performs the radix-2 butterflies in another order, so its output has to be
+    // identical, DIF only has to agree on the values.
+    #[test]
+    fn radix4_kernels() {
+        fn check<F: PrimeField>(same_bits: impl Fn(&F, &F) -> bool, max_log_n: u32) {
+            let rng = rand::thread_rng();
+            for log_n in 0..=max_log_n {
+                let plan = FftPlan::<F>::for_log_size(log_n).unwrap();
+                let a = (0..1 << log_n).map(|_| F::random(rng.clone())).collect::<Vec<_>>();
+                let mut expected = a.clone();
+                serial::serial_fft(expected.as_mut_slice(), &plan.omega(), log_n);
+
+                let mut dit = a.clone();
+                plan.fft_with(dit.as_mut_slice(), FftKernel::Radix4Dit);
+                assert!(dit.iter().zip(&expected).all(|(x, y)| same_bits(x, y)), "size 2^{}", log_n);
+
+                let mut dif = a.clone();
+                plan.fft_with(dif.as_mut_slice(), FftKernel::Radix4Dif);
+                assert_eq!(dif, expected);
+
+                plan.ifft_with(dit.as_mut_slice(), FftKernel::Radix4Dif);
+                plan.ifft_with(dif.as_mut_slice(), FftKernel::Radix4Dit);
+                assert_eq!(dit, a);
+                assert_eq!(dif, a);
+            }
+        }
+
+        check::<Goldilocks>(|x, y| x.to_noncanonical_u64() == y.to_noncanonical_u64(), 14);
+        check::<Fp>(|x, y| x == y, 9);
+    }
+
+    #[test]
+    fn four_step_matches_serial() {
+        fn check<F: PrimeField>(max_log_n: u32) {
+            let rng = rand::thread_rng();
+            for log_n in 0..=max_log_n {
+                let plan = FftPlan::<F>::for_log_size(log_n).unwrap();
+                let a = (0..1 << log_n).map(|_| F::random(rng.clone())).collect::<Vec<_>>();
+                let mut expected = a.clone();
+                serial::serial_fft(expected.as_mut_slice(), &plan.omega(), log_n);
+
+                for threads in [1, 3, 8] {
+                    let mut result = a.clone();
+                    four_step::four_step_fft(result.as_mut_slice(), &plan.omega(), log_n, threads);
+                    assert_eq!(result, expected, "size 2^{}, {} threads", log_n, threads);
+                    four_step::four_step_ifft(result.as_mut_slice(), &plan.omega(), log_n, threads);
+                    assert_eq!(result, a);
+                }
+
+                let mut result = a.clone();
+                plan.fft_with(result.as_mut_slice(), FftKernel::FourStep);
+                assert_eq!(result, expected);
+            }
+        }
+
+        check::<Goldilocks>(15);
+        check::<Fp>(9);
+    }
+
+    #[test]
+    fn batch_fft() {
+        let rng = rand::thread_rng();
+        for (log_n, width) in [(0, 3), (4, 1), (6, 13), (MT_FFT_THRESHOLD.ilog2(), 2)] {
+            let plan = FftPlan::<Goldilocks>::for_log_size(log_n).unwrap();
+            let n = plan.size();
+            let columns = (0..width)
+                .map(|_| (0..n).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let expected = columns.iter().map(|c| {
+                let mut evaluations = c.clone();
+                plan.fft(evaluations.as_mut_slice());
+                evaluations
+            }).collect::<Vec<_>>();
+
+            let column_major = columns.concat();
+            let row_major = (0..n).flat_map(|i| columns.iter().map(move |c| c[i])).collect::<Vec<_>>();
+            for (matrix, layout) in [(column_major, MatrixLayout::ColumnMajor), (row_major, MatrixLayout::RowMajor)] {
+                let rows = plan.batch_fft(&matrix, width, layout);
+                for (i, row) in rows.chunks_exact(width).enumerate() {
+                    assert!(row.iter().zip(&expected).all(|(e, c)| *e == c[i]), "{:?}, row {}", layout, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bit_reversed_order() {
+        let rng = rand::thread_rng();
+        let shift = Goldilocks::MULTIPLICATIVE_GENERATOR;
+        for log_n in [0, 1, 5, 13] {
+            let plan = FftPlan::<Goldilocks>::for_log_size(log_n).unwrap();
+            let a = (0..1 << log_n).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+
+            let mut natural = a.clone();
+            plan.fft(natural.as_mut_slice());
+            let mut reversed = a.clone();
+            plan.fft_bit_reversed(reversed.as_mut_slice());
+            bitreverse_permutation(reversed.as_mut_slice(), log_n);
+            assert_eq!(reversed, natural);
+
+            // The pair round trips without any permutation
+            let mut evaluations = a.clone();
+            plan.fft_bit_reversed(evaluations.as_mut_slice());
+            plan.ifft_bit_reversed(evaluations.as_mut_slice());
+            assert_eq!(evaluations, a);
+
+            let mut natural = a.clone();
+            coset_fft(natural.as_mut_slice(), shift).unwrap();
+            let mut reversed = a.clone();
+            coset_fft_bit_reversed(reversed.as_mut_slice(), shift).unwrap();
+            bitreverse_permutation(reversed.as_mut_slice(), log_n);
+            assert_eq!(reversed, natural);
+
+            bitreverse_permutation(reversed.as_mut_slice(), log_n);
+            coset_ifft_bit_reversed(reversed.as_mut_slice(), shift).unwrap();
+            assert_eq!(reversed, a);
+        }
+    }
+
+    #[test]
+    fn coset_transforms() {
+        let rng = rand::thread_rng();
+        let coefficients = (0..16).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+        let f_x = Polynomial::from_vec(coefficients.clone());
+        let shift = Goldilocks::MULTIPLICATIVE_GENERATOR;
+
+        let mut evaluations = coefficients.clone();
+        coset_fft(evaluations.as_mut_slice(), shift).unwrap();
+        let omega: Goldilocks = Domain::root_with_order_unchecked(16);
+        for (i, evaluation) in evaluations.iter().enumerate() {
+            assert_eq!(*evaluation, f_x.eval_single(&(shift * omega.pow([i as u64]))));
+        }
+        coset_ifft(evaluations.as_mut_slice(), shift).unwrap();
+        assert_eq!(evaluations, coefficients);
+
+        let extension = lde(&f_x, 4, shift).unwrap();
+        assert_eq!(extension.len(), 64);
+        let omega: Goldilocks = Domain::root_with_order_unchecked(64);
+        assert_eq!(extension[37], f_x.eval_single(&(shift * omega.pow([37]))));
+        assert_eq!(lde(&f_x, 4, Goldilocks::ONE).unwrap()[37], f_x.eval_single(&omega.pow([37])));
+
+        // Lengths without a subgroup, a zero shift and a blowup that is not a power of two
+        let mut odd = vec![Goldilocks::ONE; 12];
+        assert_eq!(coset_fft(odd.as_mut_slice(), shift), Err(SynthesisError::NonPowerOfTwo(12)));
+        assert_eq!(coset_ifft_bit_reversed(odd.as_mut_slice(), shift), Err(SynthesisError::NonPowerOfTwo(12)));
+        assert_eq!(odd, vec![Goldilocks::ONE; 12]);
+        assert_eq!(coset_fft(evaluations.as_mut_slice(), Goldilocks::ZERO), Err(SynthesisError::DivisionByZero));
+        assert_eq!(coset_ifft(evaluations.as_mut_slice(), Goldilocks::ZERO), Err(SynthesisError::DivisionByZero));
+        assert_eq!(coset_fft_bit_reversed(evaluations.as_mut_slice(), Goldilocks::ZERO), Err(SynthesisError::DivisionByZero));
+        assert_eq!(evaluations, coefficients);
+        assert_eq!(lde(&f_x, 3, shift), Err(SynthesisError::NonPowerOfTwo(3)));
+        assert_eq!(lde(&f_x, 1 << 40, shift), Err(SynthesisError::DomainTooLarge(1 << 44)));
+    }
+
     #[test]
     #[ignore]
     fn benchmark() {
@@ -19,17 +353,18 @@ mod fft_tests {
         let size = FRI_BLOWUP_FACTOR * base;
 
         let omega = Domain::new_for_size(size as u64).unwrap().generator;
+        let log_size = size.ilog2();
 
         let rng = rand::thread_rng();
 
-        let mut a = (0..base).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
-        let _b = a.clone();
+        let mut a = (0..size).map(|_| Goldilocks::random(rng.clone())).collect::<Vec<_>>();
+        let b = a.clone();
         let now = std::time::Instant::now();
-        serial::serial_fft(a.as_mut_slice(), &omega, log_n);
+        serial::serial_fft(a.as_mut_slice(), &omega, log_size);
         let after = std::time::Instant::now();
         println!("Serial FFT took {:?}", after - now);
         let now = std::time::Instant::now();
-        // recursive::recursive_fft(_b, &omega);
+        recursive::recursive_fft(b, &omega);
         let after = std::time::Instant::now();
         println!("Recursive FFT took {:?}", after - now);
     }
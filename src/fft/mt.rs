@@ -1,13 +1,66 @@
+use std::thread;
+
 use ff::PrimeField;
 
-// TODO: Make multi-threaded FFT.
-pub(crate) fn mt_fft<F: PrimeField>(_s: Vec<F>, _g: &F, _threads: u16) -> Vec<F> {
-    unimplemented!();
+use super::serial::{serial_fft_with_twiddles, bitreverse_permutation, layer_twiddles, run_layers, run_block_part};
+
+/*
+ * The FFT split across threads. After the bit reversal, the first layers act on blocks no larger
+ * than n/threads, so every thread runs them on its own contiguous chunk of the input. In the
+ * remaining layers a block spans several chunks, and its butterflies are split up between the
+ * threads instead, every thread taking matching parts of the two halves of a block:
+ *
+ *     layer k < log(n/threads):   [ chunk 0 | chunk 1 | chunk 2 | chunk 3 ]
+ *     layer k >= log(n/threads):  [ lo 0 | lo 1 | hi 0 | hi 1 ][ lo 2 | lo 3 | hi 2 | hi 3 ]
+ *
+ * The twiddles and the arithmetic of every butterfly are the same as in serial_fft, so the output
+ * is identical to it. The number of threads is rounded down to a power of two, and every thread
+ * gets at least 2^LOG_MIN_CHUNK elements.
+*/
+
+const LOG_MIN_CHUNK: u32 = 10;
+
+pub fn mt_fft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32, threads: u16) {
+    mt_fft_with_twiddles(a, &layer_twiddles(omega, log_n), log_n, threads);
 }
 
-pub(crate) fn mt_ifft<F: PrimeField>(_s: Vec<F>, _g: &F, _threads: u16) -> Vec<F> {
-    unimplemented!();
-    // let invlen = F::from_u128(s.len() as u128).invert().unwrap();
-    // mt_fft(s, g, threads)
+pub(crate) fn mt_fft_with_twiddles<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32, threads: u16) {
+    assert_eq!(a.len(), 1 << log_n);
+
+    let log_threads = (threads.max(1) as u32).ilog2().min(log_n.saturating_sub(LOG_MIN_CHUNK));
+    if log_threads == 0 {
+        return serial_fft_with_twiddles(a, twiddles, log_n);
+    }
+
+    bitreverse_permutation(a, log_n);
+
+    let log_chunk = log_n - log_threads;
+    thread::scope(|scope| {
+        for chunk in a.chunks_mut(1 << log_chunk) {
+            scope.spawn(move || run_layers(chunk, twiddles, 0..log_chunk));
+        }
+    });
+
+    // Every thread takes half a chunk from each half of a block
+    let part = 1 << (log_chunk - 1);
+    for k in log_chunk..log_n {
+        let m = 1 << k;
+        let layer_twiddles = &twiddles[m-1..2*m-1];
+        thread::scope(|scope| {
+            for block in a.chunks_exact_mut(2*m) {
+                let (lo, hi) = block.split_at_mut(m);
+                for ((lo, hi), twiddles) in lo.chunks_mut(part).zip(hi.chunks_mut(part)).zip(layer_twiddles.chunks(part)) {
+                    scope.spawn(move || run_block_part(lo, hi, twiddles, m));
+                }
+            }
+        });
+    }
 }
 
+pub fn mt_ifft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32, threads: u16) {
+    let invlen = F::from_u128(a.len() as u128).invert().unwrap();
+    mt_fft(a, &omega.invert().unwrap(), log_n, threads);
+    for item in a.iter_mut() {
+        *item *= invlen;
+    }
+}
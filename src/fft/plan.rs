@@ -0,0 +1,171 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use ff::PrimeField;
+
+use super::{serial, mt, radix4, four_step, batch::{self, MatrixLayout}};
+use crate::{constants::{MT_FFT_THRESHOLD, FOUR_STEP_FFT_THRESHOLD}, domains::Domain, error::SynthesisError};
+
+/*
+ * Everything an FFT over the domain of size n = 2^k generated by the canonical root of unity
+ * w_n = Domain::root_with_order_unchecked(n) needs besides the input: the twiddles of every
+ * butterfly layer for w_n and w_n^-1, and 1/n for the inverse transform.
+ *
+ * Plans are cached per field and size, so the twiddles of a size are generated once per process
+ * and shared by every later transform of that size (every FRI layer, every PLONK column, ...).
+ * Cached plans are kept for the lifetime of the process.
+*/
+
+// The butterfly network a plan runs the transform with, all of them give the same evaluations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FftKernel {
+    // Radix-2 decimation in time, split across threads for large inputs and replaced by the
+    // four-step FFT for very large ones
+    #[default]
+    Radix2,
+    // Radix-4 decimation in time, identical output to Radix2
+    Radix4Dit,
+    // Radix-4 decimation in frequency
+    Radix4Dif,
+    // Four-step FFT over a sqrt(n) x sqrt(n) matrix, split across threads
+    FourStep,
+}
+
+#[derive(Debug)]
+pub struct FftPlan<F: PrimeField> {
+    log_n: u32,
+    omega: F,
+    omega_inv: F,
+    size_inv: F,
+    twiddles: Vec<F>,
+    inverse_twiddles: Vec<F>,
+}
+
+type PlanCache = Mutex<HashMap<(TypeId, u32), Arc<dyn Any + Send + Sync>>>;
+
+fn cache() -> &'static PlanCache {
+    static CACHE: OnceLock<PlanCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl<F: PrimeField> FftPlan<F> {
+
+    pub fn new(log_n: u32) -> Result<Self, SynthesisError> {
+        if log_n > F::S {
+            return Err(SynthesisError::DomainTooLarge(1u64.checked_shl(log_n).unwrap_or(u64::MAX)));
+        }
+        let n = 1u64 << log_n;
+        let omega = Domain::<F>::root_with_order_unchecked(n);
+        let omega_inv = omega.invert().unwrap();
+        Ok(Self {
+            log_n,
+            omega,
+            omega_inv,
+            size_inv: F::from_u128(n as u128).invert().unwrap(),
+            twiddles: serial::layer_twiddles(&omega, log_n),
+            inverse_twiddles: serial::layer_twiddles(&omega_inv, log_n),
+        })
+    }
+
+    // The shared plan for size 2^log_n, built on first use.
+    pub fn for_log_size(log_n: u32) -> Result<Arc<Self>, SynthesisError> {
+        let key = (TypeId::of::<F>(), log_n);
+        if let Some(plan) = cache().lock().unwrap().get(&key) {
+            return Ok(plan.clone().downcast().unwrap());
+        }
+
+        // Built without holding the lock, two threads may race to insert the same plan
+        let plan = Arc::new(Self::new(log_n)?);
+        Ok(cache().lock().unwrap().entry(key).or_insert(plan).clone().downcast().unwrap())
+    }
+
+    pub fn for_size(size: usize) -> Result<Arc<Self>, SynthesisError> {
+        if !size.is_power_of_two() {
+            return Err(SynthesisError::NonPowerOfTwo(size));
+        }
+        Self::for_log_size(size.trailing_zeros())
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.log_n
+    }
+
+    pub fn log_size(&self) -> u32 {
+        self.log_n
+    }
+
+    pub fn omega(&self) -> F {
+        self.omega
+    }
+
+    pub fn omega_inv(&self) -> F {
+        self.omega_inv
+    }
+
+    // Coefficients to evaluations at w^0, ..., w^(n-1), split across all available threads from
+    // MT_FFT_THRESHOLD elements on and by the four-step FFT from FOUR_STEP_FFT_THRESHOLD on. All
+    // paths give the same evaluations.
+    pub fn fft(&self, a: &mut [F]) {
+        self.fft_with(a, FftKernel::default());
+    }
+
+    pub fn ifft(&self, a: &mut [F]) {
+        self.ifft_with(a, FftKernel::default());
+    }
+
+    pub fn fft_with(&self, a: &mut [F], kernel: FftKernel) {
+        transform(a, &self.twiddles, self.log_n, kernel);
+    }
+
+    pub fn ifft_with(&self, a: &mut [F], kernel: FftKernel) {
+        transform(a, &self.inverse_twiddles, self.log_n, kernel);
+        self.scale(a);
+    }
+
+    // The FFT of every column of a matrix of `width` polynomials of this size, see batch.rs. The
+    // evaluations are returned row-major.
+    pub fn batch_fft(&self, matrix: &[F], width: usize, layout: MatrixLayout) -> Vec<F> {
+        batch::batch_fft(self, matrix, width, layout)
+    }
+
+    // Coefficients in natural order to evaluations in bit-reversed order, i.e. element i is the
+    // evaluation at w^bitreverse(i). Skips the bit-reversal pass, for callers that only combine
+    // evaluations pointwise before going back with ifft_bit_reversed.
+    pub fn fft_bit_reversed(&self, a: &mut [F]) {
+        radix4::dif_to_bit_reversed(a, &self.twiddles, self.log_n);
+    }
+
+    // Evaluations in bit-reversed order to coefficients in natural order.
+    pub fn ifft_bit_reversed(&self, a: &mut [F]) {
+        radix4::dit_from_bit_reversed(a, &self.inverse_twiddles, self.log_n);
+        self.scale(a);
+    }
+
+    fn scale(&self, a: &mut [F]) {
+        for item in a.iter_mut() {
+            *item *= self.size_inv;
+        }
+    }
+}
+
+fn transform<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32, kernel: FftKernel) {
+    match kernel {
+        FftKernel::Radix2 if a.len() >= FOUR_STEP_FFT_THRESHOLD => {
+            four_step::four_step_fft_with_twiddles(a, twiddles, log_n, available_threads());
+        }
+        FftKernel::Radix2 if a.len() >= MT_FFT_THRESHOLD => {
+            mt::mt_fft_with_twiddles(a, twiddles, log_n, available_threads());
+        }
+        FftKernel::Radix2 => serial::serial_fft_with_twiddles(a, twiddles, log_n),
+        FftKernel::Radix4Dit => radix4::radix4_dit(a, twiddles, log_n),
+        FftKernel::Radix4Dif => radix4::radix4_dif(a, twiddles, log_n),
+        FftKernel::FourStep => four_step::four_step_fft_with_twiddles(a, twiddles, log_n, available_threads()),
+    }
+}
+
+pub(crate) fn available_threads() -> u16 {
+    std::thread::available_parallelism().map_or(1, |n| n.get().min(u16::MAX as usize) as u16)
+}
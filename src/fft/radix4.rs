@@ -0,0 +1,216 @@
+use core::ops::Range;
+
+use ff::PrimeField;
+
+use super::serial::{bitreverse_permutation, butterflies};
+use crate::field::packed::{self, PackedField, PackedKernel};
+
+/*
+ * Radix-4 kernels, which apply two radix-2 layers in one pass over the data and so load and store
+ * every element half as often. With m the half-size of the lower layer, one radix-4 butterfly
+ * takes the four elements x_j, x_(j+m), x_(j+2m), x_(j+3m) of a block of 4m through both layers:
+ *
+ *     DIT (bit-reversed in, natural out)    DIF (natural in, bit-reversed out)
+ *     b0 = x0 + w_2m^j x1                  b0 = x0 + x2,  b2 = (x0 - x2) w_4m^j
+ *     b1 = x0 - w_2m^j x1                  b1 = x1 + x3,  b3 = (x1 - x3) w_4m^(j+m)
+ *     b2 = x2 + w_2m^j x3
+ *     b3 = x2 - w_2m^j x3                  y0 = b0 + b1,  y1 = (b0 - b1) w_2m^j
+ *     y0, y2 = b0 +- w_4m^j b2             y2 = b2 + b3,  y3 = (b2 - b3) w_2m^j
+ *     y1, y3 = b1 +- w_4m^(j+m) b3
+ *
+ * The twiddles are those of serial::layer_twiddles, and the DIT kernel performs exactly the
+ * operations of the radix-2 FFT, so its output is identical to serial_fft. The DIF kernel ends
+ * with the bit-reversal instead of starting with it.
+ *
+ * Layers are cache blocked: the layers whose blocks fit in 2^LOG_CACHE_BLOCK elements run to
+ * completion on one such chunk before the next chunk is touched, and only the remaining layers
+ * sweep the whole input.
+*/
+
+const LOG_CACHE_BLOCK: u32 = 12;
+
+// Whether the lower and upper layer of a radix-4 step make the same choice between packed and
+// element by element butterflies as the radix-2 FFT does.
+#[inline(always)]
+fn fusable<P: PackedField>(m: usize) -> bool {
+    m >= P::WIDTH || 2*m < P::WIDTH
+}
+
+// The four quarters of a block of 4m, each of length m.
+type Quarters<'a, T> = (&'a mut [T], &'a mut [T], &'a mut [T], &'a mut [T]);
+
+#[inline(always)]
+fn quarters<F>(block: &mut [F], m: usize) -> Quarters<'_, F> {
+    let (lower, upper) = block.split_at_mut(2*m);
+    let (q0, q1) = lower.split_at_mut(m);
+    let (q2, q3) = upper.split_at_mut(m);
+    (q0, q1, q2, q3)
+}
+
+#[inline(always)]
+fn dit4<Q: PackedField>(q: Quarters<'_, Q::Scalar>, tw_m: &[Q::Scalar], tw_2m: &[Q::Scalar]) {
+    let (q0, q1, q2, q3) = q;
+    let m = q0.len();
+    for j in (0..m).step_by(Q::WIDTH) {
+        let w = Q::load(&tw_m[j..]);
+        let (x0, x2) = (Q::load(&q0[j..]), Q::load(&q2[j..]));
+        let t1 = Q::load(&q1[j..]) * w;
+        let t3 = Q::load(&q3[j..]) * w;
+        let (b0, b1, b2, b3) = (x0 + t1, x0 - t1, x2 + t3, x2 - t3);
+
+        let u2 = b2 * Q::load(&tw_2m[j..]);
+        let u3 = b3 * Q::load(&tw_2m[j+m..]);
+        (b0 - u2).store(&mut q2[j..]);
+        (b0 + u2).store(&mut q0[j..]);
+        (b1 - u3).store(&mut q3[j..]);
+        (b1 + u3).store(&mut q1[j..]);
+    }
+}
+
+#[inline(always)]
+fn dif4<Q: PackedField>(q: Quarters<'_, Q::Scalar>, tw_m: &[Q::Scalar], tw_2m: &[Q::Scalar]) {
+    let (q0, q1, q2, q3) = q;
+    let m = q0.len();
+    for j in (0..m).step_by(Q::WIDTH) {
+        let (x0, x1, x2, x3) = (Q::load(&q0[j..]), Q::load(&q1[j..]), Q::load(&q2[j..]), Q::load(&q3[j..]));
+        let b0 = x0 + x2;
+        let b2 = (x0 - x2) * Q::load(&tw_2m[j..]);
+        let b1 = x1 + x3;
+        let b3 = (x1 - x3) * Q::load(&tw_2m[j+m..]);
+
+        let w = Q::load(&tw_m[j..]);
+        (b0 + b1).store(&mut q0[j..]);
+        ((b0 - b1) * w).store(&mut q1[j..]);
+        (b2 + b3).store(&mut q2[j..]);
+        ((b2 - b3) * w).store(&mut q3[j..]);
+    }
+}
+
+#[inline(always)]
+fn dif_butterflies<P: PackedField>(lo: &mut [P::Scalar], hi: &mut [P::Scalar], twiddles: &[P::Scalar], m: usize) {
+    if m < P::WIDTH {
+        for j in 0..m {
+            let (u, v) = (lo[j], hi[j]);
+            lo[j] = u + v;
+            hi[j] = (u - v) * twiddles[j];
+        }
+    } else {
+        for j in (0..m).step_by(P::WIDTH) {
+            let (u, v) = (P::load(&lo[j..]), P::load(&hi[j..]));
+            (u + v).store(&mut lo[j..]);
+            ((u - v) * P::load(&twiddles[j..])).store(&mut hi[j..]);
+        }
+    }
+}
+
+// DIT layers in increasing order, two at a time where possible.
+#[inline(always)]
+fn dit_layers<P: PackedField>(a: &mut [P::Scalar], twiddles: &[P::Scalar], layers: Range<u32>) {
+    let mut k = layers.start;
+    while k < layers.end {
+        let m = 1 << k;
+        if k + 1 < layers.end && fusable::<P>(m) {
+            let (tw_m, tw_2m) = (&twiddles[m-1..2*m-1], &twiddles[2*m-1..4*m-1]);
+            for block in a.chunks_exact_mut(4*m) {
+                if m >= P::WIDTH {
+                    dit4::<P>(quarters(block, m), tw_m, tw_2m);
+                } else {
+                    dit4::<P::Scalar>(quarters(block, m), tw_m, tw_2m);
+                }
+            }
+            k += 2;
+        } else {
+            for block in a.chunks_exact_mut(2*m) {
+                let (lo, hi) = block.split_at_mut(m);
+                butterflies::<P>(lo, hi, &twiddles[m-1..2*m-1], m);
+            }
+            k += 1;
+        }
+    }
+}
+
+// DIF layers in decreasing order, two at a time where possible.
+#[inline(always)]
+fn dif_layers<P: PackedField>(a: &mut [P::Scalar], twiddles: &[P::Scalar], layers: Range<u32>) {
+    let mut k = layers.end;
+    while k > layers.start {
+        if k - 1 > layers.start && fusable::<P>(1 << (k - 2)) {
+            let m = 1 << (k - 2);
+            let (tw_m, tw_2m) = (&twiddles[m-1..2*m-1], &twiddles[2*m-1..4*m-1]);
+            for block in a.chunks_exact_mut(4*m) {
+                if m >= P::WIDTH {
+                    dif4::<P>(quarters(block, m), tw_m, tw_2m);
+                } else {
+                    dif4::<P::Scalar>(quarters(block, m), tw_m, tw_2m);
+                }
+            }
+            k -= 2;
+        } else {
+            let m = 1 << (k - 1);
+            for block in a.chunks_exact_mut(2*m) {
+                let (lo, hi) = block.split_at_mut(m);
+                dif_butterflies::<P>(lo, hi, &twiddles[m-1..2*m-1], m);
+            }
+            k -= 1;
+        }
+    }
+}
+
+struct Radix4<'a, F: PrimeField> {
+    a: &'a mut [F],
+    twiddles: &'a [F],
+    log_n: u32,
+    dif: bool,
+}
+
+impl<F: PrimeField> PackedKernel<F> for Radix4<'_, F> {
+    type Output = ();
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = F>>(self) {
+        let log_block = LOG_CACHE_BLOCK.min(self.log_n);
+        if self.dif {
+            dif_layers::<P>(self.a, self.twiddles, log_block..self.log_n);
+            for chunk in self.a.chunks_mut(1 << log_block) {
+                dif_layers::<P>(chunk, self.twiddles, 0..log_block);
+            }
+        } else {
+            for chunk in self.a.chunks_mut(1 << log_block) {
+                dit_layers::<P>(chunk, self.twiddles, 0..log_block);
+            }
+            dit_layers::<P>(self.a, self.twiddles, log_block..self.log_n);
+        }
+    }
+}
+
+fn run<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32, dif: bool) {
+    let kernel = Radix4 { a, twiddles, log_n, dif };
+    match (packed::as_goldilocks_mut(kernel.a), packed::as_goldilocks(twiddles)) {
+        (Some(a), Some(twiddles)) => packed::dispatch(Radix4 { a, twiddles, log_n, dif }),
+        _ => kernel.run::<F>(),
+    }
+}
+
+// Natural order in and out, with the twiddles of serial::layer_twiddles.
+pub(crate) fn radix4_dit<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32) {
+    assert_eq!(a.len(), 1 << log_n);
+    bitreverse_permutation(a, log_n);
+    run(a, twiddles, log_n, false);
+}
+
+pub(crate) fn radix4_dif<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32) {
+    dif_to_bit_reversed(a, twiddles, log_n);
+    bitreverse_permutation(a, log_n);
+}
+
+// The same transforms without the bit-reversal, natural order in and bit-reversed out for DIF and
+// the other way around for DIT. A DIF followed by a DIT never permutes anything.
+pub(crate) fn dif_to_bit_reversed<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32) {
+    assert_eq!(a.len(), 1 << log_n);
+    run(a, twiddles, log_n, true);
+}
+
+pub(crate) fn dit_from_bit_reversed<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32) {
+    assert_eq!(a.len(), 1 << log_n);
+    run(a, twiddles, log_n, false);
+}
@@ -1,32 +1,57 @@
 use ff::PrimeField;
 
-pub(crate) fn recursive_fft<F: PrimeField>(s: Vec<F>, g: &F) -> Vec<F> {
-    let split_vec = | x: Vec<F> | -> (Vec<F>, Vec<F>) {
-        let mut ev = Vec::new();
-        let mut ov = Vec::new();
-        for i in 0..x.len() {
-            if i % 2 == 0 { ev.push(x[i]); }
-            else { ov.push(x[i]); }
-        }
-        (ev, ov)
-    };
+/*
+ * Reference transforms, written for clarity rather than speed and used to check the optimized FFTs
+ * against. For a of size n and g of order n both return the evaluations a(g^0), ..., a(g^(n-1)).
+ *
+ * The recursive FFT is the textbook Cooley-Tukey split a(x) = e(x^2) + x * o(x^2), with e and o
+ * the even and odd coefficients:
+ *
+ *     a(g^i)       = e(g^2i) + g^i * o(g^2i)
+ *     a(g^(i+n/2)) = e(g^2i) - g^i * o(g^2i)
+ *
+ * The naive DFT evaluates every point on its own in O(n^2).
+*/
 
-    if s.len() == 1 { return s; }
-    let (ov, ev) = split_vec(s);
-    let l = recursive_fft(ev, &g.pow([2u64]));
-    let r = recursive_fft(ov, &g.pow([2u64]));
-    let mut o = Vec::new();
-    let mut a = Vec::new();
-    for (i, (x,y)) in r.into_iter().zip(l).enumerate() {
-        let yr = y*g.pow([i as u64]);
-        o.push(x+yr);
-        a.push(x-yr);
+pub fn recursive_fft<F: PrimeField>(a: Vec<F>, g: &F) -> Vec<F> {
+    if a.len() == 1 {
+        return a;
     }
-    o.append(&mut a);
-    o
+    assert!(a.len().is_power_of_two(), "FFT size must be a power of two.");
+
+    let (mut even, mut odd) = (Vec::with_capacity(a.len() / 2), Vec::with_capacity(a.len() / 2));
+    for (i, x) in a.into_iter().enumerate() {
+        if i % 2 == 0 { even.push(x); } else { odd.push(x); }
+    }
+
+    let g_squared = g.square();
+    let even = recursive_fft(even, &g_squared);
+    let odd = recursive_fft(odd, &g_squared);
+
+    let mut lo = Vec::with_capacity(2 * even.len());
+    let mut hi = Vec::with_capacity(even.len());
+    let mut power = F::ONE;
+    for (e, o) in even.into_iter().zip(odd) {
+        let t = o * power;
+        lo.push(e + t);
+        hi.push(e - t);
+        power *= g;
+    }
+    lo.append(&mut hi);
+    lo
+}
+
+pub fn recursive_ifft<F: PrimeField>(a: Vec<F>, g: &F) -> Vec<F> {
+    let invlen = F::from_u128(a.len() as u128).invert().unwrap();
+    recursive_fft(a, &g.invert().unwrap()).into_iter().map(|x| x * invlen).collect::<Vec<F>>()
 }
 
-pub(crate) fn recursive_ifft<F: PrimeField>(s: Vec<F>, g: &F) -> Vec<F> {
-    let invlen = F::from_u128(s.len() as u128).invert().unwrap();
-    recursive_fft(s, &g.invert().unwrap()).into_iter().map(|x| x * invlen).collect::<Vec<F>>()
+pub fn naive_dft<F: PrimeField>(a: &[F], g: &F) -> Vec<F> {
+    let mut point = F::ONE;
+    (0..a.len()).map(|_| {
+        // Horner's rule at g^i
+        let evaluation = a.iter().rev().fold(F::ZERO, |acc, c| acc * point + c);
+        point *= g;
+        evaluation
+    }).collect()
 }
@@ -1,7 +1,32 @@
+use core::ops::Range;
+
 use ff::PrimeField;
 
-pub(crate) fn serial_fft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32) {
-    
+use crate::field::packed::{self, PackedField, PackedKernel};
+
+pub fn serial_fft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32) {
+    serial_fft_with_twiddles(a, &layer_twiddles(omega, log_n), log_n);
+}
+
+// The FFT with the twiddles of layer_twiddles given, e.g. from an FftPlan.
+pub(crate) fn serial_fft_with_twiddles<F: PrimeField>(a: &mut [F], twiddles: &[F], log_n: u32) {
+    assert_eq!(a.len(), 1 << log_n);
+
+    bitreverse_permutation(a, log_n);
+    run_layers(a, twiddles, 0..log_n);
+}
+
+pub fn serial_ifft<F: PrimeField>(s: &mut [F], omega: &F, log_n: u32) {
+    let invlen = F::from_u128(s.len() as u128).invert().unwrap();
+    serial_fft(s, &omega.invert().unwrap(), log_n);
+    for item in s.iter_mut() {
+        *item *= invlen;
+    }
+}
+
+// Swaps element i with element bitreverse(i), converting between natural and bit-reversed order.
+pub fn bitreverse_permutation<F>(a: &mut [F], log_n: u32) {
+
     #[inline(always)]
     fn bitreverse(mut n: u32, l: u32) -> u32 {
         let mut r = 0;
@@ -12,46 +37,106 @@ pub(crate) fn serial_fft<F: PrimeField>(a: &mut [F], omega: &F, log_n: u32) {
         r
     }
 
-    let n = a.len() as u32;
-    assert_eq!(n, 1 << log_n);
-
-    for k in 0..n {
+    for k in 0..a.len() as u32 {
         let rk = bitreverse(k, log_n);
         if k < rk {
             a.swap(rk as usize, k as usize);
         }
     }
+}
 
+// The twiddles of every butterfly layer in one vector. Layer k has half-size m = 2^k and uses
+// w_m^0, ..., w_m^(m-1) for w_m = omega^(n/2m), stored at [m - 1, 2m - 1). They are built the same
+// way for every FFT so that the serial and threaded versions agree bit for bit.
+pub(crate) fn layer_twiddles<F: PrimeField>(omega: &F, log_n: u32) -> Vec<F> {
+    let n = 1u64 << log_n;
+    let mut twiddles = Vec::with_capacity(n as usize);
     let mut m = 1;
     for _ in 0..log_n {
-        let w_m = omega.pow([(n / (2*m)) as u64]);
-
-        let mut k = 0;
-        while k < n {
-            let mut w = F::ONE;
-            for j in 0..m {
-
-                let mut t = a[(k+j+m) as usize];
-                t.mul_assign(&w);
-                let mut tmp = a[(k+j) as usize];
-                tmp.sub_assign(&t);
-                a[(k+j+m) as usize] = tmp;
-                a[(k+j) as usize].add_assign(&t);
-                w.mul_assign(&w_m);
-            }
+        let w_m = omega.pow([n / (2*m)]);
+        twiddles.push(F::ONE);
+        for _ in 1..m {
+            let previous = *twiddles.last().unwrap();
+            twiddles.push(previous * w_m);
+        }
+        m *= 2;
+    }
+    twiddles
+}
 
-            k += 2*m;
+// The butterflies of a layer with half-size m on (matching parts of) the two halves of a block.
+// Whether they run on packings depends only on m, so a block may be split up between threads.
+#[inline(always)]
+pub(crate) fn butterflies<P: PackedField>(lo: &mut [P::Scalar], hi: &mut [P::Scalar], twiddles: &[P::Scalar], m: usize) {
+    if m < P::WIDTH {
+        for j in 0..lo.len() {
+            let t = hi[j] * twiddles[j];
+            hi[j] = lo[j] - t;
+            lo[j] += t;
+        }
+    } else {
+        for j in (0..lo.len()).step_by(P::WIDTH) {
+            let u = P::load(&lo[j..]);
+            let t = P::load(&hi[j..]) * P::load(&twiddles[j..]);
+            (u - t).store(&mut hi[j..]);
+            (u + t).store(&mut lo[j..]);
         }
+    }
+}
 
-        m *= 2;
+// A range of butterfly layers over a bit-reversed input, a being a whole number of blocks of the
+// last layer.
+struct Layers<'a, F: PrimeField> {
+    a: &'a mut [F],
+    twiddles: &'a [F],
+    layers: Range<u32>,
+}
+
+impl<F: PrimeField> PackedKernel<F> for Layers<'_, F> {
+    type Output = ();
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = F>>(self) {
+        for k in self.layers {
+            let m = 1 << k;
+            let twiddles = &self.twiddles[m-1..2*m-1];
+            for block in self.a.chunks_exact_mut(2*m) {
+                let (lo, hi) = block.split_at_mut(m);
+                butterflies::<P>(lo, hi, twiddles, m);
+            }
+        }
     }
 }
 
-pub(crate) fn serial_ifft<F: PrimeField>(s: &mut [F], omega: &F, log_n: u32) {
-    let invlen = F::from_u128(s.len() as u128).invert().unwrap();
-    serial_fft(s, &omega.invert().unwrap(), log_n);
-    for item in s.iter_mut() {
-        *item *= invlen;
+pub(crate) fn run_layers<F: PrimeField>(a: &mut [F], twiddles: &[F], layers: Range<u32>) {
+    let kernel = Layers { a, twiddles, layers: layers.clone() };
+    match (packed::as_goldilocks_mut(kernel.a), packed::as_goldilocks(twiddles)) {
+        (Some(a), Some(twiddles)) => packed::dispatch(Layers { a, twiddles, layers }),
+        _ => kernel.run::<F>(),
     }
 }
 
+// Part of a single block of the layer with half-size m, lo[j] and hi[j] being m apart in the block.
+struct BlockPart<'a, F: PrimeField> {
+    lo: &'a mut [F],
+    hi: &'a mut [F],
+    twiddles: &'a [F],
+    m: usize,
+}
+
+impl<F: PrimeField> PackedKernel<F> for BlockPart<'_, F> {
+    type Output = ();
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = F>>(self) {
+        butterflies::<P>(self.lo, self.hi, self.twiddles, self.m);
+    }
+}
+
+pub(crate) fn run_block_part<F: PrimeField>(lo: &mut [F], hi: &mut [F], twiddles: &[F], m: usize) {
+    let kernel = BlockPart { lo, hi, twiddles, m };
+    match (packed::as_goldilocks_mut(kernel.lo), packed::as_goldilocks_mut(kernel.hi), packed::as_goldilocks(twiddles)) {
+        (Some(lo), Some(hi), Some(twiddles)) => packed::dispatch(BlockPart { lo, hi, twiddles, m }),
+        _ => kernel.run::<F>(),
+    }
+}
@@ -0,0 +1,277 @@
+use core::{fmt, cmp::Ordering};
+
+use ff::{Field, PrimeField, helpers::{sqrt_tonelli_shanks, sqrt_ratio_generic}};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess, CtOption};
+
+use super::{macros::impl_field_ops, extension::{Extendable, BinomialExtension}};
+
+/*
+ * The BabyBear field, integers modulo p = 15 * 2^27 + 1.
+ *
+ * Elements fit in a u32 and are kept in Montgomery form a * 2^32 mod p, always below p. Products
+ * are reduced with a single 32-bit Montgomery step:
+ *
+ *     t = x * p^-1 mod 2^32
+ *     (x - t * p) / 2^32 = x * 2^-32 (mod p)
+ *
+ * The field has subgroups of order 2^k for k <= 27, enough for FFTs over any practical domain.
+*/
+
+const P: u32 = 0x7800_0001;
+// p^-1 mod 2^32
+const MU: u32 = 0x8800_0001;
+// 2^64 mod p, multiplying by it moves an integer into Montgomery form
+const R2: u32 = 1172168163;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BabyBear(u32);
+
+#[inline(always)]
+fn montgomery_reduce(x: u64) -> u32 {
+    let t = (x as u32).wrapping_mul(MU);
+    let u = (t as u64) * (P as u64);
+    let (difference, under) = x.overflowing_sub(u);
+    let result = (difference >> 32) as u32;
+    if under { result.wrapping_add(P) } else { result }
+}
+
+impl BabyBear {
+
+    pub fn to_canonical_u32(&self) -> u32 {
+        montgomery_reduce(self.0 as u64)
+    }
+
+    #[inline(always)]
+    fn add_inner(&self, rhs: &Self) -> Self {
+        let sum = self.0 + rhs.0;
+        Self(if sum >= P { sum - P } else { sum })
+    }
+
+    #[inline(always)]
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        let (difference, under) = self.0.overflowing_sub(rhs.0);
+        Self(if under { difference.wrapping_add(P) } else { difference })
+    }
+
+    #[inline(always)]
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self(montgomery_reduce((self.0 as u64) * (rhs.0 as u64)))
+    }
+
+    #[inline(always)]
+    fn neg_inner(&self) -> Self {
+        Self::ZERO.sub_inner(self)
+    }
+}
+
+impl_field_ops!(BabyBear);
+
+impl Ord for BabyBear {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_canonical_u32().cmp(&other.to_canonical_u32())
+    }
+}
+
+impl PartialOrd for BabyBear {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for BabyBear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BabyBear({})", self.to_canonical_u32())
+    }
+}
+
+impl fmt::Display for BabyBear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_canonical_u32())
+    }
+}
+
+impl From<u64> for BabyBear {
+    fn from(value: u64) -> Self {
+        Self(montgomery_reduce((value % P as u64) * R2 as u64))
+    }
+}
+
+impl ConstantTimeEq for BabyBear {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for BabyBear {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(u32::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Field for BabyBear {
+    const ZERO: Self = Self(0);
+    // 2^32 mod p
+    const ONE: Self = Self(268435454);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        // Reducing 64 random bits leaves a bias of about 2^-33
+        Self::from(rng.next_u64())
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([(P - 2) as u64]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        // (t - 1)/2 for p - 1 = 2^27 * t, t = 15
+        sqrt_tonelli_shanks(self, [7])
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        sqrt_ratio_generic(num, div)
+    }
+}
+
+// Constants are in Montgomery form.
+impl PrimeField for BabyBear {
+    type Repr = [u8; 4];
+
+    const MODULUS: &'static str = "0x78000001";
+    const NUM_BITS: u32 = 31;
+    const CAPACITY: u32 = 30;
+    const TWO_INV: Self = Self(134217727);
+    const MULTIPLICATIVE_GENERATOR: Self = Self(268435390);
+    const S: u32 = 27;
+    // 31^15 and its inverse
+    const ROOT_OF_UNITY: Self = Self(1476048622);
+    const ROOT_OF_UNITY_INV: Self = Self(1578289385);
+    // 31^(2^27)
+    const DELTA: Self = Self(977485801);
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u32::from_le_bytes(repr);
+        CtOption::new(Self::from(value as u64), value.ct_lt(&P))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.to_canonical_u32().to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_canonical_u32() & 1) as u8)
+    }
+}
+
+// BabyBear[x]/(x^4 - 11), 11 is not a square and p = 1 (mod 4).
+pub type BabyBearExt4 = BinomialExtension<BabyBear, 4>;
+
+impl Extendable<4> for BabyBear {
+    const W: Self = Self(939524073);
+    // 11^((p - 1)/4)
+    const DTH_ROOT: Self = Self(473486609);
+}
+
+#[cfg(test)]
+mod babybear_tests {
+
+    use super::*;
+    use crate::{
+        domains::Domain,
+        fft::serial,
+        polynomial::Polynomial,
+        fri::MatrixCommitment,
+        field::extension::ExtensionField,
+        FriProof,
+    };
+
+    // The same field through the generic derived implementation serves as the reference.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "2013265921"]
+    #[PrimeFieldGenerator = "31"]
+    #[PrimeFieldReprEndianness = "little"]
+    struct Reference([u64; 1]);
+
+    const EDGES: [u64; 10] = [0, 1, 2, 31, 1 << 27, (P - 2) as u64, (P - 1) as u64, P as u64, P as u64 + 1, u32::MAX as u64];
+
+    fn assert_matches(native: BabyBear, expected: Reference) {
+        let expected = expected.to_repr();
+        assert_eq!(native.to_repr().as_slice(), &expected.as_ref()[..4]);
+        assert!(expected.as_ref()[4..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn edge_cases_against_reference() {
+        for a in EDGES {
+            let (x, rx) = (BabyBear::from(a), Reference::from(a));
+            assert_matches(x, rx);
+            assert_matches(-x, -rx);
+            assert_matches(x.square(), rx.square());
+            if bool::from(rx.invert().is_some()) {
+                assert_matches(x.invert().unwrap(), rx.invert().unwrap());
+            }
+            for b in EDGES {
+                let (y, ry) = (BabyBear::from(b), Reference::from(b));
+                assert_matches(x + y, rx + ry);
+                assert_matches(x - y, rx - ry);
+                assert_matches(x * y, rx * ry);
+                assert_eq!(x.cmp(&y), rx.cmp(&ry));
+            }
+        }
+    }
+
+    #[test]
+    fn constants_match_reference() {
+        assert_eq!(BabyBear::S, Reference::S);
+        assert_matches(BabyBear::ONE, Reference::ONE);
+        assert_matches(BabyBear::TWO_INV, Reference::TWO_INV);
+        assert_matches(BabyBear::MULTIPLICATIVE_GENERATOR, Reference::MULTIPLICATIVE_GENERATOR);
+        assert_matches(BabyBear::ROOT_OF_UNITY, Reference::ROOT_OF_UNITY);
+        assert_matches(BabyBear::ROOT_OF_UNITY_INV, Reference::ROOT_OF_UNITY_INV);
+        assert_matches(BabyBear::DELTA, Reference::DELTA);
+        assert_eq!(BabyBear::W, BabyBear::from(11));
+        assert!(bool::from(BabyBear::from_repr(P.to_le_bytes()).is_none()));
+
+        let mut rng = rand::thread_rng();
+        let x = BabyBear::random(&mut rng);
+        assert_eq!(x.square().sqrt().unwrap().square(), x.square());
+    }
+
+    #[test]
+    fn quartic_extension() {
+        let mut rng = rand::thread_rng();
+        let (a, b) = (BabyBearExt4::random(&mut rng), BabyBearExt4::random(&mut rng));
+        assert_eq!(a * a.invert().unwrap(), BabyBearExt4::ONE);
+        assert_eq!((a * b).frobenius(), a.frobenius() * b.frobenius());
+        assert_eq!(a.frobenius(), a.pow_vartime([P as u64]));
+        assert_eq!(a.repeated_frobenius(4), a);
+
+        let x = BabyBearExt4::new([BabyBear::ZERO, BabyBear::ONE, BabyBear::ZERO, BabyBear::ZERO]);
+        assert_eq!(x.pow_vartime([4]), BabyBearExt4::from(BabyBear::from(11)));
+    }
+
+    #[test]
+    fn fft_fri_and_merkle_over_babybear() {
+        let mut rng = rand::thread_rng();
+        let coefficients = (0..16).map(|_| BabyBear::random(&mut rng)).collect::<Vec<_>>();
+        let f_x = Polynomial::from_vec(coefficients.clone());
+
+        let omega: BabyBear = Domain::new_for_size(16).unwrap().generator;
+        let mut evaluations = coefficients.clone();
+        serial::serial_fft(evaluations.as_mut_slice(), &omega, 4);
+        assert_eq!(evaluations[3], f_x.eval_single(&omega.pow([3])));
+
+        assert!(FriProof::evaluation_proof(&f_x, None).verify().is_valid());
+
+        let commitment = MatrixCommitment::new(&[f_x.clone(), f_x]).unwrap();
+        assert!(commitment.open(5).unwrap().verify(commitment.root(), commitment.domain_size()).is_valid());
+    }
+}
@@ -0,0 +1,8 @@
+use ff::PrimeField;
+
+// Scalar field of the BN254 (alt_bn128) curve, i.e. the field KZG commitments on Ethereum are over.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+#[PrimeFieldGenerator = "7"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fr([u64; 4]);
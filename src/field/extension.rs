@@ -0,0 +1,441 @@
+use ff::{Field, PrimeField};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::macros::impl_field_ops;
+
+/*
+ * Binomial extensions F[x]/(x^D - W) of a prime field. Elements are polynomials of degree less
+ * than D with coefficients in F, multiplied modulo x^D - W. For the quotient to be a field W must
+ * not be an r-th power in F for any prime r dividing D.
+ *
+ * Small fields such as Goldilocks cannot give 100+ bits of soundness when the verifier draws its
+ * challenges from the base field, so challenges should be drawn from an extension instead while
+ * the committed polynomials keep their coefficients in F. This module only provides the pieces for
+ * that: the extension fields, challenges sampled in them (`FriPcs::extension_challenge`),
+ * evaluation of base field polynomials at extension points and the soundness estimate of
+ * `FriConfig::for_extension`. The FRI fold and the PLONK protocols still draw their challenges
+ * from F.
+ *
+ * The Frobenius automorphism a -> a^p fixes F, and since x^p = x * W^((p - 1)/D) it only scales
+ * coefficient i by W^(i(p - 1)/D). It gives the norm N(a) = a * a^p * ... * a^(p^(D-1)), an element
+ * of F, which makes inversion a base field inversion: a^-1 = (a^p * ... * a^(p^(D-1))) / N(a).
+*/
+
+// A prime field with an irreducible binomial x^D - W.
+pub trait Extendable<const D: usize>: PrimeField {
+    const W: Self;
+    // W^((p - 1)/D)
+    const DTH_ROOT: Self;
+}
+
+// A field containing F as a subfield, as a vector space of dimension DEGREE over F.
+pub trait ExtensionField<F: PrimeField>: Field + From<F> {
+    const DEGREE: usize;
+
+    fn from_base_slice(coefficients: &[F]) -> Self;
+
+    fn to_base_vec(&self) -> Vec<F>;
+
+    fn scalar_mul(&self, scalar: &F) -> Self;
+
+    // a -> a^p
+    fn frobenius(&self) -> Self;
+
+    fn repeated_frobenius(&self, count: usize) -> Self {
+        (0..count % Self::DEGREE).fold(*self, |acc, _| acc.frobenius())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BinomialExtension<F: Extendable<D>, const D: usize>([F; D]);
+
+impl<F: Extendable<D>, const D: usize> BinomialExtension<F, D> {
+
+    pub const fn new(coefficients: [F; D]) -> Self {
+        Self(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &[F; D] {
+        &self.0
+    }
+
+    fn add_inner(&self, rhs: &Self) -> Self {
+        let mut result = self.0;
+        for (a, b) in result.iter_mut().zip(rhs.0.iter()) {
+            *a += b;
+        }
+        Self(result)
+    }
+
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        let mut result = self.0;
+        for (a, b) in result.iter_mut().zip(rhs.0.iter()) {
+            *a -= b;
+        }
+        Self(result)
+    }
+
+    fn neg_inner(&self) -> Self {
+        Self(self.0.map(|a| -a))
+    }
+
+    // Schoolbook multiplication, terms of degree D + k wrap around to W * x^k.
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        let mut result = [F::ZERO; D];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                if i + j < D {
+                    result[i + j] += *a * b;
+                }
+                else {
+                    result[i + j - D] += F::W * a * b;
+                }
+            }
+        }
+        Self(result)
+    }
+
+    // The first element x + k which is not a square.
+    fn non_residue(order_minus_one: &[u64]) -> Self {
+        let mut start = Self::ZERO;
+        if D > 1 { start.0[1] = F::ONE; }
+        find_non_residue(order_minus_one, start, Self::ONE)
+    }
+}
+
+impl_field_ops!([F: Extendable<D>, const D: usize] BinomialExtension<F, D>);
+
+impl<F: Extendable<D>, const D: usize> Default for BinomialExtension<F, D> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> From<F> for BinomialExtension<F, D> {
+    fn from(value: F) -> Self {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = value;
+        Self(coefficients)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ConstantTimeEq for BinomialExtension<F, D> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(Choice::from(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ConditionallySelectable for BinomialExtension<F, D> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut result = a.0;
+        for (r, b) in result.iter_mut().zip(b.0.iter()) {
+            r.conditional_assign(b, choice);
+        }
+        Self(result)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> Field for BinomialExtension<F, D> {
+    const ZERO: Self = Self([F::ZERO; D]);
+    const ONE: Self = {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = F::ONE;
+        Self(coefficients)
+    };
+
+    fn random(mut rng: impl RngCore) -> Self {
+        Self([(); D].map(|_| F::random(&mut rng)))
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        let conjugates = (1..D).fold(Self::ONE, |acc, k| acc * self.repeated_frobenius(k));
+        let norm = (*self * conjugates).0[0];
+        norm.invert().map(|n| conjugates.scalar_mul(&n))
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        let order = extension_order_minus_one::<F>(D);
+        sqrt_tonelli_shanks_vartime(self, &order, &Self::non_residue(&order))
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        let order = extension_order_minus_one::<F>(D);
+        sqrt_ratio_vartime(num, div, &order, &Self::non_residue(&order))
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ExtensionField<F> for BinomialExtension<F, D> {
+    const DEGREE: usize = D;
+
+    fn from_base_slice(coefficients: &[F]) -> Self {
+        assert_eq!(coefficients.len(), D, "Wrong number of coefficients for the extension.");
+        let mut result = [F::ZERO; D];
+        result.copy_from_slice(coefficients);
+        Self(result)
+    }
+
+    fn to_base_vec(&self) -> Vec<F> {
+        self.0.to_vec()
+    }
+
+    fn scalar_mul(&self, scalar: &F) -> Self {
+        Self(self.0.map(|a| a * scalar))
+    }
+
+    fn frobenius(&self) -> Self {
+        let mut result = self.0;
+        let mut power = F::ONE;
+        for coefficient in result.iter_mut() {
+            *coefficient *= power;
+            power *= F::DTH_ROOT;
+        }
+        Self(result)
+    }
+}
+
+// Limb arithmetic for the exponents of the extension, which do not fit in any machine integer.
+
+fn modulus_limbs<F: PrimeField>() -> Vec<u64> {
+    let digits = F::MODULUS.trim_start_matches("0x").as_bytes();
+    digits
+        .rchunks(16)
+        .map(|chunk| u64::from_str_radix(core::str::from_utf8(chunk).unwrap(), 16).unwrap())
+        .collect()
+}
+
+fn mul_limbs(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut result = vec![0u64; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, y) in b.iter().enumerate() {
+            let product = (*x as u128) * (*y as u128) + result[i + j] as u128 + carry;
+            result[i + j] = product as u64;
+            carry = product >> 64;
+        }
+        result[i + b.len()] = carry as u64;
+    }
+    result
+}
+
+fn trailing_zeros(a: &[u64]) -> u32 {
+    let mut zeros = 0;
+    for limb in a {
+        if *limb != 0 { return zeros + limb.trailing_zeros(); }
+        zeros += 64;
+    }
+    zeros
+}
+
+fn shr_limbs(a: &[u64], shift: u32) -> Vec<u64> {
+    let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+    let mut result = a[limbs..].to_vec();
+    if bits > 0 {
+        for i in 0..result.len() {
+            let high = result.get(i + 1).map_or(0, |h| h << (64 - bits));
+            result[i] = (result[i] >> bits) | high;
+        }
+    }
+    result
+}
+
+// Square roots for fields without a PrimeField implementation, given q - 1 for the q elements of
+// the field. These run in variable time, square roots are never taken of secret values in this
+// crate.
+
+// Order minus one of the extension of degree `degree` of F, as little endian limbs.
+pub(crate) fn extension_order_minus_one<F: PrimeField>(degree: usize) -> Vec<u64> {
+    let p = modulus_limbs::<F>();
+    let mut order = vec![1u64];
+    for _ in 0..degree {
+        order = mul_limbs(&order, &p);
+    }
+    // p^degree is odd, so subtracting one never borrows from the upper limbs
+    order[0] -= 1;
+    order
+}
+
+// The first of start, start + step, start + 2 * step, ... which is not a square. Squares are
+// exactly the elements a with a^((q - 1)/2) = 1.
+pub(crate) fn find_non_residue<E: Field>(order_minus_one: &[u64], start: E, step: E) -> E {
+    let half = shr_limbs(order_minus_one, 1);
+    let mut candidate = start;
+    while candidate.pow_vartime(&half) != -E::ONE {
+        candidate += step;
+    }
+    candidate
+}
+
+// Tonelli-Shanks, q - 1 = 2^s * t with t odd.
+pub(crate) fn sqrt_tonelli_shanks_vartime<E: Field>(a: &E, order_minus_one: &[u64], non_residue: &E) -> CtOption<E> {
+    if bool::from(a.is_zero()) { return CtOption::new(E::ZERO, Choice::from(1)); }
+
+    let s = trailing_zeros(order_minus_one);
+    let t = shr_limbs(order_minus_one, s);
+    let t_minus_one_half = shr_limbs(&t, 1);
+
+    let mut c = non_residue.pow_vartime(&t);
+    let mut x = a.pow_vartime(&t_minus_one_half);
+    let mut b = x.square() * a;
+    x *= a;
+    let mut m = s;
+
+    while b != E::ONE {
+        let mut i = 0;
+        let mut b_power = b;
+        while b_power != E::ONE {
+            b_power = b_power.square();
+            i += 1;
+        }
+        if i == m { return CtOption::new(E::ZERO, Choice::from(0)); }
+
+        let g = (0..m - i - 1).fold(c, |acc, _| acc.square());
+        x *= g;
+        c = g.square();
+        b *= c;
+        m = i;
+    }
+    CtOption::new(x, Choice::from(1))
+}
+
+// When num/div is not a square, the root of non_residue * num/div is returned.
+pub(crate) fn sqrt_ratio_vartime<E: Field>(num: &E, div: &E, order_minus_one: &[u64], non_residue: &E) -> (Choice, E) {
+    if bool::from(num.is_zero()) { return (Choice::from(1), E::ZERO); }
+    if bool::from(div.is_zero()) { return (Choice::from(0), E::ZERO); }
+
+    let ratio = *num * div.invert().unwrap();
+    let root = sqrt_tonelli_shanks_vartime(&ratio, order_minus_one, non_residue);
+    if bool::from(root.is_some()) { return (Choice::from(1), root.unwrap()); }
+    (Choice::from(0), sqrt_tonelli_shanks_vartime(&(ratio * non_residue), order_minus_one, non_residue).unwrap())
+}
+
+#[cfg(test)]
+mod extension_tests {
+
+    use super::*;
+    use crate::{
+        field::goldilocks::{Goldilocks, GoldilocksExt2, GoldilocksExt3},
+        polynomial::Polynomial,
+        fri::FriPcs,
+    };
+
+    fn field_axioms<E: ExtensionField<Goldilocks>>() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let (a, b, c) = (E::random(&mut rng), E::random(&mut rng), E::random(&mut rng));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * b, b * a);
+            assert_eq!((a + b) - b, a);
+            assert_eq!(a * a.invert().unwrap(), E::ONE);
+            assert_eq!(a.square(), a * a);
+        }
+        assert!(bool::from(E::ZERO.invert().is_none()));
+    }
+
+    fn frobenius<E: ExtensionField<Goldilocks>>() {
+        let mut rng = rand::thread_rng();
+        let p = 0xffff_ffff_0000_0001u64;
+        let a = E::random(&mut rng);
+        assert_eq!(a.frobenius(), a.pow_vartime([p]));
+        assert_eq!(a.repeated_frobenius(E::DEGREE), a);
+
+        // Base field elements are fixed
+        let x = Goldilocks::random(&mut rng);
+        assert_eq!(E::from(x).frobenius(), E::from(x));
+    }
+
+    fn embedding<E: ExtensionField<Goldilocks>>() {
+        let mut rng = rand::thread_rng();
+        let (x, y) = (Goldilocks::random(&mut rng), Goldilocks::random(&mut rng));
+        assert_eq!(E::from(x) + E::from(y), E::from(x + y));
+        assert_eq!(E::from(x) * E::from(y), E::from(x * y));
+        assert_eq!(E::from(x).invert().unwrap(), E::from(x.invert().unwrap()));
+
+        let a = E::random(&mut rng);
+        assert_eq!(a.scalar_mul(&x), a * E::from(x));
+        assert_eq!(E::from_base_slice(&a.to_base_vec()), a);
+    }
+
+    fn square_roots<E: ExtensionField<Goldilocks>>() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let a = E::random(&mut rng);
+            let root = a.square().sqrt().unwrap();
+            assert!(root == a || root == -a);
+        }
+    }
+
+    #[test]
+    fn quadratic_extension() {
+        field_axioms::<GoldilocksExt2>();
+        frobenius::<GoldilocksExt2>();
+        embedding::<GoldilocksExt2>();
+        square_roots::<GoldilocksExt2>();
+
+        // x^2 = 7
+        let x = GoldilocksExt2::new([Goldilocks::ZERO, Goldilocks::ONE]);
+        assert_eq!(x.square(), GoldilocksExt2::from(Goldilocks::from(7)));
+        // 7 has no root in the base field, but it does in the extension
+        assert!(bool::from(Goldilocks::from(7).sqrt().is_none()));
+        assert!(bool::from(GoldilocksExt2::from(Goldilocks::from(7)).sqrt().is_some()));
+    }
+
+    #[test]
+    fn cubic_extension() {
+        field_axioms::<GoldilocksExt3>();
+        frobenius::<GoldilocksExt3>();
+        embedding::<GoldilocksExt3>();
+        square_roots::<GoldilocksExt3>();
+
+        let x = GoldilocksExt3::new([Goldilocks::ZERO, Goldilocks::ONE, Goldilocks::ZERO]);
+        assert_eq!(x.cube(), GoldilocksExt3::from(Goldilocks::from(2)));
+    }
+
+    #[test]
+    fn extension_challenges() {
+        let mut rng = rand::thread_rng();
+        let f_x = Polynomial::masking_polynomial(8, &mut rng);
+        let x = Goldilocks::random(&mut rng);
+        assert_eq!(f_x.eval_extension(&GoldilocksExt3::from(x)), GoldilocksExt3::from(f_x.eval_single(&x)));
+
+        // The challenge drawn from the transcript is a genuine extension element
+        let commitments = [f_x.commitment()];
+        let y = f_x.eval_single(&x);
+        let a: GoldilocksExt2 = FriPcs::extension_challenge(&commitments, &x, &[y]);
+        assert!(a.to_base_vec()[1] != Goldilocks::ZERO);
+
+        // A different claimed evaluation or point gives a different challenge
+        let shifted: GoldilocksExt2 = FriPcs::extension_challenge(&commitments, &x, &[y + Goldilocks::ONE]);
+        assert_ne!(a, shifted);
+        let moved: GoldilocksExt2 = FriPcs::extension_challenge(&commitments, &(x + Goldilocks::ONE), &[y]);
+        assert_ne!(a, moved);
+
+        // f(a) computed coefficient-wise matches Horner in the extension
+        let expected = f_x.coefficients().iter().rev().fold(GoldilocksExt2::ZERO, |acc, c| acc * a + GoldilocksExt2::from(*c));
+        assert_eq!(f_x.eval_extension(&a), expected);
+    }
+
+    #[test]
+    fn limb_arithmetic() {
+        assert_eq!(modulus_limbs::<Goldilocks>(), vec![0xffff_ffff_0000_0001]);
+        assert_eq!(mul_limbs(&[u64::MAX], &[u64::MAX]), vec![1, u64::MAX - 1]);
+        assert_eq!(shr_limbs(&[0, 1], 1), vec![1 << 63, 0]);
+        assert_eq!(trailing_zeros(&[0, 4]), 66);
+
+        // p^2 - 1 = (p - 1)(p + 1) picks up one more factor of two than p - 1
+        let order = extension_order_minus_one::<Goldilocks>(2);
+        assert_eq!(trailing_zeros(&order), 33);
+    }
+}
@@ -1,7 +1,357 @@
-use ff::PrimeField;
+use core::{fmt, hash::{Hash, Hasher}, cmp::Ordering};
 
-#[derive(PrimeField)]
-#[PrimeFieldModulus = "18446744069414584321"] // 2^64 - 2^32 + 1
-#[PrimeFieldGenerator = "3"]
-#[PrimeFieldReprEndianness = "little"]
-pub struct Goldilocks([u64; 2]);
+use ff::{Field, PrimeField, helpers::{sqrt_tonelli_shanks, sqrt_ratio_generic}};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess, CtOption};
+
+use super::{macros::impl_field_ops, extension::{Extendable, BinomialExtension}};
+
+/*
+ * The Goldilocks field, integers modulo p = 2^64 - 2^32 + 1.
+ *
+ * Elements are a single u64 and are not kept in canonical form: any u64 is a valid element, and
+ * the values in [p, 2^64) stand for their residue modulo p. Only equality, hashing, ordering and
+ * serialization reduce to canonical form. The shape of p makes reduction cheap, with
+ * EPSILON = 2^32 - 1 we have
+ *
+ *     2^64 = EPSILON      (mod p)
+ *     2^96 = -1           (mod p)
+ *
+ * so a 128-bit product lo + 2^64 * (hi_lo + 2^32 * hi_hi) reduces to lo - hi_hi + hi_lo * EPSILON,
+ * using only 64-bit additions, subtractions and a single 32x32 bit multiplication.
+*/
+
+const P: u64 = 0xffff_ffff_0000_0001;
+const EPSILON: u64 = 0xffff_ffff;
+
+// Transparent so that slices of elements can be read as slices of u64 by the packed arithmetic.
+#[derive(Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct Goldilocks(u64);
+
+#[inline(always)]
+fn reduce128(x: u128) -> u64 {
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & EPSILON;
+
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        // Wrapping around added 2^64 = EPSILON
+        t0 -= EPSILON;
+    }
+    let t1 = hi_lo * EPSILON;
+
+    let (sum, carry) = t0.overflowing_add(t1);
+    sum + EPSILON * (carry as u64)
+}
+
+impl Goldilocks {
+
+    pub const fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    // The unique representative in [0, p).
+    #[inline(always)]
+    pub fn to_canonical_u64(&self) -> u64 {
+        if self.0 >= P { self.0 - P } else { self.0 }
+    }
+
+    // The stored value, which may be anywhere in [0, 2^64).
+    #[inline(always)]
+    pub const fn to_noncanonical_u64(&self) -> u64 {
+        self.0
+    }
+
+    #[inline(always)]
+    fn add_inner(&self, rhs: &Self) -> Self {
+        let (sum, over) = self.0.overflowing_add(rhs.0);
+        let (mut sum, over) = sum.overflowing_add(EPSILON * (over as u64));
+        if over {
+            // Only reachable when both operands are non-canonical
+            sum += EPSILON;
+        }
+        Self(sum)
+    }
+
+    #[inline(always)]
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        let (diff, under) = self.0.overflowing_sub(rhs.0);
+        let (mut diff, under) = diff.overflowing_sub(EPSILON * (under as u64));
+        if under {
+            diff -= EPSILON;
+        }
+        Self(diff)
+    }
+
+    #[inline(always)]
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self(reduce128((self.0 as u128) * (rhs.0 as u128)))
+    }
+
+    #[inline(always)]
+    fn neg_inner(&self) -> Self {
+        let x = self.to_canonical_u64();
+        Self(if x == 0 { 0 } else { P - x })
+    }
+}
+
+impl_field_ops!(Goldilocks);
+
+impl PartialEq for Goldilocks {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_canonical_u64() == other.to_canonical_u64()
+    }
+}
+
+impl Eq for Goldilocks {}
+
+impl Hash for Goldilocks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_canonical_u64().hash(state);
+    }
+}
+
+impl Ord for Goldilocks {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_canonical_u64().cmp(&other.to_canonical_u64())
+    }
+}
+
+impl PartialOrd for Goldilocks {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Goldilocks(0x{:016x})", self.to_canonical_u64())
+    }
+}
+
+impl fmt::Display for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_canonical_u64())
+    }
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl ConstantTimeEq for Goldilocks {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_canonical_u64().ct_eq(&other.to_canonical_u64())
+    }
+}
+
+impl ConditionallySelectable for Goldilocks {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(u64::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Field for Goldilocks {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        // Rejection sampling, a u64 is above p with probability 2^-32
+        loop {
+            let x = rng.next_u64();
+            if x < P {
+                return Self(x);
+            }
+        }
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([P - 2]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        // (t - 1)/2 for p - 1 = 2^32 * t
+        sqrt_tonelli_shanks(self, [0x7fff_ffff])
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        sqrt_ratio_generic(num, div)
+    }
+}
+
+impl PrimeField for Goldilocks {
+    type Repr = [u8; 8];
+
+    const MODULUS: &'static str = "0xffffffff00000001";
+    const NUM_BITS: u32 = 64;
+    const CAPACITY: u32 = 63;
+    const TWO_INV: Self = Self(0x7fff_ffff_8000_0001);
+    const MULTIPLICATIVE_GENERATOR: Self = Self(7);
+    const S: u32 = 32;
+    // 7^((p - 1)/2^32) and its inverse
+    const ROOT_OF_UNITY: Self = Self(0x1856_29dc_da58_878c);
+    const ROOT_OF_UNITY_INV: Self = Self(0x76b6_b635_b6fc_8719);
+    // 7^(2^32)
+    const DELTA: Self = Self(0xaa5b_2509_f86b_b4d4);
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u64::from_le_bytes(repr);
+        CtOption::new(Self(value), value.ct_lt(&P))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.to_canonical_u64().to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_canonical_u64() & 1) as u8)
+    }
+
+    fn from_u128(value: u128) -> Self {
+        Self(reduce128(value))
+    }
+}
+
+// Goldilocks[x]/(x^2 - 7), 7 is not a square.
+pub type GoldilocksExt2 = BinomialExtension<Goldilocks, 2>;
+
+// Goldilocks[x]/(x^3 - 2), 2 is not a cube.
+pub type GoldilocksExt3 = BinomialExtension<Goldilocks, 3>;
+
+impl Extendable<2> for Goldilocks {
+    const W: Self = Self(7);
+    const DTH_ROOT: Self = Self(P - 1);
+}
+
+impl Extendable<3> for Goldilocks {
+    const W: Self = Self(2);
+    // A primitive cube root of unity
+    const DTH_ROOT: Self = Self(0xffff_ffff);
+}
+
+#[cfg(test)]
+mod goldilocks_tests {
+
+    use super::*;
+
+    // The previous, derived implementation of the same field serves as the reference.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "18446744069414584321"]
+    #[PrimeFieldGenerator = "7"]
+    #[PrimeFieldReprEndianness = "little"]
+    struct Reference([u64; 2]);
+
+    const EDGES: [u64; 16] = [
+        0, 1, 2, 7,
+        EPSILON - 1, EPSILON, EPSILON + 1,
+        1 << 32, 1 << 63,
+        P - 2, P - 1, P, P + 1,
+        u64::MAX - 1, u64::MAX,
+        0x1234_5678_9abc_def0,
+    ];
+
+    fn reference(x: u64) -> Reference {
+        Reference::from(x)
+    }
+
+    fn assert_matches(native: Goldilocks, expected: Reference) {
+        let expected = expected.to_repr();
+        assert_eq!(native.to_repr().as_slice(), &expected.as_ref()[..8]);
+        assert!(expected.as_ref()[8..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn edge_cases_against_reference() {
+        for a in EDGES {
+            let (x, rx) = (Goldilocks(a), reference(a));
+            assert_matches(x, rx);
+            assert_matches(-x, -rx);
+            assert_matches(x.square(), rx.square());
+            assert_matches(x.double(), rx.double());
+            assert_eq!(bool::from(x.is_odd()), bool::from(rx.is_odd()));
+
+            let inverse = x.invert();
+            assert_eq!(bool::from(inverse.is_some()), bool::from(rx.invert().is_some()));
+            if bool::from(inverse.is_some()) {
+                assert_matches(inverse.unwrap(), rx.invert().unwrap());
+            }
+
+            for b in EDGES {
+                let (y, ry) = (Goldilocks(b), reference(b));
+                assert_matches(x + y, rx + ry);
+                assert_matches(x - y, rx - ry);
+                assert_matches(x * y, rx * ry);
+                assert_eq!(x == y, rx == ry);
+                assert_eq!(x.cmp(&y), rx.cmp(&ry));
+            }
+        }
+    }
+
+    #[test]
+    fn random_against_reference() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64(), rng.next_u64());
+            let (x, y) = (Goldilocks(a), Goldilocks(b));
+            let (rx, ry) = (reference(a), reference(b));
+            assert_matches(x * y + x - y, rx * ry + rx - ry);
+
+            let wide = ((a as u128) << 64) | b as u128;
+            assert_matches(Goldilocks::from_u128(wide), Reference::from_u128(wide));
+        }
+    }
+
+    #[test]
+    fn constants_match_reference() {
+        assert_eq!(Goldilocks::MODULUS, Reference::MODULUS);
+        assert_eq!(Goldilocks::NUM_BITS, Reference::NUM_BITS);
+        assert_eq!(Goldilocks::S, Reference::S);
+        assert_matches(Goldilocks::TWO_INV, Reference::TWO_INV);
+        assert_matches(Goldilocks::MULTIPLICATIVE_GENERATOR, Reference::MULTIPLICATIVE_GENERATOR);
+        assert_matches(Goldilocks::ROOT_OF_UNITY, Reference::ROOT_OF_UNITY);
+        assert_matches(Goldilocks::ROOT_OF_UNITY_INV, Reference::ROOT_OF_UNITY_INV);
+        assert_matches(Goldilocks::DELTA, Reference::DELTA);
+
+        assert_eq!(Goldilocks::ROOT_OF_UNITY.pow([1 << 31]), -Goldilocks::ONE);
+        assert_eq!(Goldilocks::ROOT_OF_UNITY.pow([1 << 32]), Goldilocks::ONE);
+    }
+
+    #[test]
+    fn canonical_forms() {
+        // p and 0 are the same element, as are p + 1 and 1
+        assert_eq!(Goldilocks(P), Goldilocks::ZERO);
+        assert_eq!(Goldilocks(P + 1), Goldilocks::ONE);
+        assert!(bool::from(Goldilocks(P).is_zero()));
+        assert_eq!(Goldilocks(u64::MAX).to_canonical_u64(), EPSILON - 1);
+        assert_eq!(Goldilocks(P).to_repr(), [0u8; 8]);
+
+        // Only canonical encodings are accepted
+        assert!(bool::from(Goldilocks::from_repr((P - 1).to_le_bytes()).is_some()));
+        assert!(bool::from(Goldilocks::from_repr(P.to_le_bytes()).is_none()));
+        assert!(bool::from(Goldilocks::from_repr(u64::MAX.to_le_bytes()).is_none()));
+    }
+
+    #[test]
+    fn square_roots() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let x = Goldilocks::random(&mut rng);
+            let root = x.square().sqrt().unwrap();
+            assert!(root == x || root == -x);
+        }
+        assert!(bool::from(Goldilocks::MULTIPLICATIVE_GENERATOR.sqrt().is_none()));
+    }
+}
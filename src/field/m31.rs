@@ -0,0 +1,496 @@
+use core::fmt;
+
+use ff::{Field, PrimeField, helpers::{sqrt_tonelli_shanks, sqrt_ratio_generic}};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess, CtOption};
+
+use super::{
+    macros::impl_field_ops,
+    extension::{ExtensionField, extension_order_minus_one, find_non_residue, sqrt_tonelli_shanks_vartime, sqrt_ratio_vartime},
+};
+
+/*
+ * The Mersenne-31 field, integers modulo p = 2^31 - 1, and its degree 4 extension.
+ *
+ * Reduction is a shift and an add, since 2^31 = 1 (mod p): x = hi * 2^31 + lo = hi + lo. The price
+ * is that p - 1 = 2 * 3^2 * 7 * 11 * 31 * 151 * 331 has no large power of two, so M31 has no
+ * multiplicative subgroups to run the radix-2 FFT over (S = 1), and `Domain` and `fft` do not
+ * support it. The circle group x^2 + y^2 = 1 has p + 1 = 2^31 points instead, and
+ * `domains::circle` runs the circle FFT over its subgroups.
+ *
+ * Since p = 3 (mod 4), -1 is not a square and the extension is built as a tower:
+ *
+ *     CM31 = M31[i]/(i^2 + 1)
+ *     QM31 = CM31[u]/(u^2 - (2 + i))
+*/
+
+const P: u32 = 0x7fff_ffff;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct M31(u32);
+
+#[inline(always)]
+fn reduce(x: u64) -> u32 {
+    // x < 2^62, so the folded value is below 2^32 and one conditional subtraction is enough
+    let folded = ((x & P as u64) + (x >> 31)) as u32;
+    let folded = (folded & P) + (folded >> 31);
+    if folded >= P { folded - P } else { folded }
+}
+
+impl M31 {
+
+    pub const fn new(value: u32) -> Self {
+        Self(value % P)
+    }
+
+    pub fn to_canonical_u32(&self) -> u32 {
+        self.0
+    }
+
+    #[inline(always)]
+    fn add_inner(&self, rhs: &Self) -> Self {
+        let sum = self.0 + rhs.0;
+        Self(if sum >= P { sum - P } else { sum })
+    }
+
+    #[inline(always)]
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        let (difference, under) = self.0.overflowing_sub(rhs.0);
+        Self(if under { difference.wrapping_add(P) } else { difference })
+    }
+
+    #[inline(always)]
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self(reduce((self.0 as u64) * (rhs.0 as u64)))
+    }
+
+    #[inline(always)]
+    fn neg_inner(&self) -> Self {
+        Self::ZERO.sub_inner(self)
+    }
+}
+
+impl_field_ops!(M31);
+
+impl fmt::Debug for M31 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "M31({})", self.0)
+    }
+}
+
+impl fmt::Display for M31 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for M31 {
+    fn from(value: u64) -> Self {
+        // 2^62 = 1 (mod p), so the two bits above 2^62 are added back unchanged
+        Self(reduce(value & ((1 << 62) - 1))) + Self((value >> 62) as u32)
+    }
+}
+
+impl ConstantTimeEq for M31 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for M31 {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(u32::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Field for M31 {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        // Rejection sampling on 31 bits, only 2^31 - 1 itself is rejected
+        loop {
+            let x = rng.next_u32() >> 1;
+            if x < P {
+                return Self(x);
+            }
+        }
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([(P - 2) as u64]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        // (t - 1)/2 for p - 1 = 2 * t
+        sqrt_tonelli_shanks(self, [(1 << 29) - 1])
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        sqrt_ratio_generic(num, div)
+    }
+}
+
+impl PrimeField for M31 {
+    type Repr = [u8; 4];
+
+    const MODULUS: &'static str = "0x7fffffff";
+    const NUM_BITS: u32 = 31;
+    const CAPACITY: u32 = 30;
+    const TWO_INV: Self = Self(1 << 30);
+    const MULTIPLICATIVE_GENERATOR: Self = Self(7);
+    const S: u32 = 1;
+    const ROOT_OF_UNITY: Self = Self(P - 1);
+    const ROOT_OF_UNITY_INV: Self = Self(P - 1);
+    // 7^2
+    const DELTA: Self = Self(49);
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u32::from_le_bytes(repr);
+        CtOption::new(Self(value % P), value.ct_lt(&P))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.0 & 1) as u8)
+    }
+}
+
+// a + b * i, with i^2 = -1.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Cm31 {
+    pub a: M31,
+    pub b: M31,
+}
+
+impl Cm31 {
+
+    pub const fn new(a: M31, b: M31) -> Self {
+        Self { a, b }
+    }
+
+    // The complex conjugate a - b * i, which is also the Frobenius a -> a^p.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.a, -self.b)
+    }
+
+    // (a + b * i) * (a - b * i), an element of M31
+    pub fn norm(&self) -> M31 {
+        self.a.square() + self.b.square()
+    }
+
+    fn add_inner(&self, rhs: &Self) -> Self {
+        Self::new(self.a + rhs.a, self.b + rhs.b)
+    }
+
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        Self::new(self.a - rhs.a, self.b - rhs.b)
+    }
+
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self::new(self.a * rhs.a - self.b * rhs.b, self.a * rhs.b + self.b * rhs.a)
+    }
+
+    fn neg_inner(&self) -> Self {
+        Self::new(-self.a, -self.b)
+    }
+
+    fn non_residue(order_minus_one: &[u64]) -> Self {
+        find_non_residue(order_minus_one, Self::new(M31::ZERO, M31::ONE), Self::ONE)
+    }
+}
+
+impl_field_ops!(Cm31);
+
+impl From<M31> for Cm31 {
+    fn from(value: M31) -> Self {
+        Self::new(value, M31::ZERO)
+    }
+}
+
+impl ConstantTimeEq for Cm31 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.a.ct_eq(&other.a) & self.b.ct_eq(&other.b)
+    }
+}
+
+impl ConditionallySelectable for Cm31 {
+    fn conditional_select(x: &Self, y: &Self, choice: Choice) -> Self {
+        Self::new(M31::conditional_select(&x.a, &y.a, choice), M31::conditional_select(&x.b, &y.b, choice))
+    }
+}
+
+impl Field for Cm31 {
+    const ZERO: Self = Self::new(M31::ZERO, M31::ZERO);
+    const ONE: Self = Self::new(M31::ONE, M31::ZERO);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        Self::new(M31::random(&mut rng), M31::random(&mut rng))
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        self.norm().invert().map(|n| Self::new(self.a * n, -self.b * n))
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        let order = extension_order_minus_one::<M31>(2);
+        sqrt_tonelli_shanks_vartime(self, &order, &Self::non_residue(&order))
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        let order = extension_order_minus_one::<M31>(2);
+        sqrt_ratio_vartime(num, div, &order, &Self::non_residue(&order))
+    }
+}
+
+impl ExtensionField<M31> for Cm31 {
+    const DEGREE: usize = 2;
+
+    fn from_base_slice(coefficients: &[M31]) -> Self {
+        assert_eq!(coefficients.len(), 2, "Wrong number of coefficients for the extension.");
+        Self::new(coefficients[0], coefficients[1])
+    }
+
+    fn to_base_vec(&self) -> Vec<M31> {
+        vec![self.a, self.b]
+    }
+
+    fn scalar_mul(&self, scalar: &M31) -> Self {
+        Self::new(self.a * scalar, self.b * scalar)
+    }
+
+    fn frobenius(&self) -> Self {
+        self.conjugate()
+    }
+}
+
+// u^2
+const U_SQUARED: Cm31 = Cm31::new(M31(2), M31(1));
+
+// u^(p - 1) = (2 + i)^((p - 1)/2), so that u^p = FROBENIUS_U * u
+const FROBENIUS_U: Cm31 = Cm31::new(M31(21189756), M31(42379512));
+
+// a + b * u, with u^2 = 2 + i.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Qm31 {
+    pub a: Cm31,
+    pub b: Cm31,
+}
+
+impl Qm31 {
+
+    pub const fn new(a: Cm31, b: Cm31) -> Self {
+        Self { a, b }
+    }
+
+    fn add_inner(&self, rhs: &Self) -> Self {
+        Self::new(self.a + rhs.a, self.b + rhs.b)
+    }
+
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        Self::new(self.a - rhs.a, self.b - rhs.b)
+    }
+
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self::new(
+            self.a * rhs.a + U_SQUARED * self.b * rhs.b,
+            self.a * rhs.b + self.b * rhs.a,
+        )
+    }
+
+    fn neg_inner(&self) -> Self {
+        Self::new(-self.a, -self.b)
+    }
+
+    fn non_residue(order_minus_one: &[u64]) -> Self {
+        find_non_residue(order_minus_one, Self::new(Cm31::ZERO, Cm31::ONE), Self::ONE)
+    }
+}
+
+impl_field_ops!(Qm31);
+
+impl From<M31> for Qm31 {
+    fn from(value: M31) -> Self {
+        Self::new(Cm31::from(value), Cm31::ZERO)
+    }
+}
+
+impl From<Cm31> for Qm31 {
+    fn from(value: Cm31) -> Self {
+        Self::new(value, Cm31::ZERO)
+    }
+}
+
+impl ConstantTimeEq for Qm31 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.a.ct_eq(&other.a) & self.b.ct_eq(&other.b)
+    }
+}
+
+impl ConditionallySelectable for Qm31 {
+    fn conditional_select(x: &Self, y: &Self, choice: Choice) -> Self {
+        Self::new(Cm31::conditional_select(&x.a, &y.a, choice), Cm31::conditional_select(&x.b, &y.b, choice))
+    }
+}
+
+impl Field for Qm31 {
+    const ZERO: Self = Self::new(Cm31::ZERO, Cm31::ZERO);
+    const ONE: Self = Self::new(Cm31::ONE, Cm31::ZERO);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        Self::new(Cm31::random(&mut rng), Cm31::random(&mut rng))
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    // (a + b * u)^-1 = (a - b * u) / (a^2 - b^2 * u^2)
+    fn invert(&self) -> CtOption<Self> {
+        let denominator = self.a.square() - U_SQUARED * self.b.square();
+        denominator.invert().map(|d| Self::new(self.a * d, -self.b * d))
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        let order = extension_order_minus_one::<M31>(4);
+        sqrt_tonelli_shanks_vartime(self, &order, &Self::non_residue(&order))
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        let order = extension_order_minus_one::<M31>(4);
+        sqrt_ratio_vartime(num, div, &order, &Self::non_residue(&order))
+    }
+}
+
+// Coordinates (a.a, a.b, b.a, b.b) over M31.
+impl ExtensionField<M31> for Qm31 {
+    const DEGREE: usize = 4;
+
+    fn from_base_slice(coefficients: &[M31]) -> Self {
+        assert_eq!(coefficients.len(), 4, "Wrong number of coefficients for the extension.");
+        Self::new(Cm31::new(coefficients[0], coefficients[1]), Cm31::new(coefficients[2], coefficients[3]))
+    }
+
+    fn to_base_vec(&self) -> Vec<M31> {
+        vec![self.a.a, self.a.b, self.b.a, self.b.b]
+    }
+
+    fn scalar_mul(&self, scalar: &M31) -> Self {
+        Self::new(self.a.scalar_mul(scalar), self.b.scalar_mul(scalar))
+    }
+
+    fn frobenius(&self) -> Self {
+        Self::new(self.a.conjugate(), self.b.conjugate() * FROBENIUS_U)
+    }
+}
+
+#[cfg(test)]
+mod m31_tests {
+
+    use super::*;
+
+    // The same field through the generic derived implementation serves as the reference.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "2147483647"]
+    #[PrimeFieldGenerator = "7"]
+    #[PrimeFieldReprEndianness = "little"]
+    struct Reference([u64; 1]);
+
+    const EDGES: [u64; 12] = [
+        0, 1, 2, 7,
+        (1 << 30) - 1, 1 << 30,
+        (P - 1) as u64, P as u64, P as u64 + 1,
+        u32::MAX as u64, 1 << 62, u64::MAX,
+    ];
+
+    fn assert_matches(native: M31, expected: Reference) {
+        let expected = expected.to_repr();
+        assert_eq!(native.to_repr().as_slice(), &expected.as_ref()[..4]);
+        assert!(expected.as_ref()[4..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn edge_cases_against_reference() {
+        for a in EDGES {
+            let (x, rx) = (M31::from(a), Reference::from(a));
+            assert_matches(x, rx);
+            assert_matches(-x, -rx);
+            assert_matches(x.square(), rx.square());
+            if bool::from(rx.invert().is_some()) {
+                assert_matches(x.invert().unwrap(), rx.invert().unwrap());
+            }
+            for b in EDGES {
+                let (y, ry) = (M31::from(b), Reference::from(b));
+                assert_matches(x + y, rx + ry);
+                assert_matches(x - y, rx - ry);
+                assert_matches(x * y, rx * ry);
+            }
+        }
+    }
+
+    #[test]
+    fn constants_match_reference() {
+        assert_eq!(M31::S, Reference::S);
+        assert_matches(M31::TWO_INV, Reference::TWO_INV);
+        assert_matches(M31::ROOT_OF_UNITY, Reference::ROOT_OF_UNITY);
+        assert_matches(M31::DELTA, Reference::DELTA);
+        assert!(bool::from(M31::from_repr(P.to_le_bytes()).is_none()));
+
+        let mut rng = rand::thread_rng();
+        let x = M31::random(&mut rng);
+        assert_eq!(x.square().sqrt().unwrap().square(), x.square());
+    }
+
+    fn extension_axioms<E: ExtensionField<M31>>() {
+        let mut rng = rand::thread_rng();
+        let (a, b, c) = (E::random(&mut rng), E::random(&mut rng), E::random(&mut rng));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * a.invert().unwrap(), E::ONE);
+        assert_eq!(a.frobenius(), a.pow_vartime([P as u64]));
+        assert_eq!(a.repeated_frobenius(E::DEGREE), a);
+        assert_eq!(E::from_base_slice(&a.to_base_vec()), a);
+
+        let root = a.square().sqrt().unwrap();
+        assert!(root == a || root == -a);
+    }
+
+    #[test]
+    fn tower_extensions() {
+        extension_axioms::<Cm31>();
+        extension_axioms::<Qm31>();
+
+        // i^2 = -1 and u^2 = 2 + i
+        let i = Cm31::new(M31::ZERO, M31::ONE);
+        assert_eq!(i.square(), -Cm31::ONE);
+        let u = Qm31::new(Cm31::ZERO, Cm31::ONE);
+        assert_eq!(u.square(), Qm31::from(U_SQUARED));
+
+        // -1 has no square root in M31 but has one in CM31
+        assert!(bool::from((-M31::ONE).sqrt().is_none()));
+        assert!(bool::from((-Cm31::ONE).sqrt().is_some()));
+    }
+}
@@ -0,0 +1,99 @@
+// Operator boilerplate for the hand-written fields. The type is expected to provide the inherent
+// methods `add_inner`, `sub_inner`, `mul_inner` and `neg_inner`, everything else (owned and
+// borrowed operands, assignment operators, sums and products) is derived from those. Generic
+// types pass their parameters in brackets: `impl_field_ops!([F: Bound, const D: usize] Ext<F, D>)`.
+macro_rules! impl_field_ops {
+    ($field:ident) => {
+        impl_field_ops!([] $field);
+    };
+
+    ([$($g:tt)*] $field:ty) => {
+        impl<$($g)*> core::ops::Neg for $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn neg(self) -> $field {
+                self.neg_inner()
+            }
+        }
+
+        impl<'a, $($g)*> core::ops::Neg for &'a $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn neg(self) -> $field {
+                self.neg_inner()
+            }
+        }
+
+        impl_field_ops!(@binary [$($g)*] $field, Add, add, AddAssign, add_assign, add_inner);
+        impl_field_ops!(@binary [$($g)*] $field, Sub, sub, SubAssign, sub_assign, sub_inner);
+        impl_field_ops!(@binary [$($g)*] $field, Mul, mul, MulAssign, mul_assign, mul_inner);
+
+        impl<T: core::borrow::Borrow<$field>, $($g)*> core::iter::Sum<T> for $field {
+            fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+                iter.fold(<$field as ff::Field>::ZERO, |acc, x| acc.add_inner(x.borrow()))
+            }
+        }
+
+        impl<T: core::borrow::Borrow<$field>, $($g)*> core::iter::Product<T> for $field {
+            fn product<I: Iterator<Item = T>>(iter: I) -> Self {
+                iter.fold(<$field as ff::Field>::ONE, |acc, x| acc.mul_inner(x.borrow()))
+            }
+        }
+    };
+
+    (@binary [$($g:tt)*] $field:ty, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $inner:ident) => {
+        impl<$($g)*> core::ops::$trait<$field> for $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn $method(self, rhs: $field) -> $field {
+                self.$inner(&rhs)
+            }
+        }
+
+        impl<'a, $($g)*> core::ops::$trait<&'a $field> for $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn $method(self, rhs: &'a $field) -> $field {
+                self.$inner(rhs)
+            }
+        }
+
+        impl<'a, $($g)*> core::ops::$trait<$field> for &'a $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn $method(self, rhs: $field) -> $field {
+                self.$inner(&rhs)
+            }
+        }
+
+        impl<'a, 'b, $($g)*> core::ops::$trait<&'b $field> for &'a $field {
+            type Output = $field;
+
+            #[inline(always)]
+            fn $method(self, rhs: &'b $field) -> $field {
+                self.$inner(rhs)
+            }
+        }
+
+        impl<$($g)*> core::ops::$assign_trait<$field> for $field {
+            #[inline(always)]
+            fn $assign_method(&mut self, rhs: $field) {
+                *self = self.$inner(&rhs);
+            }
+        }
+
+        impl<'a, $($g)*> core::ops::$assign_trait<&'a $field> for $field {
+            #[inline(always)]
+            fn $assign_method(&mut self, rhs: &'a $field) {
+                *self = self.$inner(rhs);
+            }
+        }
+    };
+}
+
+pub(crate) use impl_field_ops;
@@ -1,9 +1,11 @@
-pub(crate) mod goldilocks;
+mod macros;
 
-use ff::PrimeField;
+pub mod extension;
+pub mod goldilocks;
+pub mod bn254;
+pub mod stark252;
+pub mod babybear;
+pub mod m31;
+pub mod packed;
 
-#[derive(PrimeField)]
-#[PrimeFieldModulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"] // 2^251 + 2^196 + 2^192 + 1
-#[PrimeFieldGenerator = "3"]
-#[PrimeFieldReprEndianness = "little"]
-pub struct Fp([u64; 4]);
+pub use stark252::Fp;
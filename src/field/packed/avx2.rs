@@ -0,0 +1,148 @@
+use core::arch::x86_64::*;
+
+use super::{PackedField, impl_packed_ops};
+use crate::field::goldilocks::Goldilocks;
+
+/*
+ * Four Goldilocks elements in the 64-bit lanes of a 256-bit register, stored as non-canonically as
+ * the scalar field stores them. AVX2 has neither unsigned 64-bit comparisons nor a 64x64 bit
+ * multiplication, so
+ *
+ *     x < y (unsigned)  is  (x ^ 2^63) < (y ^ 2^63) (signed)
+ *     x * y             is  assembled from the four 32x32 bit products of the halves
+ *
+ * and the 128-bit product is reduced the same way as in the scalar field. Additions and
+ * subtractions first bring the right operand below p, after which a single correction by
+ * EPSILON = 2^64 mod p is enough.
+*/
+
+const P: i64 = 0xffff_ffff_0000_0001u64 as i64;
+const EPSILON: i64 = 0xffff_ffff;
+
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub(crate) struct PackedGoldilocksAvx2(__m256i);
+
+// Lanes of all ones where x < y as unsigned integers.
+#[inline(always)]
+unsafe fn less_than(x: __m256i, y: __m256i) -> __m256i {
+    let sign = _mm256_set1_epi64x(i64::MIN);
+    _mm256_cmpgt_epi64(_mm256_xor_si256(y, sign), _mm256_xor_si256(x, sign))
+}
+
+#[inline(always)]
+unsafe fn canonicalize(x: __m256i) -> __m256i {
+    let p = _mm256_set1_epi64x(P);
+    _mm256_sub_epi64(x, _mm256_andnot_si256(less_than(x, p), p))
+}
+
+#[inline(always)]
+unsafe fn add(x: __m256i, y: __m256i) -> __m256i {
+    let y = canonicalize(y);
+    let sum = _mm256_add_epi64(x, y);
+    let carry = less_than(sum, y);
+    _mm256_add_epi64(sum, _mm256_and_si256(carry, _mm256_set1_epi64x(EPSILON)))
+}
+
+#[inline(always)]
+unsafe fn sub(x: __m256i, y: __m256i) -> __m256i {
+    let y = canonicalize(y);
+    let difference = _mm256_sub_epi64(x, y);
+    let borrow = less_than(x, y);
+    _mm256_sub_epi64(difference, _mm256_and_si256(borrow, _mm256_set1_epi64x(EPSILON)))
+}
+
+// lo + 2^64 * hi = lo - hi_hi + hi_lo * EPSILON
+#[inline(always)]
+unsafe fn reduce128(lo: __m256i, hi: __m256i) -> __m256i {
+    let epsilon = _mm256_set1_epi64x(EPSILON);
+    let hi_hi = _mm256_srli_epi64::<32>(hi);
+    let hi_lo = _mm256_and_si256(hi, epsilon);
+
+    let t0 = _mm256_sub_epi64(lo, hi_hi);
+    let t0 = _mm256_sub_epi64(t0, _mm256_and_si256(less_than(lo, hi_hi), epsilon));
+    let t1 = _mm256_sub_epi64(_mm256_slli_epi64::<32>(hi_lo), hi_lo);
+
+    let sum = _mm256_add_epi64(t0, t1);
+    _mm256_add_epi64(sum, _mm256_and_si256(less_than(sum, t1), epsilon))
+}
+
+#[inline(always)]
+unsafe fn mul(x: __m256i, y: __m256i) -> __m256i {
+    let mask = _mm256_set1_epi64x(EPSILON);
+    let x_hi = _mm256_srli_epi64::<32>(x);
+    let y_hi = _mm256_srli_epi64::<32>(y);
+
+    // _mm256_mul_epu32 multiplies the low halves of the lanes
+    let ll = _mm256_mul_epu32(x, y);
+    let lh = _mm256_mul_epu32(x, y_hi);
+    let hl = _mm256_mul_epu32(x_hi, y);
+    let hh = _mm256_mul_epu32(x_hi, y_hi);
+
+    // Bits 32 to 95 of the product, before the carries above bit 63 are moved to the high word
+    let middle = _mm256_add_epi64(
+        _mm256_srli_epi64::<32>(ll),
+        _mm256_add_epi64(_mm256_and_si256(lh, mask), _mm256_and_si256(hl, mask)),
+    );
+    let lo = _mm256_or_si256(_mm256_and_si256(ll, mask), _mm256_slli_epi64::<32>(middle));
+    let hi = _mm256_add_epi64(
+        _mm256_add_epi64(hh, _mm256_srli_epi64::<32>(middle)),
+        _mm256_add_epi64(_mm256_srli_epi64::<32>(lh), _mm256_srli_epi64::<32>(hl)),
+    );
+    reduce128(lo, hi)
+}
+
+// Only ever constructed inside `run_avx2`, after AVX2 support has been detected.
+impl PackedGoldilocksAvx2 {
+
+    #[inline(always)]
+    fn add_inner(self, rhs: Self) -> Self {
+        Self(unsafe { add(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn sub_inner(self, rhs: Self) -> Self {
+        Self(unsafe { sub(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn mul_inner(self, rhs: Self) -> Self {
+        Self(unsafe { mul(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn neg_inner(self) -> Self {
+        Self(unsafe { sub(_mm256_setzero_si256(), self.0) })
+    }
+}
+
+impl_packed_ops!(PackedGoldilocksAvx2);
+
+impl PackedField for PackedGoldilocksAvx2 {
+    type Scalar = Goldilocks;
+
+    const WIDTH: usize = 4;
+
+    #[inline(always)]
+    fn broadcast(value: Goldilocks) -> Self {
+        Self(unsafe { _mm256_set1_epi64x(value.to_noncanonical_u64() as i64) })
+    }
+
+    #[inline(always)]
+    fn load(slice: &[Goldilocks]) -> Self {
+        let lanes = &slice[..Self::WIDTH];
+        // Goldilocks is a transparent u64
+        Self(unsafe { _mm256_loadu_si256(lanes.as_ptr() as *const __m256i) })
+    }
+
+    #[inline(always)]
+    fn store(&self, slice: &mut [Goldilocks]) {
+        let lanes = &mut slice[..Self::WIDTH];
+        unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, self.0) }
+    }
+
+    #[inline(always)]
+    fn canonicalize(self) -> Self {
+        Self(unsafe { canonicalize(self.0) })
+    }
+}
@@ -0,0 +1,132 @@
+use core::arch::x86_64::*;
+
+use super::{PackedField, impl_packed_ops};
+use crate::field::goldilocks::Goldilocks;
+
+/*
+ * Eight Goldilocks elements in the 64-bit lanes of a 512-bit register. The arithmetic is the same
+ * as for AVX2, but AVX-512 compares unsigned integers directly into mask registers, which the
+ * corrections by EPSILON = 2^64 mod p are then applied under.
+*/
+
+const P: i64 = 0xffff_ffff_0000_0001u64 as i64;
+const EPSILON: i64 = 0xffff_ffff;
+
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub(crate) struct PackedGoldilocksAvx512(__m512i);
+
+#[inline(always)]
+unsafe fn canonicalize(x: __m512i) -> __m512i {
+    let p = _mm512_set1_epi64(P);
+    _mm512_mask_sub_epi64(x, _mm512_cmpge_epu64_mask(x, p), x, p)
+}
+
+#[inline(always)]
+unsafe fn add(x: __m512i, y: __m512i) -> __m512i {
+    let y = canonicalize(y);
+    let sum = _mm512_add_epi64(x, y);
+    let carry = _mm512_cmplt_epu64_mask(sum, y);
+    _mm512_mask_add_epi64(sum, carry, sum, _mm512_set1_epi64(EPSILON))
+}
+
+#[inline(always)]
+unsafe fn sub(x: __m512i, y: __m512i) -> __m512i {
+    let y = canonicalize(y);
+    let difference = _mm512_sub_epi64(x, y);
+    let borrow = _mm512_cmplt_epu64_mask(x, y);
+    _mm512_mask_sub_epi64(difference, borrow, difference, _mm512_set1_epi64(EPSILON))
+}
+
+// lo + 2^64 * hi = lo - hi_hi + hi_lo * EPSILON
+#[inline(always)]
+unsafe fn reduce128(lo: __m512i, hi: __m512i) -> __m512i {
+    let epsilon = _mm512_set1_epi64(EPSILON);
+    let hi_hi = _mm512_srli_epi64::<32>(hi);
+    let hi_lo = _mm512_and_si512(hi, epsilon);
+
+    let t0 = _mm512_sub_epi64(lo, hi_hi);
+    let t0 = _mm512_mask_sub_epi64(t0, _mm512_cmplt_epu64_mask(lo, hi_hi), t0, epsilon);
+    let t1 = _mm512_sub_epi64(_mm512_slli_epi64::<32>(hi_lo), hi_lo);
+
+    let sum = _mm512_add_epi64(t0, t1);
+    _mm512_mask_add_epi64(sum, _mm512_cmplt_epu64_mask(sum, t1), sum, epsilon)
+}
+
+#[inline(always)]
+unsafe fn mul(x: __m512i, y: __m512i) -> __m512i {
+    let mask = _mm512_set1_epi64(EPSILON);
+    let x_hi = _mm512_srli_epi64::<32>(x);
+    let y_hi = _mm512_srli_epi64::<32>(y);
+
+    let ll = _mm512_mul_epu32(x, y);
+    let lh = _mm512_mul_epu32(x, y_hi);
+    let hl = _mm512_mul_epu32(x_hi, y);
+    let hh = _mm512_mul_epu32(x_hi, y_hi);
+
+    let middle = _mm512_add_epi64(
+        _mm512_srli_epi64::<32>(ll),
+        _mm512_add_epi64(_mm512_and_si512(lh, mask), _mm512_and_si512(hl, mask)),
+    );
+    let lo = _mm512_or_si512(_mm512_and_si512(ll, mask), _mm512_slli_epi64::<32>(middle));
+    let hi = _mm512_add_epi64(
+        _mm512_add_epi64(hh, _mm512_srli_epi64::<32>(middle)),
+        _mm512_add_epi64(_mm512_srli_epi64::<32>(lh), _mm512_srli_epi64::<32>(hl)),
+    );
+    reduce128(lo, hi)
+}
+
+// Only ever constructed inside `run_avx512`, after AVX-512 support has been detected.
+impl PackedGoldilocksAvx512 {
+
+    #[inline(always)]
+    fn add_inner(self, rhs: Self) -> Self {
+        Self(unsafe { add(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn sub_inner(self, rhs: Self) -> Self {
+        Self(unsafe { sub(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn mul_inner(self, rhs: Self) -> Self {
+        Self(unsafe { mul(self.0, rhs.0) })
+    }
+
+    #[inline(always)]
+    fn neg_inner(self) -> Self {
+        Self(unsafe { sub(_mm512_setzero_si512(), self.0) })
+    }
+}
+
+impl_packed_ops!(PackedGoldilocksAvx512);
+
+impl PackedField for PackedGoldilocksAvx512 {
+    type Scalar = Goldilocks;
+
+    const WIDTH: usize = 8;
+
+    #[inline(always)]
+    fn broadcast(value: Goldilocks) -> Self {
+        Self(unsafe { _mm512_set1_epi64(value.to_noncanonical_u64() as i64) })
+    }
+
+    #[inline(always)]
+    fn load(slice: &[Goldilocks]) -> Self {
+        let lanes = &slice[..Self::WIDTH];
+        // Goldilocks is a transparent u64
+        Self(unsafe { _mm512_loadu_si512(lanes.as_ptr() as *const _) })
+    }
+
+    #[inline(always)]
+    fn store(&self, slice: &mut [Goldilocks]) {
+        let lanes = &mut slice[..Self::WIDTH];
+        unsafe { _mm512_storeu_si512(lanes.as_mut_ptr() as *mut _, self.0) }
+    }
+
+    #[inline(always)]
+    fn canonicalize(self) -> Self {
+        Self(unsafe { canonicalize(self.0) })
+    }
+}
@@ -0,0 +1,312 @@
+use core::{
+    any::TypeId,
+    fmt::Debug,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use ff::{Field, PrimeField};
+
+use super::goldilocks::Goldilocks;
+
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+#[cfg(target_arch = "x86_64")]
+mod avx512;
+
+/*
+ * Vectors of field elements operated on lane by lane, the way the prover's hot loops (FFT
+ * butterflies, pointwise products, hashing rows) want to consume them. Every prime field is its
+ * own packing of width one, which is the portable fallback. Goldilocks additionally has AVX2
+ * (4 lanes) and AVX-512 (8 lanes) packings.
+ *
+ * The SIMD packings may only be used once the CPU is known to support them, so they are not
+ * exposed. Code runs on them by implementing `PackedKernel` generically over the packing and
+ * handing the kernel to `dispatch`, which picks the widest packing available at runtime and
+ * compiles the kernel with the matching target features enabled.
+*/
+
+pub trait PackedField:
+    'static
+    + Copy
+    + Send
+    + Sync
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+{
+    type Scalar: PrimeField;
+
+    const WIDTH: usize;
+
+    // Every lane set to the same value.
+    fn broadcast(value: Self::Scalar) -> Self;
+
+    // Reads the first WIDTH elements of the slice.
+    fn load(slice: &[Self::Scalar]) -> Self;
+
+    // Writes the lanes to the first WIDTH elements of the slice.
+    fn store(&self, slice: &mut [Self::Scalar]);
+
+    // Replaces every lane by the representative the field serializes to.
+    fn canonicalize(self) -> Self;
+}
+
+impl<F: PrimeField> PackedField for F {
+    type Scalar = F;
+
+    const WIDTH: usize = 1;
+
+    #[inline(always)]
+    fn broadcast(value: F) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    fn load(slice: &[F]) -> Self {
+        slice[0]
+    }
+
+    #[inline(always)]
+    fn store(&self, slice: &mut [F]) {
+        slice[0] = *self;
+    }
+
+    #[inline(always)]
+    fn canonicalize(self) -> Self {
+        F::from_repr(self.to_repr()).unwrap()
+    }
+}
+
+// Operators for the SIMD packings, which provide `add_inner`, `sub_inner`, `mul_inner` and
+// `neg_inner` like the hand-written fields do.
+#[cfg(target_arch = "x86_64")]
+macro_rules! impl_packed_ops {
+    ($packed:ident) => {
+        impl core::ops::Neg for $packed {
+            type Output = Self;
+
+            #[inline(always)]
+            fn neg(self) -> Self {
+                self.neg_inner()
+            }
+        }
+
+        impl_packed_ops!(@binary $packed, Add, add, AddAssign, add_assign, add_inner);
+        impl_packed_ops!(@binary $packed, Sub, sub, SubAssign, sub_assign, sub_inner);
+        impl_packed_ops!(@binary $packed, Mul, mul, MulAssign, mul_assign, mul_inner);
+    };
+
+    (@binary $packed:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $inner:ident) => {
+        impl core::ops::$trait for $packed {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $method(self, rhs: Self) -> Self {
+                self.$inner(rhs)
+            }
+        }
+
+        impl core::ops::$assign_trait for $packed {
+            #[inline(always)]
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = self.$inner(rhs);
+            }
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+use impl_packed_ops;
+
+// A computation written once over any packing of F. Implementations should be #[inline(always)],
+// so that the body is compiled inside the target feature context `dispatch` sets up.
+pub trait PackedKernel<F: PrimeField> {
+    type Output;
+
+    fn run<P: PackedField<Scalar = F>>(self) -> Self::Output;
+}
+
+// A computation that writes out[i] from the inputs at index i alone. `apply` handles a range of
+// out that starts at index `start` and is made of whole packings of P.
+pub(crate) trait Lanewise<F: PrimeField> {
+    fn apply<P: PackedField<Scalar = F>>(&self, out: &mut [F], start: usize);
+}
+
+// Runs a lane-wise computation over out on packings of P, from a PackedKernel. Elements past the
+// last whole packing are handled one at a time.
+#[inline(always)]
+pub(crate) fn lanewise<F: PrimeField, P: PackedField<Scalar = F>, L: Lanewise<F>>(out: &mut [F], lanewise: &L) {
+    let split = out.len() - out.len() % P::WIDTH;
+    let (packed, rest) = out.split_at_mut(split);
+    lanewise.apply::<P>(packed, 0);
+    lanewise.apply::<F>(rest, split);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Scalar,
+    Avx2,
+    Avx512,
+}
+
+impl Backend {
+
+    // The widest packing of Goldilocks this CPU supports.
+    pub fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if std::is_x86_feature_detected!("avx512f") {
+                return Backend::Avx512;
+            }
+            if std::is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+        }
+        Backend::Scalar
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            Backend::Scalar => 1,
+            Backend::Avx2 => 4,
+            Backend::Avx512 => 8,
+        }
+    }
+}
+
+pub fn dispatch<K: PackedKernel<Goldilocks>>(kernel: K) -> K::Output {
+    dispatch_with(Backend::detect(), kernel)
+}
+
+// Runs the kernel on the given backend, which must be supported by the CPU.
+pub(crate) fn dispatch_with<K: PackedKernel<Goldilocks>>(backend: Backend, kernel: K) -> K::Output {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx512 => {
+            assert!(std::is_x86_feature_detected!("avx512f"), "AVX-512 is not supported.");
+            // The feature was just detected
+            unsafe { run_avx512(kernel) }
+        }
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => {
+            assert!(std::is_x86_feature_detected!("avx2"), "AVX2 is not supported.");
+            unsafe { run_avx2(kernel) }
+        }
+        _ => kernel.run::<Goldilocks>(),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn run_avx2<K: PackedKernel<Goldilocks>>(kernel: K) -> K::Output {
+    kernel.run::<avx2::PackedGoldilocksAvx2>()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn run_avx512<K: PackedKernel<Goldilocks>>(kernel: K) -> K::Output {
+    kernel.run::<avx512::PackedGoldilocksAvx512>()
+}
+
+// Generic code reaches the Goldilocks kernels through these, they return the same memory as
+// Goldilocks elements when F is Goldilocks and None otherwise.
+pub(crate) fn as_goldilocks<F: Field>(values: &[F]) -> Option<&[Goldilocks]> {
+    if TypeId::of::<F>() == TypeId::of::<Goldilocks>() {
+        // F and Goldilocks are the same type
+        Some(unsafe { core::slice::from_raw_parts(values.as_ptr() as *const Goldilocks, values.len()) })
+    } else {
+        None
+    }
+}
+
+pub(crate) fn as_goldilocks_mut<F: Field>(values: &mut [F]) -> Option<&mut [Goldilocks]> {
+    if TypeId::of::<F>() == TypeId::of::<Goldilocks>() {
+        Some(unsafe { core::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut Goldilocks, values.len()) })
+    } else {
+        None
+    }
+}
+
+pub(crate) fn as_goldilocks_scalar<F: Field>(value: &F) -> Option<Goldilocks> {
+    as_goldilocks(core::slice::from_ref(value)).map(|v| v[0])
+}
+
+#[cfg(test)]
+mod packed_tests {
+
+    use super::*;
+    use rand::RngCore;
+
+    // Applies every operation to random (including non-canonical) inputs and returns the results
+    // in lane order, so that the packings can be compared against the scalar field.
+    struct Operations<'a> {
+        x: &'a [Goldilocks],
+        y: &'a [Goldilocks],
+    }
+
+    impl PackedKernel<Goldilocks> for Operations<'_> {
+        type Output = Vec<Vec<Goldilocks>>;
+
+        #[inline(always)]
+        fn run<P: PackedField<Scalar = Goldilocks>>(self) -> Self::Output {
+            let mut results = vec![vec![Goldilocks::ZERO; self.x.len()]; 6];
+            for i in (0..self.x.len()).step_by(P::WIDTH) {
+                let (x, y) = (P::load(&self.x[i..]), P::load(&self.y[i..]));
+                (x + y).store(&mut results[0][i..]);
+                (x - y).store(&mut results[1][i..]);
+                (x * y).store(&mut results[2][i..]);
+                (-x).store(&mut results[3][i..]);
+                (x * P::broadcast(self.y[0])).store(&mut results[4][i..]);
+                x.canonicalize().store(&mut results[5][i..]);
+            }
+            results
+        }
+    }
+
+    fn edge_values() -> Vec<Goldilocks> {
+        let p = 0xffff_ffff_0000_0001u64;
+        let mut rng = rand::thread_rng();
+        let mut values = [0, 1, 2, 0xffff_ffff, 1 << 32, 1 << 63, p - 1, p, p + 1, u64::MAX - 1, u64::MAX]
+            .into_iter()
+            .collect::<Vec<_>>();
+        while values.len() % 8 != 0 || values.len() < 64 {
+            values.push(rng.next_u64());
+        }
+        values.into_iter().map(Goldilocks::new).collect()
+    }
+
+    #[test]
+    fn packings_agree_with_scalar() {
+        let x = edge_values();
+        let mut y = x.clone();
+        // Every pair of edge values meets in some rotation
+        for _ in 0..x.len() {
+            y.rotate_left(1);
+            let expected = Operations { x: &x, y: &y }.run::<Goldilocks>();
+            assert!(expected[5].iter().zip(&x).all(|(c, x)| c.to_noncanonical_u64() == x.to_canonical_u64()));
+            for backend in [Backend::Avx2, Backend::Avx512] {
+                if backend.width() > Backend::detect().width() {
+                    continue;
+                }
+                let results = dispatch_with(backend, Operations { x: &x, y: &y });
+                assert_eq!(results, expected, "{:?}", backend);
+                assert!(results[5].iter().zip(&x).all(|(c, x)| c.to_noncanonical_u64() == x.to_canonical_u64()));
+            }
+        }
+    }
+
+    #[test]
+    fn downcasts() {
+        let mut values = vec![Goldilocks::from(3), Goldilocks::from(4)];
+        assert_eq!(as_goldilocks(&values).unwrap()[1], Goldilocks::from(4));
+        as_goldilocks_mut(&mut values).unwrap()[0] = Goldilocks::from(5);
+        assert_eq!(values[0], Goldilocks::from(5));
+        assert_eq!(as_goldilocks_scalar(&values[1]), Some(Goldilocks::from(4)));
+        assert!(as_goldilocks(&[crate::field::Fp::ONE]).is_none());
+    }
+}
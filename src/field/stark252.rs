@@ -0,0 +1,448 @@
+use core::{fmt, hash::{Hash, Hasher}, cmp::Ordering};
+
+use ff::{Field, PrimeField, helpers::{sqrt_tonelli_shanks, sqrt_ratio_generic}};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::macros::impl_field_ops;
+
+/*
+ * The STARK-252 field used by Cairo and Starknet, integers modulo p = 2^251 + 17 * 2^192 + 1.
+ *
+ * Elements are four little endian limbs in Montgomery form a * R mod p, with R = 2^256. The
+ * modulus has only two non-zero limbs, p = [1, 0, 0, P3] with P3 = 2^59 + 17, and
+ * p = 1 (mod 2^64), so -p^-1 = -1 (mod 2^64). Every step of the Montgomery reduction
+ *
+ *     m = -t_i                    (instead of m = t_i * -p^-1)
+ *     t = t + m * p * 2^(64i)     (a single 64x64 bit product, m * P3, instead of four)
+ *
+ * is therefore much cheaper than for a dense modulus. p - 1 = 2^192 * (2^59 + 17), so the field
+ * has subgroups of order 2^k for every k <= 192 and the FFT runs over domains of any practical size.
+*/
+
+const P: [u64; 4] = [1, 0, 0, 0x0800_0000_0000_0011];
+const P3: u64 = P[3];
+
+// R^2 mod p, multiplying by it moves an integer into Montgomery form
+const R2: [u64; 4] = [0xffff_fd73_7e00_0401, 0x0000_0001_330f_ffff, 0xffff_ffff_ff6f_8000, 0x07ff_d4ab_5e00_8810];
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fp([u64; 4]);
+
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = (a as u128) + (b as u128) + (carry as u128);
+    (sum as u64, (sum >> 64) as u64)
+}
+
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let diff = (a as u128).wrapping_sub((b as u128) + (borrow as u128));
+    (diff as u64, (diff >> 127) as u64)
+}
+
+// a + b * c + carry, never overflows 128 bits
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = (a as u128) + (b as u128) * (c as u128) + (carry as u128);
+    (t as u64, (t >> 64) as u64)
+}
+
+// Subtracts p from a value below 2p.
+#[inline(always)]
+fn subtract_modulus(a: [u64; 4]) -> [u64; 4] {
+    let (d0, borrow) = sbb(a[0], P[0], 0);
+    let (d1, borrow) = sbb(a[1], P[1], borrow);
+    let (d2, borrow) = sbb(a[2], P[2], borrow);
+    let (d3, borrow) = sbb(a[3], P[3], borrow);
+    if borrow == 0 { [d0, d1, d2, d3] } else { a }
+}
+
+// t * R^-1 mod p for t < p * R.
+#[inline(always)]
+fn montgomery_reduce(mut t: [u64; 8]) -> [u64; 4] {
+    for i in 0..4 {
+        let m = t[i].wrapping_neg();
+        // t_i + m is 0 mod 2^64, with a carry unless both are zero
+        let mut carry = (t[i] != 0) as u64;
+        t[i] = 0;
+        (t[i + 1], carry) = adc(t[i + 1], 0, carry);
+        (t[i + 2], carry) = adc(t[i + 2], 0, carry);
+        (t[i + 3], carry) = mac(t[i + 3], m, P3, carry);
+        for limb in t.iter_mut().skip(i + 4) {
+            (*limb, carry) = adc(*limb, 0, carry);
+        }
+    }
+    subtract_modulus([t[4], t[5], t[6], t[7]])
+}
+
+#[inline(always)]
+fn montgomery_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut t = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0;
+        for j in 0..4 {
+            (t[i + j], carry) = mac(t[i + j], a[i], b[j], carry);
+        }
+        t[i + 4] = carry;
+    }
+    montgomery_reduce(t)
+}
+
+impl Fp {
+
+    // The canonical integer in [0, p), out of Montgomery form.
+    pub fn to_canonical_limbs(&self) -> [u64; 4] {
+        let l = self.0;
+        montgomery_reduce([l[0], l[1], l[2], l[3], 0, 0, 0, 0])
+    }
+
+    fn from_canonical_limbs(limbs: [u64; 4]) -> Self {
+        Self(montgomery_mul(&limbs, &R2))
+    }
+
+    #[inline(always)]
+    fn add_inner(&self, rhs: &Self) -> Self {
+        // Both operands are below p < 2^252, so the sum cannot overflow
+        let (d0, carry) = adc(self.0[0], rhs.0[0], 0);
+        let (d1, carry) = adc(self.0[1], rhs.0[1], carry);
+        let (d2, carry) = adc(self.0[2], rhs.0[2], carry);
+        let (d3, _) = adc(self.0[3], rhs.0[3], carry);
+        Self(subtract_modulus([d0, d1, d2, d3]))
+    }
+
+    #[inline(always)]
+    fn sub_inner(&self, rhs: &Self) -> Self {
+        let (d0, borrow) = sbb(self.0[0], rhs.0[0], 0);
+        let (d1, borrow) = sbb(self.0[1], rhs.0[1], borrow);
+        let (d2, borrow) = sbb(self.0[2], rhs.0[2], borrow);
+        let (d3, borrow) = sbb(self.0[3], rhs.0[3], borrow);
+
+        // Add p back on underflow, p * borrow has the same sparse shape as p
+        let (d0, carry) = adc(d0, borrow, 0);
+        let (d1, carry) = adc(d1, 0, carry);
+        let (d2, carry) = adc(d2, 0, carry);
+        let (d3, _) = adc(d3, P3 * borrow, carry);
+        Self([d0, d1, d2, d3])
+    }
+
+    #[inline(always)]
+    fn mul_inner(&self, rhs: &Self) -> Self {
+        Self(montgomery_mul(&self.0, &rhs.0))
+    }
+
+    #[inline(always)]
+    fn neg_inner(&self) -> Self {
+        Self::ZERO.sub_inner(self)
+    }
+}
+
+impl_field_ops!(Fp);
+
+impl Hash for Fp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+// Elements are ordered as the integers they represent.
+impl Ord for Fp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.to_canonical_limbs(), other.to_canonical_limbs());
+        a.iter().rev().cmp(b.iter().rev())
+    }
+}
+
+impl PartialOrd for Fp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for Fp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fp({})", self)
+    }
+}
+
+impl fmt::Display for Fp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let l = self.to_canonical_limbs();
+        write!(f, "0x{:016x}{:016x}{:016x}{:016x}", l[3], l[2], l[1], l[0])
+    }
+}
+
+impl From<u64> for Fp {
+    fn from(value: u64) -> Self {
+        Self::from_canonical_limbs([value, 0, 0, 0])
+    }
+}
+
+impl ConstantTimeEq for Fp {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self([
+            u64::conditional_select(&a.0[0], &b.0[0], choice),
+            u64::conditional_select(&a.0[1], &b.0[1], choice),
+            u64::conditional_select(&a.0[2], &b.0[2], choice),
+            u64::conditional_select(&a.0[3], &b.0[3], choice),
+        ])
+    }
+}
+
+impl Field for Fp {
+    const ZERO: Self = Self([0, 0, 0, 0]);
+    // R mod p
+    const ONE: Self = Self([0xffff_ffff_ffff_ffe1, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x07ff_ffff_ffff_fdf0]);
+
+    fn random(mut rng: impl RngCore) -> Self {
+        // Reducing 512 random bits leaves a bias of about 2^-260
+        let mut t = [0u64; 8];
+        for limb in t.iter_mut() {
+            *limb = rng.next_u64();
+        }
+        let low = Self(montgomery_reduce([t[0], t[1], t[2], t[3], 0, 0, 0, 0]));
+        let high = Self(montgomery_reduce([t[4], t[5], t[6], t[7], 0, 0, 0, 0]));
+        // low and high stand for t_low * R^-2 and t_high * R^-2, so the element
+        // t_low + t_high * R is recovered with multiplications by R
+        (low + high * Self(R2)) * Self(R2) * Self(R2)
+    }
+
+    fn square(&self) -> Self {
+        self.mul_inner(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add_inner(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        // p - 2
+        let exponent = [0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, P3 - 1];
+        CtOption::new(self.pow_vartime(exponent), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        // (t - 1)/2 for p - 1 = 2^192 * t, t = 2^59 + 17
+        sqrt_tonelli_shanks(self, [(1 << 58) + 8])
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        sqrt_ratio_generic(num, div)
+    }
+}
+
+impl PrimeField for Fp {
+    type Repr = [u8; 32];
+
+    const MODULUS: &'static str = "0x800000000000011000000000000000000000000000000000000000000000001";
+    const NUM_BITS: u32 = 252;
+    const CAPACITY: u32 = 251;
+    const TWO_INV: Self = Self([0xffff_ffff_ffff_fff1, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x07ff_ffff_ffff_ff00]);
+    const MULTIPLICATIVE_GENERATOR: Self = Self([0xffff_ffff_ffff_ffa1, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x07ff_ffff_ffff_f9b0]);
+    const S: u32 = 192;
+    // 3^t and its inverse
+    const ROOT_OF_UNITY: Self = Self([0x4106_bccd_64a2_bdd8, 0xaaad_a257_31fe_3be9, 0x0a35_c5be_6050_5574, 0x0722_2e32_c47a_fc26]);
+    const ROOT_OF_UNITY_INV: Self = Self([0xa466_faaf_f264_cc44, 0xae39_84dc_7ebb_0392, 0xbd6b_e289_f2ad_1083, 0x0379_d13e_525e_2be0]);
+    // 3^(2^192)
+    const DELTA: Self = Self([0x4572_264b_618d_1611, 0xd06b_f0e3_fe1b_244b, 0xbfcd_878e_97bf_dedc, 0x05c7_6625_5a6f_90bf]);
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let mut limbs = [0u64; 4];
+        for (limb, bytes) in limbs.iter_mut().zip(repr.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+
+        // Only canonical encodings, below p, are accepted
+        let (_, borrow) = sbb(limbs[0], P[0], 0);
+        let (_, borrow) = sbb(limbs[1], P[1], borrow);
+        let (_, borrow) = sbb(limbs[2], P[2], borrow);
+        let (_, borrow) = sbb(limbs[3], P[3], borrow);
+        CtOption::new(Self::from_canonical_limbs(limbs), Choice::from(borrow as u8))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        let mut repr = [0u8; 32];
+        for (bytes, limb) in repr.chunks_exact_mut(8).zip(self.to_canonical_limbs()) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        repr
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_canonical_limbs()[0] & 1) as u8)
+    }
+}
+
+#[cfg(test)]
+mod stark252_tests {
+
+    use super::*;
+    use crate::{
+        domains::Domain,
+        fft::serial,
+        polynomial::Polynomial,
+        fri::BatchFriProof,
+        FriProof,
+    };
+
+    // The previous, derived implementation of the same field serves as the reference.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"]
+    #[PrimeFieldGenerator = "3"]
+    #[PrimeFieldReprEndianness = "little"]
+    struct Reference([u64; 4]);
+
+    fn edges() -> Vec<[u64; 4]> {
+        let p_minus = |k: u64| {
+            let (d0, borrow) = sbb(P[0], k, 0);
+            let (d1, borrow) = sbb(P[1], 0, borrow);
+            let (d2, borrow) = sbb(P[2], 0, borrow);
+            let (d3, _) = sbb(P[3], 0, borrow);
+            [d0, d1, d2, d3]
+        };
+        vec![
+            [0, 0, 0, 0],
+            [1, 0, 0, 0],
+            [2, 0, 0, 0],
+            [u64::MAX, 0, 0, 0],
+            [0, 1, 0, 0],
+            [u64::MAX, u64::MAX, u64::MAX, 0],
+            [0, 0, 0, 1 << 59],
+            [0, 0, 0, P3 - 1],
+            p_minus(1),
+            p_minus(2),
+            [0x1234_5678_9abc_def0, 0x0fed_cba9_8765_4321, 0xdead_beef_dead_beef, 0x0123_4567_89ab_cdef],
+        ]
+    }
+
+    fn to_bytes(limbs: [u64; 4]) -> [u8; 32] {
+        let mut repr = [0u8; 32];
+        for (bytes, limb) in repr.chunks_exact_mut(8).zip(limbs) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        repr
+    }
+
+    fn assert_matches(native: Fp, expected: Reference) {
+        assert_eq!(native.to_repr().as_slice(), expected.to_repr().as_ref());
+    }
+
+    #[test]
+    fn edge_cases_against_reference() {
+        for a in edges() {
+            let x = Fp::from_repr(to_bytes(a)).unwrap();
+            let rx = Reference::from_repr(ReferenceRepr(to_bytes(a))).unwrap();
+            assert_matches(x, rx);
+            assert_matches(-x, -rx);
+            assert_matches(x.square(), rx.square());
+            assert_matches(x.double(), rx.double());
+            assert_eq!(bool::from(x.is_odd()), bool::from(rx.is_odd()));
+            assert_eq!(bool::from(x.invert().is_some()), bool::from(rx.invert().is_some()));
+            if bool::from(x.invert().is_some()) {
+                assert_matches(x.invert().unwrap(), rx.invert().unwrap());
+            }
+
+            for b in edges() {
+                let y = Fp::from_repr(to_bytes(b)).unwrap();
+                let ry = Reference::from_repr(ReferenceRepr(to_bytes(b))).unwrap();
+                assert_matches(x + y, rx + ry);
+                assert_matches(x - y, rx - ry);
+                assert_matches(x * y, rx * ry);
+                assert_eq!(x.cmp(&y), rx.cmp(&ry));
+            }
+        }
+    }
+
+    #[test]
+    fn random_against_reference() {
+        let mut rng = rand::thread_rng();
+        for i in 0..1000u64 {
+            let x = Fp::random(&mut rng);
+            let rx = Reference::from_repr(ReferenceRepr(x.to_repr())).unwrap();
+            assert_matches(x * x + x - Fp::from(i), rx * rx + rx - Reference::from(i));
+        }
+    }
+
+    #[test]
+    fn constants_match_reference() {
+        assert_eq!(Fp::NUM_BITS, Reference::NUM_BITS);
+        assert_eq!(Fp::S, Reference::S);
+        assert_matches(Fp::ONE, Reference::ONE);
+        assert_matches(Fp::TWO_INV, Reference::TWO_INV);
+        assert_matches(Fp::MULTIPLICATIVE_GENERATOR, Reference::MULTIPLICATIVE_GENERATOR);
+        assert_matches(Fp::ROOT_OF_UNITY, Reference::ROOT_OF_UNITY);
+        assert_matches(Fp::ROOT_OF_UNITY_INV, Reference::ROOT_OF_UNITY_INV);
+        assert_matches(Fp::DELTA, Reference::DELTA);
+        // R^2 in Montgomery form is the integer R
+        assert_eq!(Fp(super::R2), Fp::from_repr(to_bytes(Fp::ONE.0)).unwrap());
+
+        // Only canonical encodings are accepted
+        assert!(bool::from(Fp::from_repr(to_bytes(P)).is_none()));
+        assert!(bool::from(Fp::from_repr([0xff; 32]).is_none()));
+    }
+
+    #[test]
+    fn two_adicity() {
+        assert_eq!(Fp::S, 192);
+        let mut root = Fp::ROOT_OF_UNITY;
+        for _ in 0..191 {
+            root = root.square();
+            assert!(root != Fp::ONE);
+        }
+        assert_eq!(root, -Fp::ONE);
+        assert_eq!(root.square(), Fp::ONE);
+
+        // The largest domain any u64 size asks for
+        let domain = Domain::<Fp>::new_for_size(1 << 63).unwrap();
+        assert_eq!(domain.generator.pow_vartime([1u64 << 62]), -Fp::ONE);
+    }
+
+    #[test]
+    fn square_roots() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let x = Fp::random(&mut rng);
+            let root = x.square().sqrt().unwrap();
+            assert!(root == x || root == -x);
+        }
+        assert!(bool::from(Fp::MULTIPLICATIVE_GENERATOR.sqrt().is_none()));
+    }
+
+    #[test]
+    fn fft_over_fp() {
+        let mut rng = rand::thread_rng();
+        let coefficients = (0..16).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+        let f_x = Polynomial::from_vec(coefficients.clone());
+
+        let omega: Fp = Domain::new_for_size(16).unwrap().generator;
+        let mut evaluations = coefficients.clone();
+        serial::serial_fft(evaluations.as_mut_slice(), &omega, 4);
+        for (i, e) in evaluations.iter().enumerate() {
+            assert_eq!(*e, f_x.eval_single(&omega.pow([i as u64])));
+        }
+
+        serial::serial_ifft(evaluations.as_mut_slice(), &omega, 4);
+        assert_eq!(evaluations, coefficients);
+    }
+
+    #[test]
+    fn fri_over_fp() {
+        let mut rng = rand::thread_rng();
+        let f_x = Polynomial::from_vec((0..16).map(|_| Fp::random(&mut rng)).collect());
+        assert!(FriProof::evaluation_proof(&f_x, None).verify().is_valid());
+
+        let polynomials = (0..3)
+            .map(|_| Polynomial::from_vec((0..8).map(|_| Fp::random(&mut rng)).collect()))
+            .collect::<Vec<_>>();
+        let commitments = polynomials.iter().map(|p| p.commitment()).collect::<Vec<_>>();
+        let r = Fp::random(&mut rng);
+        let proof = BatchFriProof::prove(&polynomials, &commitments.iter().cloned().map(Into::into).collect::<Vec<_>>(), r).unwrap();
+        assert!(proof.verify(&commitments, &r).is_valid());
+    }
+}
@@ -1,9 +1,8 @@
 use crate::{
-    fri::{AuthenticationPath, AuthenticationHash},
-    fft::serial,
-    domains::Domain,
+    fri::{AuthenticationPath, AuthenticationHash, Salt, commitment::hash_leaf},
     constants::*,
     polynomial::Polynomial,
+    error::SynthesisError,
 };
 
 use ff::PrimeField;
@@ -15,15 +14,20 @@ impl<F: PrimeField> AuthenticationPath<F> {
         Self {
             first_evaluation,
             second_evaluation,
+            salt: None,
             authentication_path,
         }
     }
 
+    // Attaches the salt of the opened leaf so that the root of a salted commitment can be
+    // reconstructed.
+    pub fn with_salt(mut self, salt: Salt) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
     pub fn derive_root(&self) -> Hash {
-        let mut hasher = Hasher::new();
-        hasher.update(self.first_evaluation.to_repr().as_ref());
-        hasher.update(self.second_evaluation.to_repr().as_ref());
-        let mut target = hasher.finalize();
+        let mut target = hash_leaf(&self.first_evaluation, &self.second_evaluation, self.salt.as_ref());
 
         /*
          * Reconstructs the root from log_d hashes:
@@ -73,18 +77,23 @@ impl AuthenticationHash {
 }
 
 impl<F: PrimeField> Polynomial<F> {
-    pub(crate) fn authentication_path_for(&self, root: &F) -> AuthenticationPath<F> {
+    // The opening of the leaf holding the evaluation at `root`, which has to be a point of the
+    // blown-up evaluation domain.
+    pub(crate) fn authentication_path_for(&self, root: &F) -> Result<AuthenticationPath<F>, SynthesisError> {
+        self.salted_authentication_path_for(root, None)
+    }
 
-        let target = self.eval_single(root);
+    // Same as above, but the leaves are hashed together with the salts that were used when the
+    // polynomial was committed to. Salts are indexed by leaf, i.e. one salt per pair of
+    // evaluations.
+    pub(crate) fn salted_authentication_path_for(&self, root: &F, salts: Option<&[Salt]>) -> Result<AuthenticationPath<F>, SynthesisError> {
 
-        let log_n = self.log_n() + FRI_BLOWUP_LOG;
-        let honest_base_generator = Domain::root_with_order_unchecked((self.len() * FRI_BLOWUP_FACTOR) as u64);
-        let mut evaluations = self.coefficients();
-        evaluations.append(&mut vec![F::ZERO; (1<<log_n) - (1<<self.log_n())]);
+        let target = self.eval_single(root);
 
-        serial::serial_fft(evaluations.as_mut_slice(), &honest_base_generator, log_n as u32);
+        let log_n = self.commitment_len().trailing_zeros() as usize + FRI_BLOWUP_LOG;
+        let evaluations = self.commitment_evaluations();
 
-        let mut hash_vec = Vec::with_capacity((self.len()/2)-1);
+        let mut hash_vec = Vec::with_capacity(self.commitment_leaves() - 1);
         let mut authentication_vec = Vec::with_capacity(log_n);
         let mut first_evaluation: Option<F> = None;
         let mut second_evaluation: Option<F> = None;
@@ -96,13 +105,14 @@ impl<F: PrimeField> Polynomial<F> {
                 index = i/2;
             }
             else {
-                let mut hasher = Hasher::new();
-                hasher.update(evaluations[i].to_repr().as_ref());
-                hasher.update(evaluations[i+1].to_repr().as_ref());
-                hash_vec.push(hasher.finalize());
+                let salt = salts.map(|s| &s[i/2]);
+                hash_vec.push(hash_leaf(&evaluations[i], &evaluations[i+1], salt));
             }
         }
-        assert!(first_evaluation.is_some(), "Polynomial does not have root.");
+        let (Some(first_evaluation), Some(second_evaluation)) = (first_evaluation, second_evaluation) else {
+            return Err(SynthesisError::PointNotInDomain);
+        };
+        let index_of_leaf = index;
 
         for _i in 0..(log_n-2) {
 
@@ -125,6 +135,10 @@ impl<F: PrimeField> Polynomial<F> {
 
         assert!(hash_vec.len() == 1);
         authentication_vec.push(AuthenticationHash::new(hash_vec[0], is_first));
-        AuthenticationPath::new(first_evaluation.unwrap(), second_evaluation.unwrap(), authentication_vec)
+        let path = AuthenticationPath::new(first_evaluation, second_evaluation, authentication_vec);
+        Ok(match salts {
+            Some(s) => path.with_salt(s[index_of_leaf]),
+            None => path,
+        })
     }
 }
@@ -1,37 +1,79 @@
 use ff::PrimeField;
-use blake3::{Hasher};
+use blake3::{Hasher, Hash};
 use primitive_types::U256;
 
 use crate::{
     polynomial::Polynomial,
-    fri::{FriCommitment},
-    fft::serial,
+    fri::{FriCommitment, Salt},
+    fft,
     constants::*,
-    domains::Domain,
+    field::extension::ExtensionField,
 };
 
+// Hashes a pair of neighbouring evaluations into a Merkle leaf. In zero-knowledge mode the leaf
+// is prefixed with a random salt, so that the leaf hashes themselves reveal nothing about the
+// evaluations.
+pub(crate) fn hash_leaf<F: PrimeField>(first: &F, second: &F, salt: Option<&Salt>) -> Hash {
+    let mut hasher = Hasher::new();
+    if let Some(s) = salt {
+        hasher.update(s.as_slice());
+    }
+    hasher.update(first.to_repr().as_ref());
+    hasher.update(second.to_repr().as_ref());
+    hasher.finalize()
+}
+
+// Offset of the coset that FRI layers with `domain_size` evaluations are committed over,
+// g^(2^S / domain_size) for g the multiplicative generator. Its domain_size-th power is g^(2^S),
+// which is not one, so the coset misses the subgroup of that size and with it every constraint
+// domain H: a query never opens a blinded witness at a point of H, where it still equals the
+// witness. The square of the offset is the offset for half the size, which is the domain the
+// folded layer lives on.
+pub(crate) fn domain_offset<F: PrimeField>(domain_size: usize) -> F {
+    let mut offset = F::MULTIPLICATIVE_GENERATOR;
+    for _ in domain_size.trailing_zeros()..F::S {
+        offset = offset.square();
+    }
+    offset
+}
+
 impl<F: PrimeField> Polynomial<F> {
 
     pub(crate) fn commitment(&self) -> FriCommitment<F> {
+        self.salted_root(None)
+    }
 
-        // FRI commitment is evaluation of a polynomial across the dp-th roots of unity, where d is
-        // the degree of the polynomial and p is the FRI_BLOWUP_FACTOR constant.
+    // Number of coefficients the commitment to this polynomial is made for: the smallest power of
+    // two holding every coefficient, and at least two, so that even a constant is folded once.
+    pub(crate) fn commitment_len(&self) -> usize {
+        self.len().next_power_of_two().max(2)
+    }
 
+    // Number of evaluations the commitment to this polynomial is made of.
+    pub(crate) fn commitment_domain_size(&self) -> usize {
+        self.commitment_len() * FRI_BLOWUP_FACTOR
+    }
+
+    // The evaluations over the coset of that size, in the order they are committed.
+    pub(crate) fn commitment_evaluations(&self) -> Vec<F> {
         let mut evaluations = self.coefficients();
-        let log_n = {
-            let mut x = 0usize;
-            let mut y = evaluations.len();
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
-        let extended_log_n = log_n + FRI_BLOWUP_LOG;
-        let omega = Domain::root_with_order_unchecked((FRI_BLOWUP_FACTOR * evaluations.len()) as u64);
-        
-        evaluations.append(&mut vec![F::ZERO; (1<<extended_log_n) - (1<<log_n)]);
-        serial::serial_fft(evaluations.as_mut_slice(), &omega, extended_log_n as u32);
+        evaluations.resize(self.commitment_domain_size(), F::ZERO);
+        fft::coset_fft(evaluations.as_mut_slice(), domain_offset(self.commitment_domain_size()))
+            .expect("Commitment domain is larger than the field supports.");
+        evaluations
+    }
+
+    // Number of leaves in the Merkle tree of the commitment to this polynomial.
+    pub(crate) fn commitment_leaves(&self) -> usize {
+        self.commitment_domain_size() / 2
+    }
+
+    pub(crate) fn salted_root(&self, salts: Option<&[Salt]>) -> FriCommitment<F> {
+
+        // FRI commitment is evaluation of a polynomial across a coset of the dp-th roots of unity,
+        // where d is the degree of the polynomial and p is the FRI_BLOWUP_FACTOR constant.
+
+        let evaluations = self.commitment_evaluations();
 
         // Create the merkle tree out of the evaluations of the dp-th roots of unity:
         // f(w^0) --
@@ -50,15 +92,16 @@ impl<F: PrimeField> Polynomial<F> {
         //          | H(f6|f7) -- 
         // f(w^7) -- 
 
+        if let Some(s) = salts {
+            assert_eq!(s.len(), evaluations.len() / 2, "One salt is needed per leaf.");
+        }
+
         let mut hash_vector = Vec::new();
         for i in (0..evaluations.len()).step_by(2) {
-            let mut hash = Hasher::new();
-            hash.update(evaluations[i].to_repr().as_ref());
-            hash.update(evaluations[i+1].to_repr().as_ref());
-            hash_vector.push(hash.finalize());
+            hash_vector.push(hash_leaf(&evaluations[i], &evaluations[i+1], salts.map(|s| &s[i/2])));
         }
 
-        for _ in 0..(extended_log_n-1) {
+        while hash_vector.len() > 1 {
             let mut new_hash_vector = Vec::new();
             for i in (0..hash_vector.len()).step_by(2) {
                 let mut hasher = Hasher::new();
@@ -78,9 +121,22 @@ impl<F: PrimeField> FriCommitment<F> {
         crate::utils::field_element_from_bytes(self.value().as_bytes().as_slice())
     }
 
-    pub fn interpret_as_root_of_unity(&self, domain_size: u64) -> F {
+    // Challenge in an extension of F. Every coefficient is read from its own hash of the
+    // commitment, so the challenge has the entropy of the whole extension rather than of F.
+    pub fn interpret_as_extension_element<E: ExtensionField<F>>(&self) -> E {
+        let coefficients = (0..E::DEGREE).map(|i| {
+            let mut hasher = Hasher::new();
+            hasher.update(self.value().as_bytes().as_slice());
+            hasher.update((i as u64).to_le_bytes().as_slice());
+            crate::utils::field_element_from_bytes(hasher.finalize().as_bytes().as_slice())
+        }).collect::<Vec<F>>();
+        E::from_base_slice(&coefficients)
+    }
+
+    // A point of the coset the FRI layer of `domain_size` evaluations is committed over.
+    pub fn interpret_as_query_point(&self, domain_size: u64) -> F {
         let random_exponent = U256::from_big_endian(self.value().as_bytes().as_slice()).low_u64();
         let base: F = crate::domains::Domain::root_with_order_unchecked(domain_size);
-        base.pow([random_exponent])
+        domain_offset::<F>(domain_size as usize) * base.pow([random_exponent])
     }
 }
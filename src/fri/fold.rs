@@ -3,23 +3,17 @@ use blake3::{Hasher, Hash};
 
 use crate::{
     polynomial::Polynomial,
-    fri::{FriCommitment, FriChallenge},
+    fri::{FriCommitment, FriChallenge, commitment::hash_leaf},
     constants::*,
 };
 
 impl<F: PrimeField> Polynomial<F> {
 
     pub fn fold_full(&self) -> (Vec<FriCommitment<F>>, Vec<Self>) {
-        let mut target_length = self.len()/2;
-        let log_n = {
-            let mut x = 0usize;
-            let mut y = self.len();
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
+        // Every fold halves the length, so the coefficients are padded to a power of two first, of
+        // at least two so that there is one fold
+        let log_n = self.commitment_len().trailing_zeros() as usize;
+        let mut target_length = (1 << log_n) / 2;
 
         let mut commitment_vector = Vec::with_capacity(log_n);
         let mut polynomial_vector = Vec::with_capacity(log_n);
@@ -71,15 +65,14 @@ impl<F: PrimeField> FriChallenge<F> {
             target = target.square()
         }
 
-        let mut evals: [Hash; FRI_BLOWUP_FACTOR/2] = [Hash::from(ZERO_BYTES); FRI_BLOWUP_FACTOR/2];
-        for eval in evals.iter_mut().take(FRI_BLOWUP_FACTOR/2) {
-            let mut hasher = Hasher::new();
-            hasher.update(assembled.to_repr().as_ref());
-            hasher.update(assembled.to_repr().as_ref());
-            *eval = hasher.finalize();
+        // The constant is committed like any polynomial of fewer than two coefficients, over
+        // 2 * FRI_BLOWUP_FACTOR points
+        let mut evals: [Hash; FRI_BLOWUP_FACTOR] = [Hash::from(ZERO_BYTES); FRI_BLOWUP_FACTOR];
+        for eval in evals.iter_mut() {
+            *eval = hash_leaf(&assembled, &assembled, None);
         }
 
-        for _ in 0..FRI_BLOWUP_LOG-1 {
+        for _ in 0..FRI_BLOWUP_LOG {
             for (c, i) in (0..evals.len()).step_by(2).enumerate() {
                 let mut hasher = Hasher::new();
                 hasher.update(evals[i].as_bytes().as_slice());
@@ -0,0 +1,387 @@
+use ff::PrimeField;
+use blake3::{Hasher, Hash};
+use rand::RngCore;
+
+use crate::{
+    polynomial::Polynomial,
+    fri::{FriCommitment, Salt, domain_offset},
+    error::{SynthesisError, VerificationResult, RejectionReason},
+    fft::{FftPlan, MatrixLayout, coset::scale_by_powers},
+    domains::Domain,
+    constants::*,
+    field::{goldilocks::Goldilocks, packed::{self, PackedField, PackedKernel}},
+};
+
+/*
+ * Commitment to a batch of polynomials with a single Merkle tree. Every polynomial is evaluated
+ * over the same blown-up coset c * <w>, and leaf i is the hash of row i of the resulting matrix:
+ *
+ *             p_0        p_1          p_k
+ * cw^0  [ p_0(cw^0) p_1(cw^0) ... p_k(cw^0) ] -- H(row 0) --
+ *                                                           | ...
+ * cw^1  [ p_0(cw^1) p_1(cw^1) ... p_k(cw^1) ] -- H(row 1) --
+ *  .
+ *  .
+ *
+ * Opening leaf i returns the whole row, so one authentication path covers every polynomial in
+ * the batch. The offset c is the one single-polynomial FRI commitments use, so no row is an
+ * evaluation on the constraint domain, which a salt alone would not hide.
+*/
+
+#[derive(Debug, Clone)]
+pub struct MatrixCommitment<F: PrimeField> {
+    root: FriCommitment<F>,
+    rows: Vec<Vec<F>>,
+    salts: Option<Vec<Salt>>,
+    // Layers of the Merkle tree, starting at the leaves and ending at the root.
+    layers: Vec<Vec<Hash>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RowOpening<F: PrimeField> {
+    index: usize,
+    row: Vec<F>,
+    salt: Option<Salt>,
+    siblings: Vec<Hash>,
+}
+
+fn hash_row<F: PrimeField>(row: &[F], salt: Option<&Salt>) -> Hash {
+    let mut hasher = Hasher::new();
+    if let Some(s) = salt {
+        hasher.update(s.as_slice());
+    }
+    match packed::as_goldilocks(row) {
+        Some(row) => {
+            hasher.update(&packed::dispatch(SerializeRow { row }));
+        }
+        None => {
+            for element in row {
+                hasher.update(element.to_repr().as_ref());
+            }
+        }
+    }
+    hasher.finalize()
+}
+
+// The bytes hash_row hashes for a row of Goldilocks elements, i.e. the concatenated reprs, with
+// the reduction to canonical form done on whole packings.
+struct SerializeRow<'a> {
+    row: &'a [Goldilocks],
+}
+
+impl PackedKernel<Goldilocks> for SerializeRow<'_> {
+    type Output = Vec<u8>;
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = Goldilocks>>(self) -> Vec<u8> {
+        let mut canonical = self.row.to_vec();
+        let mut chunks = canonical.chunks_exact_mut(P::WIDTH);
+        for chunk in &mut chunks {
+            P::load(chunk).canonicalize().store(chunk);
+        }
+        for element in chunks.into_remainder() {
+            *element = element.canonicalize();
+        }
+        canonical.iter().flat_map(|e| e.to_noncanonical_u64().to_le_bytes()).collect()
+    }
+}
+
+impl<F: PrimeField> MatrixCommitment<F> {
+
+    pub fn new(polynomials: &[Polynomial<F>]) -> Result<Self, SynthesisError> {
+        Self::commit(polynomials, None)
+    }
+
+    // Zero-knowledge variant, every row is hashed together with a random salt.
+    pub fn new_salted<R: RngCore>(polynomials: &[Polynomial<F>], rng: &mut R) -> Result<Self, SynthesisError> {
+        let leaves = Self::domain_size_for(polynomials)?;
+        let salts = (0..leaves).map(|_| {
+            let mut salt = [0u8; SALT_SIZE];
+            rng.fill_bytes(&mut salt);
+            salt
+        }).collect();
+        Self::commit(polynomials, Some(salts))
+    }
+
+    // EmptyBatch without polynomials, DomainTooLarge if the blown-up domain does not fit a usize.
+    fn domain_size_for(polynomials: &[Polynomial<F>]) -> Result<usize, SynthesisError> {
+        let len = polynomials.iter().map(|p| p.len()).max().ok_or(SynthesisError::EmptyBatch)?;
+        len.checked_next_power_of_two()
+            .and_then(|n| n.checked_mul(FRI_BLOWUP_FACTOR))
+            .ok_or(SynthesisError::DomainTooLarge(u64::MAX))
+    }
+
+    fn commit(polynomials: &[Polynomial<F>], salts: Option<Vec<Salt>>) -> Result<Self, SynthesisError> {
+
+        let domain_size = Self::domain_size_for(polynomials)?;
+        let plan = FftPlan::for_size(domain_size)?;
+        let offset = domain_offset::<F>(domain_size);
+
+        // Low degree extension of every column over the coset. Shorter polynomials are padded
+        // with zero coefficients, which does not change them, and coefficient j is scaled by c^j
+        // so that the plain FFT evaluates at c * w^i.
+        let columns = polynomials.iter().flat_map(|p| {
+            let mut coefficients = p.coefficients();
+            coefficients.resize(domain_size, F::ZERO);
+            scale_by_powers(coefficients.as_mut_slice(), offset);
+            coefficients
+        }).collect::<Vec<_>>();
+
+        let rows = plan
+            .batch_fft(&columns, polynomials.len(), MatrixLayout::ColumnMajor)
+            .chunks_exact(polynomials.len())
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+
+        let leaves = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| hash_row(row, salts.as_ref().map(|s| &s[i])))
+            .collect::<Vec<_>>();
+
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers.last().unwrap().chunks_exact(2).map(|pair| {
+                let mut hasher = Hasher::new();
+                hasher.update(pair[0].as_bytes().as_slice());
+                hasher.update(pair[1].as_bytes().as_slice());
+                hasher.finalize()
+            }).collect::<Vec<_>>();
+            layers.push(next);
+        }
+
+        Ok(Self {
+            root: FriCommitment::new(layers.last().unwrap()[0]),
+            rows,
+            salts,
+            layers,
+        })
+    }
+
+    pub fn root(&self) -> &FriCommitment<F> {
+        &self.root
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    // The point row i holds the evaluations at, c * w^i.
+    pub fn point(&self, index: usize) -> Result<F, SynthesisError> {
+        row_point(index, self.domain_size())
+    }
+
+    // Row i holds the evaluations of every committed polynomial at c * w^i. PointNotInDomain for
+    // an index past the last row.
+    pub fn open(&self, index: usize) -> Result<RowOpening<F>, SynthesisError> {
+        if index >= self.domain_size() {
+            return Err(SynthesisError::PointNotInDomain);
+        }
+
+        let mut siblings = Vec::with_capacity(self.layers.len() - 1);
+        let mut i = index;
+        for layer in self.layers.iter().take(self.layers.len() - 1) {
+            siblings.push(layer[i ^ 1]);
+            i >>= 1;
+        }
+
+        Ok(RowOpening {
+            index,
+            row: self.rows[index].clone(),
+            salt: self.salts.as_ref().map(|s| s[index]),
+            siblings,
+        })
+    }
+}
+
+// c * w^index on the coset of `domain_size` rows, or PointNotInDomain past the last row.
+fn row_point<F: PrimeField>(index: usize, domain_size: usize) -> Result<F, SynthesisError> {
+    if !domain_size.is_power_of_two() {
+        return Err(SynthesisError::NonPowerOfTwo(domain_size));
+    }
+    if index >= domain_size {
+        return Err(SynthesisError::PointNotInDomain);
+    }
+    let domain = Domain::<F>::new_for_size(domain_size as u64)?.with_offset(domain_offset(domain_size))?;
+    Ok(domain.element(index as u64))
+}
+
+impl<F: PrimeField> RowOpening<F> {
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn row(&self) -> &[F] {
+        &self.row
+    }
+
+    // The point the row holds evaluations at, c * w^index, in a commitment to `domain_size` rows.
+    pub fn point(&self, domain_size: usize) -> Result<F, SynthesisError> {
+        row_point(self.index, domain_size)
+    }
+
+    // Checks the row against the root of a commitment to `domain_size` rows. The shape of the
+    // opening is checked first: the path of a tree with n leaves has exactly log2(n) siblings.
+    pub fn verify(&self, root: &FriCommitment<F>, domain_size: usize) -> VerificationResult {
+        if !domain_size.is_power_of_two() || self.siblings.len() != domain_size.trailing_zeros() as usize {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one sibling per level of the Merkle tree"));
+        }
+        if self.index >= domain_size {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("row index out of range"));
+        }
+
+        let mut target = hash_row(&self.row, self.salt.as_ref());
+        let mut i = self.index;
+        for sibling in &self.siblings {
+            let mut hasher = Hasher::new();
+            if i & 1 == 0 {
+                hasher.update(target.as_bytes().as_slice());
+                hasher.update(sibling.as_bytes().as_slice());
+            }
+            else {
+                hasher.update(sibling.as_bytes().as_slice());
+                hasher.update(target.as_bytes().as_slice());
+            }
+            target = hasher.finalize();
+            i >>= 1;
+        }
+        if target != root.value() {
+            return VerificationResult::InvalidProof(RejectionReason::MerkleRootMismatch { layer: 0 });
+        }
+        VerificationResult::ValidProof
+    }
+}
+
+#[cfg(test)]
+mod matrix_tests {
+
+    use super::*;
+    use crate::{field::goldilocks::Goldilocks, domains::Domain};
+
+    use ff::Field;
+
+    #[test]
+    fn open_rows() {
+        let mut rng = rand::thread_rng();
+        let polynomials = (0..5)
+            .map(|i| Polynomial::masking_polynomial(if i == 2 { 4 } else { 8 }, &mut rng))
+            .collect::<Vec<Polynomial<Goldilocks>>>();
+
+        let commitment = MatrixCommitment::new(&polynomials).unwrap();
+        assert_eq!(commitment.domain_size(), 8 * FRI_BLOWUP_FACTOR);
+        assert_eq!(commitment.width(), 5);
+
+        let omega: Goldilocks = Domain::root_with_order_unchecked(commitment.domain_size() as u64);
+        let size = commitment.domain_size();
+        for index in [0, 1, 13, size - 1] {
+            let opening = commitment.open(index).unwrap();
+            assert!(opening.verify(commitment.root(), size).is_valid());
+
+            let x = opening.point(size).unwrap();
+            assert_eq!(x, domain_offset::<Goldilocks>(size) * omega.pow([index as u64]));
+            assert_eq!(commitment.point(index).unwrap(), x);
+            for (p, value) in polynomials.iter().zip(opening.row()) {
+                assert_eq!(p.eval_single(&x), *value);
+            }
+        }
+
+        let mut tampered = commitment.open(7).unwrap();
+        tampered.row[3] += Goldilocks::ONE;
+        assert!(!tampered.verify(commitment.root(), size).is_valid());
+
+        let mut moved = commitment.open(7).unwrap();
+        moved.index = 6;
+        assert!(!moved.verify(commitment.root(), size).is_valid());
+    }
+
+    #[test]
+    fn rows_avoid_the_constraint_domain() {
+        let mut rng = rand::thread_rng();
+        let polynomials = (0..3)
+            .map(|_| Polynomial::masking_polynomial(8, &mut rng))
+            .collect::<Vec<Polynomial<Goldilocks>>>();
+        let commitment = MatrixCommitment::new_salted(&polynomials, &mut rng).unwrap();
+
+        // No row is an evaluation on the subgroup H of size 8, i.e. no row point has x^8 = 1
+        for index in 0..commitment.domain_size() {
+            assert_ne!(commitment.point(index).unwrap().pow([8]), Goldilocks::ONE, "row {}", index);
+        }
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let polynomials = vec![Polynomial::<Goldilocks>::masking_polynomial(4, &mut rand::thread_rng())];
+        let commitment = MatrixCommitment::new(&polynomials).unwrap();
+        let size = commitment.domain_size();
+
+        assert!(matches!(MatrixCommitment::<Goldilocks>::new(&[]), Err(SynthesisError::EmptyBatch)));
+        assert!(matches!(MatrixCommitment::<Goldilocks>::new_salted(&[], &mut rand::thread_rng()), Err(SynthesisError::EmptyBatch)));
+        assert!(matches!(commitment.open(size), Err(SynthesisError::PointNotInDomain)));
+        assert_eq!(commitment.point(size), Err(SynthesisError::PointNotInDomain));
+        assert_eq!(commitment.open(0).unwrap().point(size + 1), Err(SynthesisError::NonPowerOfTwo(size + 1)));
+    }
+
+    #[test]
+    fn sibling_count() {
+        let mut rng = rand::thread_rng();
+        let polynomials = (0..2)
+            .map(|_| Polynomial::masking_polynomial(8, &mut rng))
+            .collect::<Vec<Polynomial<Goldilocks>>>();
+        let commitment = MatrixCommitment::new(&polynomials).unwrap();
+        let size = commitment.domain_size();
+        let malformed = |opening: &RowOpening<Goldilocks>, size| matches!(
+            opening.verify(commitment.root(), size).rejection_reason(),
+            Some(RejectionReason::MalformedProof(_))
+        );
+
+        let mut short = commitment.open(3).unwrap();
+        short.siblings.pop();
+        assert!(malformed(&short, size));
+
+        let mut long = commitment.open(3).unwrap();
+        long.siblings.push(long.siblings[0]);
+        assert!(malformed(&long, size));
+
+        // A path for a smaller tree does not pass as one for the committed tree
+        assert!(malformed(&commitment.open(3).unwrap(), size / 2));
+        assert!(malformed(&commitment.open(3).unwrap(), size + 1));
+
+        let mut out_of_range = commitment.open(3).unwrap();
+        out_of_range.index += size;
+        assert!(malformed(&out_of_range, size));
+    }
+
+    #[test]
+    fn salted_rows() {
+        let mut rng = rand::thread_rng();
+        let polynomials = (0..3)
+            .map(|_| Polynomial::masking_polynomial(8, &mut rng))
+            .collect::<Vec<Polynomial<Goldilocks>>>();
+
+        let plain = MatrixCommitment::new(&polynomials).unwrap();
+        let salted = MatrixCommitment::new_salted(&polynomials, &mut rng).unwrap();
+        assert_ne!(plain.root().value(), salted.root().value());
+
+        let opening = salted.open(5).unwrap();
+        assert_eq!(opening.row(), plain.open(5).unwrap().row());
+        assert!(opening.verify(salted.root(), salted.domain_size()).is_valid());
+        assert!(!opening.verify(plain.root(), plain.domain_size()).is_valid());
+    }
+
+    #[test]
+    fn packed_row_hashing() {
+        // Non-canonical elements and a row length that is not a multiple of any packing
+        let row = [0, 1, u64::MAX, 0xffff_ffff_0000_0001, 7, 0xffff_ffff_0000_0002, 3, 5, 1 << 63, 2, 9]
+            .map(Goldilocks::new);
+        let mut hasher = Hasher::new();
+        for element in &row {
+            hasher.update(element.to_repr().as_ref());
+        }
+        assert_eq!(hash_row(&row, None), hasher.finalize());
+    }
+}
@@ -3,12 +3,28 @@ use ff::PrimeField;
 
 use std::marker::PhantomData;
 
-use crate::polynomial::Polynomial;
+use crate::{
+    polynomial::Polynomial,
+    constants::{SALT_SIZE, FRI_BLOWUP_LOG},
+    error::RejectionReason,
+};
 
 mod commitment;
 mod authentication;
 mod fold;
 mod proof;
+mod zk;
+mod matrix;
+mod pcs;
+mod security;
+
+pub use zk::{SaltedCommitment, QueryOpening, MaskingOpening, BatchFriProof};
+pub use matrix::{MatrixCommitment, RowOpening};
+pub use pcs::FriPcs;
+pub use security::FriConfig;
+pub(crate) use commitment::domain_offset;
+
+pub type Salt = [u8; SALT_SIZE];
 
 #[derive(Debug, Clone)]
 pub struct FriCommitment<F: PrimeField>(Hash, PhantomData<F>);
@@ -23,6 +39,7 @@ pub struct AuthenticationHash {
 pub struct AuthenticationPath<F: PrimeField> {
     first_evaluation: F,
     second_evaluation: F,
+    salt: Option<Salt>,
     authentication_path: Vec<AuthenticationHash>
 }
 
@@ -38,9 +55,19 @@ impl<F: PrimeField> FriCommitment<F> {
     pub fn next_value(&self) -> Hash {
         blake3::hash(self.0.as_bytes().as_slice())
     }
+
+    // Hashes several commitments into a single one, so that a challenge derived from the result
+    // depends on every commitment in the batch.
+    pub fn combine(commitments: &[Self]) -> Self {
+        let mut hasher = blake3::Hasher::new();
+        for commitment in commitments {
+            hasher.update(commitment.0.as_bytes().as_slice());
+        }
+        Self::new(hasher.finalize())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FriChallenge<F: PrimeField> {
     positive_evaluation: F,
     negative_evaluation: F,
@@ -51,12 +78,6 @@ pub struct FriChallenge<F: PrimeField> {
     commitment_vector: Vec<FriCommitment<F>>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum VerificationResult {
-    ValidProof,
-    InvalidProof,
-}
-
 impl<F: PrimeField> FriChallenge<F> {
     pub fn new(
             positive_evaluation: F,
@@ -107,6 +128,24 @@ impl<F: PrimeField> FriChallenge<F> {
         &self.authentication_vector
     }
 
+    // A fold of k layers has k + 1 commitments, the last to the constant function, and a query
+    // with its authentication path for each of the first k. Anything else cannot be verified.
+    pub fn check_shape(&self) -> Result<(), RejectionReason> {
+        if self.commitment_vector.is_empty() {
+            return Err(RejectionReason::MalformedProof("no fold commitments"));
+        }
+        if self.commitment_vector.len() + FRI_BLOWUP_LOG > F::S as usize {
+            return Err(RejectionReason::MalformedProof("more fold layers than the field has roots of unity"));
+        }
+        if self.authentication_vector.len() != self.fold_queries.len() {
+            return Err(RejectionReason::MalformedProof("fold queries and authentication paths differ in number"));
+        }
+        if self.fold_queries.len() + 1 != self.commitment_vector.len() {
+            return Err(RejectionReason::MalformedProof("fold queries and commitments differ in number"));
+        }
+        Ok(())
+    }
+
 }
 
 impl<F: PrimeField> Polynomial<F> {
@@ -116,14 +155,7 @@ impl<F: PrimeField> Polynomial<F> {
         let eval = self.eval_single(&r);
         let numerator = self.sub_constant(eval);
         let denominator = Polynomial::from_vec(vec![F::ZERO-r, F::ONE]);
-        let (w_x, _) = numerator.long_division(&denominator);
+        let (w_x, _) = numerator.long_division(&denominator).expect("x - r is nonzero");
         w_x
     }
 }
-
-impl VerificationResult {
-    pub fn is_valid(&self) -> bool {
-        *self == VerificationResult::ValidProof
-    }
-}
-
@@ -0,0 +1,84 @@
+use ff::PrimeField;
+
+use core::slice;
+
+use crate::{
+    pcs::PolynomialCommitmentScheme,
+    polynomial::Polynomial,
+    field::extension::ExtensionField,
+    fri::{FriCommitment, SaltedCommitment, BatchFriProof},
+    error::{SynthesisError, VerificationResult, RejectionReason},
+};
+
+// FRI as a polynomial commitment scheme. A commitment is the Merkle root of the blown-up
+// evaluations, and every opening is a batched FRI proof, which ties the claimed evaluations to the
+// commitments through the openings at the FRI query point.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FriPcs;
+
+impl FriPcs {
+
+    // Batching challenge for evaluations claimed at `point`, drawn from an extension of F for
+    // fields too small to give the required soundness on their own. Like the challenge of a
+    // batched opening it depends on the claimed evaluations, not only on the commitments.
+    pub fn extension_challenge<F: PrimeField, E: ExtensionField<F>>(commitments: &[FriCommitment<F>], point: &F, evaluations: &[F]) -> E {
+        BatchFriProof::batching_transcript(commitments, None, point, evaluations).interpret_as_extension_element()
+    }
+}
+
+impl<F: PrimeField> PolynomialCommitmentScheme<F> for FriPcs {
+    type Commitment = FriCommitment<F>;
+    type Proof = Vec<BatchFriProof<F>>;
+    type BatchProof = BatchFriProof<F>;
+
+    // FRI needs no trusted setup.
+    fn setup(_max_len: usize) -> Self {
+        FriPcs
+    }
+
+    fn commit(&self, polynomial: &Polynomial<F>) -> Result<Self::Commitment, SynthesisError> {
+        Ok(polynomial.commitment())
+    }
+
+    fn challenge(commitments: &[Self::Commitment]) -> F {
+        FriCommitment::combine(commitments).interpret_as_element()
+    }
+
+    fn open(&self, polynomial: &Polynomial<F>, points: &[F]) -> Result<(Vec<F>, Self::Proof), SynthesisError> {
+        let commitment = SaltedCommitment::from(polynomial.commitment());
+        let proofs = points
+            .iter()
+            .map(|z| BatchFriProof::prove(slice::from_ref(polynomial), slice::from_ref(&commitment), *z))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((proofs.iter().map(|p| p.evaluations()[0]).collect(), proofs))
+    }
+
+    // The polynomials of a batch share the FRI domain, so they must have the same length.
+    fn batch_open(&self, polynomials: &[Polynomial<F>], point: F) -> Result<(Vec<F>, Self::BatchProof), SynthesisError> {
+        let commitments = polynomials.iter().map(|p| SaltedCommitment::from(p.commitment())).collect::<Vec<_>>();
+        let proof = BatchFriProof::prove(polynomials, &commitments, point)?;
+        Ok((proof.evaluations().clone(), proof))
+    }
+
+    fn verify(&self, commitment: &Self::Commitment, points: &[F], evaluations: &[F], proof: &Self::Proof) -> VerificationResult {
+        if points.len() != evaluations.len() || points.len() != proof.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation and proof per point"));
+        }
+        for (index, ((z, y), p)) in points.iter().zip(evaluations).zip(proof).enumerate() {
+            if p.evaluations().first() != Some(y) { return VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index }); }
+            let result = p.verify(slice::from_ref(commitment), z);
+            if !result.is_valid() { return result; }
+        }
+        VerificationResult::ValidProof
+    }
+
+    fn batch_verify(&self, commitments: &[Self::Commitment], point: &F, evaluations: &[F], proof: &Self::BatchProof) -> VerificationResult {
+        if proof.evaluations().len() != evaluations.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation per commitment"));
+        }
+        if let Some(index) = proof.evaluations().iter().zip(evaluations).position(|(a, b)| a != b) {
+            return VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index });
+        }
+        proof.verify(commitments, point)
+    }
+}
@@ -2,7 +2,8 @@ use ff::PrimeField;
 
 use crate::{
     FriProof,
-    fri::{FriCommitment, FriChallenge, VerificationResult},
+    fri::{FriCommitment, FriChallenge},
+    error::{VerificationResult, RejectionReason},
     constants::*,
     polynomial::Polynomial,
 };
@@ -18,11 +19,15 @@ impl<F: PrimeField> FriProof<F> {
 
     pub fn evaluation_proof(f_x: &Polynomial<F>, r: Option<F>) -> Self {
         
-        // Prover makes w_x out of f_x and randomness.
-        let w_x = {
+        // Prover makes w_x out of f_x and randomness. It is one coefficient shorter than f_x and
+        // padded back to the length f_x is committed for, so that it is queried on the domain of
+        // f_x, where the openings of a batch are checked.
+        let mut w_x = {
             if let Some(x) = r { f_x.shift_polynomial(x) }
             else { f_x.shift_polynomial(f_x.commitment().interpret_as_element()) }
-        };
+        }.coefficients();
+        w_x.resize(f_x.commitment_len(), F::ZERO);
+        let w_x = Polynomial::from_vec(w_x);
         let w_commitment = w_x.commitment();
 
         // Prover folds w_x. Has vector with intermediate polynomials to use as a utility and a
@@ -34,11 +39,12 @@ impl<F: PrimeField> FriProof<F> {
         let random_root_of_unity = commitment_vector
             .last()
             .expect("Commitment vector empty.")
-            .interpret_as_root_of_unity((f_x.len().next_power_of_two() * FRI_BLOWUP_FACTOR) as u64);
+            .interpret_as_query_point(w_x.commitment_domain_size() as u64);
 
         // Prover makes authentication paths for w_x and evaluates values accordingly.
-        let positive_authentication_path = w_x.authentication_path_for(&random_root_of_unity);
-        let negative_authentication_path = w_x.authentication_path_for(&-random_root_of_unity);
+        let in_domain = "The query root is in the evaluation domain";
+        let positive_authentication_path = w_x.authentication_path_for(&random_root_of_unity).expect(in_domain);
+        let negative_authentication_path = w_x.authentication_path_for(&-random_root_of_unity).expect(in_domain);
         let positive_evaluation = w_x.eval_single(&random_root_of_unity);
         let negative_evaluation = w_x.eval_single(&-random_root_of_unity);
 
@@ -48,7 +54,7 @@ impl<F: PrimeField> FriProof<F> {
 
         let mut target = random_root_of_unity.square();
         for polynomial in polynomial_vector.iter().take(polynomial_vector.len()-1) {
-            auth_vec.push(polynomial.authentication_path_for(&-target));
+            auth_vec.push(polynomial.authentication_path_for(&-target).expect(in_domain));
             query_vec.push(polynomial.eval_single(&-target));
             target = target.square();
         }
@@ -68,34 +74,47 @@ impl<F: PrimeField> FriProof<F> {
     
     pub fn verify(&self) -> VerificationResult {
 
+        if let Err(reason) = self.fri_challenge().check_shape() {
+            return VerificationResult::InvalidProof(reason);
+        }
+
         // Check that the queries are consistent with the authentication paths
-        if !self.fri_challenge().positive_authentication_path().contains_evaluation(&self.fri_challenge().positive_evaluation()) { return VerificationResult::InvalidProof; }
-        if !self.fri_challenge().negative_authentication_path().contains_evaluation(&self.fri_challenge().negative_evaluation()) { return VerificationResult::InvalidProof; }
+        let query_mismatch = |layer| VerificationResult::InvalidProof(RejectionReason::QueryValueMismatch { layer });
+        if !self.fri_challenge().positive_authentication_path().contains_evaluation(&self.fri_challenge().positive_evaluation()) { return query_mismatch(0); }
+        if !self.fri_challenge().negative_authentication_path().contains_evaluation(&self.fri_challenge().negative_evaluation()) { return query_mismatch(0); }
         for i in 0..self.fri_challenge().authentication_paths().len() {
-            if !self.fri_challenge().authentication_paths()[i].contains_evaluation(&self.fri_challenge().fold_queries()[i]) { return VerificationResult::InvalidProof; }
+            if !self.fri_challenge().authentication_paths()[i].contains_evaluation(&self.fri_challenge().fold_queries()[i]) { return query_mismatch(i + 1); }
         }
 
         // Check that the authentication paths are consistent with the commitments
         
-        if self.w_com().value() != self.fri_challenge().positive_authentication_path().derive_root() { return VerificationResult::InvalidProof; }
-        if self.w_com().value() != self.fri_challenge().negative_authentication_path().derive_root() { return VerificationResult::InvalidProof; }
+        let root_mismatch = |layer| VerificationResult::InvalidProof(RejectionReason::MerkleRootMismatch { layer });
+        if self.w_com().value() != self.fri_challenge().positive_authentication_path().derive_root() { return root_mismatch(0); }
+        if self.w_com().value() != self.fri_challenge().negative_authentication_path().derive_root() { return root_mismatch(0); }
 
         for i in 0..self.fri_challenge().authentication_paths().len() {
-            if self.fri_challenge().authentication_paths()[i].derive_root() != self.fri_challenge().commitment_vector()[i].value() { return VerificationResult::InvalidProof; }
+            if self.fri_challenge().authentication_paths()[i].derive_root() != self.fri_challenge().commitment_vector()[i].value() { return root_mismatch(i + 1); }
         }
         // Check that the fold is proper
         
-        let should_be_root = self
-            .fri_challenge()
-            .commitment_vector()
-            .last()
-            .unwrap()
-            .interpret_as_root_of_unity(1<<(self.fri_challenge().commitment_vector().len() + FRI_BLOWUP_LOG));
+        let should_be_root = self.query_root();
 
         let should_be_constant_function = self.fri_challenge().query_check(self.w_com(), &should_be_root);
-        if should_be_constant_function != self.fri_challenge().commitment_vector().last().unwrap().value() { return VerificationResult::InvalidProof; }
+        if should_be_constant_function != self.fri_challenge().commitment_vector().last().unwrap().value() {
+            return VerificationResult::InvalidProof(RejectionReason::FoldInconsistency);
+        }
         
         VerificationResult::ValidProof
 
     }
+
+    // The point at which the top layer of the fold is queried. It is derived from the commitment to
+    // the final constant function, so the prover cannot choose it.
+    pub(crate) fn query_root(&self) -> F {
+        self.fri_challenge()
+            .commitment_vector()
+            .last()
+            .unwrap()
+            .interpret_as_query_point(1<<(self.fri_challenge().commitment_vector().len() + FRI_BLOWUP_LOG))
+    }
 }
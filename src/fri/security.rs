@@ -0,0 +1,269 @@
+use ff::PrimeField;
+
+use crate::{
+    constants::*,
+    field::extension::ExtensionField,
+    error::SynthesisError,
+};
+
+/*
+ * Soundness of a FRI configuration. Two terms bound the security of FRI:
+ *
+ * - Query phase: every query catches a cheating prover with a probability that depends only on the
+ *   rate p = 1/blowup_factor. Each bit of grinding (proof of work on the Fiat-Shamir transcript)
+ *   adds one bit on top of the queries.
+ * - Commit phase: the folding challenges are drawn from the (extension) field, and a prover can get
+ *   lucky with probability proportional to |D|/|F| per round, where D is the evaluation domain.
+ *   This term cannot be improved with more queries, only with a larger field.
+ *
+ * The conjectured bound is the ethSTARK conjecture: every query contributes log2(1/p) bits. The
+ * proven bound uses the unique decoding radius (1 - p)/2, where each query only contributes
+ * log2(2/(1 + p)) bits, and the BCIKS20 bound for the commit phase.
+*/
+
+// The fields are private so that every configuration goes through `new`: the estimates below
+// divide by the folding arity and the log of the blowup factor, and fold until a single
+// coefficient is left, none of which terminates or makes sense for values below two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FriConfig {
+    blowup_factor: usize,
+    num_queries: usize,
+    folding_arity: usize,
+    grinding_bits: u32,
+    field_bits: u32,
+    extension_degree: u32,
+}
+
+impl FriConfig {
+
+    pub fn new(
+        blowup_factor: usize,
+        num_queries: usize,
+        folding_arity: usize,
+        grinding_bits: u32,
+        field_bits: u32,
+        extension_degree: u32
+    ) -> Result<Self, SynthesisError> {
+        if blowup_factor < 2 {
+            return Err(SynthesisError::InvalidConfiguration("blowup factor below two"));
+        }
+        if folding_arity < 2 {
+            return Err(SynthesisError::InvalidConfiguration("folding arity below two"));
+        }
+        if field_bits == 0 || extension_degree == 0 {
+            return Err(SynthesisError::InvalidConfiguration("empty challenge field"));
+        }
+        Ok(Self {
+            blowup_factor,
+            num_queries,
+            folding_arity,
+            grinding_bits,
+            field_bits,
+            extension_degree,
+        })
+    }
+
+    // The configuration this crate uses for F, with the given number of queries.
+    pub fn for_field<F: PrimeField>(num_queries: usize) -> Self {
+        Self {
+            blowup_factor: FRI_BLOWUP_FACTOR,
+            num_queries,
+            folding_arity: 2,
+            grinding_bits: 0,
+            field_bits: F::NUM_BITS,
+            extension_degree: 1,
+        }
+    }
+
+    // Same as `for_field`, with the folding challenges drawn from the extension E.
+    pub fn for_extension<F: PrimeField, E: ExtensionField<F>>(num_queries: usize) -> Self {
+        Self {
+            extension_degree: E::DEGREE as u32,
+            ..Self::for_field::<F>(num_queries)
+        }
+    }
+
+    pub fn blowup_factor(&self) -> usize {
+        self.blowup_factor
+    }
+
+    pub fn num_queries(&self) -> usize {
+        self.num_queries
+    }
+
+    pub fn folding_arity(&self) -> usize {
+        self.folding_arity
+    }
+
+    pub fn grinding_bits(&self) -> u32 {
+        self.grinding_bits
+    }
+
+    pub fn field_bits(&self) -> u32 {
+        self.field_bits
+    }
+
+    pub fn extension_degree(&self) -> u32 {
+        self.extension_degree
+    }
+
+    fn log_blowup(&self) -> f64 {
+        (self.blowup_factor as f64).log2()
+    }
+
+    // Bits of the field the challenges are drawn from.
+    fn challenge_field_bits(&self) -> f64 {
+        (self.field_bits * self.extension_degree) as f64
+    }
+
+    // Number of folding rounds to reduce a polynomial with `degree` coefficients to a constant.
+    pub fn num_rounds(&self, degree: usize) -> usize {
+        let mut rounds = 0;
+        let mut d = degree.max(1);
+        while d > 1 {
+            d = d.div_ceil(self.folding_arity);
+            rounds += 1;
+        }
+        rounds
+    }
+
+    fn lde_domain_bits(&self, degree: usize) -> f64 {
+        (degree.max(1).next_power_of_two() as f64).log2() + self.log_blowup()
+    }
+
+    // Security bits contributed by each query under the conjecture and by the proven bound.
+    fn conjectured_bits_per_query(&self) -> f64 {
+        self.log_blowup()
+    }
+
+    fn proven_bits_per_query(&self) -> f64 {
+        let rate = 1.0 / self.blowup_factor as f64;
+        -((1.0 + rate) / 2.0).log2()
+    }
+
+    fn conjectured_commit_bits(&self, degree: usize) -> f64 {
+        self.challenge_field_bits() - self.lde_domain_bits(degree)
+    }
+
+    fn proven_commit_bits(&self, degree: usize) -> f64 {
+        let rounds = self.num_rounds(degree).max(1) as f64;
+        let per_round = (self.folding_arity - 1) as f64;
+        self.challenge_field_bits() - self.lde_domain_bits(degree) - (rounds * per_round).log2()
+    }
+
+    // Conjectured bits of security for committing to a polynomial with `degree` coefficients.
+    pub fn conjectured_security_bits(&self, degree: usize) -> f64 {
+        let query_bits = self.num_queries as f64 * self.conjectured_bits_per_query() + self.grinding_bits as f64;
+        query_bits.min(self.conjectured_commit_bits(degree))
+    }
+
+    // Provable bits of security for committing to a polynomial with `degree` coefficients.
+    pub fn proven_security_bits(&self, degree: usize) -> f64 {
+        let query_bits = self.num_queries as f64 * self.proven_bits_per_query() + self.grinding_bits as f64;
+        query_bits.min(self.proven_commit_bits(degree))
+    }
+
+    // The smallest number of queries reaching `target_bits` of conjectured security. Returns None if
+    // the field is too small for the target, no matter how many queries are made.
+    pub fn min_queries_for_conjectured(&self, target_bits: u32, degree: usize) -> Option<usize> {
+        if self.conjectured_commit_bits(degree) < target_bits as f64 { return None; }
+        Some(self.min_queries(target_bits, self.conjectured_bits_per_query()))
+    }
+
+    // Same as above for the proven bound.
+    pub fn min_queries_for_proven(&self, target_bits: u32, degree: usize) -> Option<usize> {
+        if self.proven_commit_bits(degree) < target_bits as f64 { return None; }
+        Some(self.min_queries(target_bits, self.proven_bits_per_query()))
+    }
+
+    fn min_queries(&self, target_bits: u32, bits_per_query: f64) -> usize {
+        let remaining = target_bits.saturating_sub(self.grinding_bits) as f64;
+        (remaining / bits_per_query).ceil() as usize
+    }
+
+    // Returns this configuration with the number of queries lowered (or raised) to the minimum
+    // reaching `target_bits` of conjectured security.
+    pub fn with_conjectured_security(&self, target_bits: u32, degree: usize) -> Option<Self> {
+        self.min_queries_for_conjectured(target_bits, degree).map(|num_queries| Self { num_queries, ..*self })
+    }
+
+    pub fn with_proven_security(&self, target_bits: u32, degree: usize) -> Option<Self> {
+        self.min_queries_for_proven(target_bits, degree).map(|num_queries| Self { num_queries, ..*self })
+    }
+}
+
+#[cfg(test)]
+mod security_tests {
+
+    use super::*;
+    use crate::field::{Fp, goldilocks::{Goldilocks, GoldilocksExt2}};
+
+    fn goldilocks_ext2() -> FriConfig {
+        FriConfig::new(8, 28, 2, 16, 64, 2).unwrap()
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        // Arity 0 divided by zero, arity 1 never finished folding and blowup 1 gives no bits per query
+        assert_eq!(FriConfig::new(8, 28, 0, 16, 64, 2), Err(SynthesisError::InvalidConfiguration("folding arity below two")));
+        assert_eq!(FriConfig::new(8, 28, 1, 16, 64, 2), Err(SynthesisError::InvalidConfiguration("folding arity below two")));
+        assert_eq!(FriConfig::new(1, 28, 2, 16, 64, 2), Err(SynthesisError::InvalidConfiguration("blowup factor below two")));
+        assert_eq!(FriConfig::new(8, 28, 2, 16, 64, 0), Err(SynthesisError::InvalidConfiguration("empty challenge field")));
+
+        let quartic = FriConfig::new(8, 28, 4, 16, 64, 2).unwrap();
+        assert_eq!(quartic.folding_arity(), 4);
+        assert_eq!(quartic.num_rounds(1 << 20), 10);
+    }
+
+    #[test]
+    fn conjectured_security() {
+        let config = goldilocks_ext2();
+        assert_eq!(config.num_rounds(1 << 20), 20);
+        assert_eq!(config.conjectured_security_bits(1 << 20), 100.0);
+
+        // 2^20 coefficients with blowup 8 leave 128 - 23 bits for the commit phase
+        let config = FriConfig { num_queries: 100, ..config };
+        assert_eq!(config.conjectured_security_bits(1 << 20), 105.0);
+
+        // Without the extension the field is the bottleneck
+        let config = FriConfig { extension_degree: 1, ..config };
+        assert_eq!(config.conjectured_security_bits(1 << 20), 41.0);
+    }
+
+    #[test]
+    fn proven_is_below_conjectured() {
+        let config = goldilocks_ext2();
+        for log_degree in 4..24 {
+            let degree = 1 << log_degree;
+            assert!(config.proven_security_bits(degree) < config.conjectured_security_bits(degree));
+        }
+    }
+
+    #[test]
+    fn minimal_queries() {
+        let config = goldilocks_ext2();
+        assert_eq!(config.min_queries_for_conjectured(100, 1 << 20), Some(28));
+        assert_eq!(config.min_queries_for_conjectured(101, 1 << 20), Some(29));
+        assert_eq!(config.min_queries_for_conjectured(110, 1 << 20), None);
+
+        let proven = config.with_proven_security(90, 1 << 20).unwrap();
+        assert!(proven.proven_security_bits(1 << 20) >= 90.0);
+        let fewer = FriConfig { num_queries: proven.num_queries - 1, ..proven };
+        assert!(fewer.proven_security_bits(1 << 20) < 90.0);
+    }
+
+    #[test]
+    fn crate_configuration() {
+        let goldilocks = FriConfig::for_field::<Goldilocks>(1);
+        assert_eq!(goldilocks.field_bits(), 64);
+        assert_eq!(goldilocks.conjectured_security_bits(1 << 10), FRI_BLOWUP_LOG as f64);
+
+        // Goldilocks alone cannot reach 100 bits, its quadratic extension can
+        assert!(goldilocks.with_conjectured_security(100, 1 << 20).is_none());
+        let extended = FriConfig::for_extension::<Goldilocks, GoldilocksExt2>(1);
+        assert!(extended.with_conjectured_security(100, 1 << 20).is_some());
+
+        let fp = FriConfig::for_field::<Fp>(1).with_conjectured_security(128, 1 << 20).unwrap();
+        assert_eq!(fp.num_queries(), 128 / FRI_BLOWUP_LOG);
+    }
+}
@@ -0,0 +1,487 @@
+use ff::PrimeField;
+use rand::RngCore;
+use blake3::Hasher;
+
+use crate::{
+    FriProof,
+    polynomial::Polynomial,
+    fri::{FriCommitment, AuthenticationPath, Salt},
+    constants::*,
+    error::{SynthesisError, VerificationResult, RejectionReason},
+};
+
+/*
+ * Zero-knowledge mode for FRI. Three things leak information about a committed polynomial in the
+ * plain scheme:
+ *
+ * 1. Merkle leaves are hashes of raw evaluations, so a verifier can test guesses for f(x). We
+ *    prefix every leaf with a random salt that is only revealed when that leaf is opened.
+ * 2. Opened evaluations outside of the constraint domain H are evaluations of the witness itself.
+ *    Witness polynomials are blinded with a random multiple of the vanishing polynomial of H,
+ *    which leaves them unchanged on H but makes a bounded number of other evaluations uniform.
+ *    FRI commits over a coset that is disjoint from H, so no query opens a value on H.
+ * 3. The folded polynomials in the batched opening are linear combinations of the witnesses. A
+ *    random masking polynomial is added to the combination so that the whole fold is uniform.
+*/
+
+// A Merkle commitment together with the salts of its leaves. The salts are the prover's secret;
+// only the root is sent to the verifier. Commitments made without zero-knowledge carry no salts.
+#[derive(Debug, Clone)]
+pub struct SaltedCommitment<F: PrimeField> {
+    commitment: FriCommitment<F>,
+    salts: Option<Vec<Salt>>,
+}
+
+impl<F: PrimeField> SaltedCommitment<F> {
+
+    pub fn commitment(&self) -> &FriCommitment<F> {
+        &self.commitment
+    }
+
+    pub fn is_hiding(&self) -> bool {
+        self.salts.is_some()
+    }
+}
+
+impl<F: PrimeField> From<FriCommitment<F>> for SaltedCommitment<F> {
+    fn from(commitment: FriCommitment<F>) -> Self {
+        Self {
+            commitment,
+            salts: None,
+        }
+    }
+}
+
+impl<F: PrimeField> Polynomial<F> {
+
+    // Commits to the polynomial with a fresh random salt in every Merkle leaf.
+    pub fn salted_commitment<R: RngCore>(&self, rng: &mut R) -> SaltedCommitment<F> {
+        let salts = (0..self.commitment_leaves()).map(|_| {
+            let mut salt = [0u8; SALT_SIZE];
+            rng.fill_bytes(&mut salt);
+            salt
+        }).collect::<Vec<Salt>>();
+
+        SaltedCommitment {
+            commitment: self.salted_root(Some(&salts)),
+            salts: Some(salts),
+        }
+    }
+
+    // Opens the commitment at a point of the evaluation domain.
+    pub(crate) fn open_at(&self, point: &F, commitment: &SaltedCommitment<F>) -> Result<QueryOpening<F>, SynthesisError> {
+        Ok(QueryOpening {
+            value: self.eval_single(point),
+            path: self.salted_authentication_path_for(point, commitment.salts.as_deref())?,
+        })
+    }
+
+    // Returns f(x) + b(x)(x^n - 1) for a random b(x) with `hiding_bound` coefficients, where n is
+    // `domain_size`. The result agrees with f(x) on the n-th roots of unity, and any
+    // `hiding_bound` evaluations outside of them are independent and uniformly random.
+    pub fn blind<R: RngCore>(&self, domain_size: usize, hiding_bound: usize, rng: &mut R) -> Self {
+        let mut coefficients = self.coefficients();
+        let len = coefficients.len().max(domain_size + hiding_bound);
+        coefficients.resize(len, F::ZERO);
+
+        for i in 0..hiding_bound {
+            let b = F::random(&mut *rng);
+            coefficients[i] -= b;
+            coefficients[i + domain_size] += b;
+        }
+
+        Self::from_vec(coefficients)
+    }
+
+    // A uniformly random polynomial with `len` coefficients.
+    pub fn masking_polynomial<R: RngCore>(len: usize, rng: &mut R) -> Self {
+        Self::from_vec((0..len).map(|_| F::random(&mut *rng)).collect())
+    }
+}
+
+// The evaluation of a committed polynomial at a point of its evaluation domain, together with the
+// Merkle path that ties it to the commitment.
+#[derive(Debug, Clone)]
+pub struct QueryOpening<F: PrimeField> {
+    value: F,
+    path: AuthenticationPath<F>,
+}
+
+impl<F: PrimeField> QueryOpening<F> {
+
+    pub fn value(&self) -> F {
+        self.value
+    }
+
+    pub fn check(&self, commitment: &FriCommitment<F>) -> bool {
+        self.path.contains_evaluation(&self.value) && self.path.derive_root() == commitment.value()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MaskingOpening<F: PrimeField> {
+    commitment: FriCommitment<F>,
+    evaluation: F,
+    opening: QueryOpening<F>,
+}
+
+// Opening of several polynomials f_0, ..., f_k at the same point r with a single FRI proof. The
+// prover folds the random linear combination m(x) + f_0(x) + a*f_1(x) + ... + a^k*f_k(x), where a is
+// derived from the commitments, r and the claimed evaluations, and m(x) is the masking polynomial
+// (zero without zero-knowledge).
+// At the FRI query point every f_i is opened against its own commitment, which lets the verifier
+// recompute the combination and check it against the first layer of the fold.
+#[derive(Debug)]
+pub struct BatchFriProof<F: PrimeField> {
+    evaluations: Vec<F>,
+    openings: Vec<QueryOpening<F>>,
+    masking: Option<MaskingOpening<F>>,
+    combined_proof: FriProof<F>,
+}
+
+impl<F: PrimeField> BatchFriProof<F> {
+
+    // EmptyBatch without polynomials, LengthMismatch unless there is one commitment per polynomial
+    // and the polynomials have the same length, which is then returned.
+    fn check_batch(polynomials: &[Polynomial<F>], commitments: &[SaltedCommitment<F>]) -> Result<usize, SynthesisError> {
+        let first = polynomials.first().ok_or(SynthesisError::EmptyBatch)?;
+        if polynomials.len() != commitments.len() {
+            return Err(SynthesisError::LengthMismatch(polynomials.len(), commitments.len()));
+        }
+        if let Some(other) = polynomials.iter().find(|p| p.len() != first.len()) {
+            return Err(SynthesisError::LengthMismatch(first.len(), other.len()));
+        }
+        Ok(first.len())
+    }
+
+    pub fn prove(polynomials: &[Polynomial<F>], commitments: &[SaltedCommitment<F>], r: F) -> Result<Self, SynthesisError> {
+        Self::prove_with_mask(polynomials, commitments, r, None)
+    }
+
+    // Zero-knowledge batched opening. The commitments should be salted and the polynomials
+    // blinded, otherwise the openings at the query point still leak.
+    pub fn prove_zk<R: RngCore>(polynomials: &[Polynomial<F>], commitments: &[SaltedCommitment<F>], r: F, rng: &mut R) -> Result<Self, SynthesisError> {
+        let len = Self::check_batch(polynomials, commitments)?;
+        let masking_polynomial = Polynomial::masking_polynomial(len, rng);
+        let masking_commitment = masking_polynomial.salted_commitment(rng);
+        Self::prove_with_mask(polynomials, commitments, r, Some((masking_polynomial, masking_commitment)))
+    }
+
+    fn prove_with_mask(
+        polynomials: &[Polynomial<F>],
+        commitments: &[SaltedCommitment<F>],
+        r: F,
+        masking: Option<(Polynomial<F>, SaltedCommitment<F>)>
+    ) -> Result<Self, SynthesisError> {
+
+        let len = Self::check_batch(polynomials, commitments)?;
+
+        let evaluations = polynomials.iter().map(|p| p.eval_single(&r)).collect::<Vec<_>>();
+        let masking_evaluation = masking.as_ref().map(|(m, _)| m.eval_single(&r));
+        let roots = commitments.iter().map(|c| c.commitment().clone()).collect::<Vec<_>>();
+        let alpha = Self::batching_challenge(
+            &roots,
+            masking.as_ref().map(|(_, c)| c.commitment()).zip(masking_evaluation),
+            &r,
+            &evaluations,
+        );
+
+        let mut combined = match &masking {
+            Some((m, _)) => m.coefficients(),
+            None => vec![F::ZERO; len],
+        };
+        let mut power = F::ONE;
+        for polynomial in polynomials {
+            for (c, a) in combined.iter_mut().zip(polynomial.coefficients()) {
+                *c += power * a;
+            }
+            power *= alpha;
+        }
+
+        let combined_proof = FriProof::evaluation_proof(&Polynomial::from_vec(combined), Some(r));
+        let query = combined_proof.query_root();
+
+        let openings = polynomials
+            .iter()
+            .zip(commitments)
+            .map(|(p, c)| p.open_at(&query, c).expect("Same-length polynomials share the evaluation domain"))
+            .collect();
+
+        let masking = masking.zip(masking_evaluation).map(|((m, c), evaluation)| MaskingOpening {
+            evaluation,
+            opening: m.open_at(&query, &c).expect("Same-length polynomials share the evaluation domain"),
+            commitment: c.commitment,
+        });
+
+        Ok(Self {
+            evaluations,
+            openings,
+            masking,
+            combined_proof,
+        })
+    }
+
+    // The challenge has to bind the claimed evaluations as well as the commitments. Otherwise a
+    // prover who knows a before sending them can shift f_0(r) by a*d and f_1(r) by -d, which leaves
+    // the combination at r, and with it the batched check, unchanged.
+    fn batching_challenge(
+        commitments: &[FriCommitment<F>],
+        masking: Option<(&FriCommitment<F>, F)>,
+        r: &F,
+        evaluations: &[F]
+    ) -> F {
+        Self::batching_transcript(commitments, masking, r, evaluations).interpret_as_element()
+    }
+
+    // Hash of everything the batching challenge depends on.
+    pub(crate) fn batching_transcript(
+        commitments: &[FriCommitment<F>],
+        masking: Option<(&FriCommitment<F>, F)>,
+        r: &F,
+        evaluations: &[F]
+    ) -> FriCommitment<F> {
+        let mut hasher = Hasher::new();
+        for commitment in commitments {
+            hasher.update(commitment.value().as_bytes().as_slice());
+        }
+        if let Some((commitment, _)) = masking {
+            hasher.update(commitment.value().as_bytes().as_slice());
+        }
+        hasher.update(r.to_repr().as_ref());
+        for evaluation in evaluations {
+            hasher.update(evaluation.to_repr().as_ref());
+        }
+        if let Some((_, evaluation)) = masking {
+            hasher.update(evaluation.to_repr().as_ref());
+        }
+        FriCommitment::new(hasher.finalize())
+    }
+
+    // The claimed evaluations f_i(r), in the order the polynomials were given to the prover.
+    pub fn evaluations(&self) -> &Vec<F> {
+        &self.evaluations
+    }
+
+    pub fn verify(&self, commitments: &[FriCommitment<F>], r: &F) -> VerificationResult {
+
+        if commitments.len() != self.evaluations.len() || commitments.len() != self.openings.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation and opening per commitment"));
+        }
+        let combined = self.combined_proof.verify();
+        if !combined.is_valid() { return combined; }
+
+        // Check the openings at the query point against the commitments, the masking polynomial
+        // counting as the last one
+        for (index, (opening, commitment)) in self.openings.iter().zip(commitments).enumerate() {
+            if !opening.check(commitment) { return VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index }); }
+        }
+        if let Some(m) = &self.masking {
+            if !m.opening.check(&m.commitment) {
+                return VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index: commitments.len() });
+            }
+        }
+
+        // Recompute the combination at the query point and at r
+        let alpha = Self::batching_challenge(
+            commitments,
+            self.masking.as_ref().map(|m| (&m.commitment, m.evaluation)),
+            r,
+            &self.evaluations,
+        );
+        let (mut at_query, mut at_r) = match &self.masking {
+            Some(m) => (m.opening.value(), m.evaluation),
+            None => (F::ZERO, F::ZERO),
+        };
+        let mut power = F::ONE;
+        for (opening, evaluation) in self.openings.iter().zip(&self.evaluations) {
+            at_query += power * opening.value();
+            at_r += power * evaluation;
+            power *= alpha;
+        }
+
+        // The first layer of the fold is w(x) = (f(x) - f(r))/(x - r)
+        let query = self.combined_proof.query_root();
+        let w = self.combined_proof.fri_challenge().positive_evaluation();
+        if w * (query - r) != at_query - at_r { return VerificationResult::InvalidProof(RejectionReason::BatchingInconsistency); }
+
+        VerificationResult::ValidProof
+    }
+}
+
+#[cfg(test)]
+mod zk_tests {
+
+    use super::*;
+    use crate::{
+        field::goldilocks::Goldilocks,
+        domains::Domain,
+        fri::{domain_offset, FriPcs},
+        pcs::PolynomialCommitmentScheme,
+        FriProof,
+    };
+
+    use ff::Field;
+
+    #[test]
+    fn blinding_preserves_domain() {
+        let mut rng = rand::thread_rng();
+        let f_x = Polynomial::<Goldilocks>::random(8);
+        let blinded = f_x.blind(8, 2, &mut rng);
+
+        assert_eq!(blinded.len(), 10);
+        let omega: Goldilocks = Domain::new_for_size(8).unwrap().generator;
+        let mut x = Goldilocks::ONE;
+        for _ in 0..8 {
+            assert_eq!(f_x.eval_single(&x), blinded.eval_single(&x));
+            x *= omega;
+        }
+        let outside = Goldilocks::from(3);
+        assert_ne!(f_x.eval_single(&outside), blinded.eval_single(&outside));
+    }
+
+    #[test]
+    fn salted_commitments_open() {
+        let mut rng = rand::thread_rng();
+        let f_x = Polynomial::<Goldilocks>::random(8);
+        let first = f_x.salted_commitment(&mut rng);
+        let second = f_x.salted_commitment(&mut rng);
+
+        assert!(first.is_hiding());
+        assert_ne!(first.commitment().value(), second.commitment().value());
+        assert_ne!(first.commitment().value(), f_x.commitment().value());
+
+        let point: Goldilocks = Domain::root_with_order_unchecked(32);
+        let point = domain_offset::<Goldilocks>(32) * point.square();
+        let opening = f_x.open_at(&point, &first).unwrap();
+        assert_eq!(f_x.open_at(&Goldilocks::from(3), &first).unwrap_err(), SynthesisError::PointNotInDomain);
+        assert!(opening.check(first.commitment()));
+        assert!(!opening.check(second.commitment()));
+    }
+
+    #[test]
+    fn fri_rejection_reasons() {
+        let f_x = Polynomial::<Goldilocks>::random(16);
+        let proof = FriProof::evaluation_proof(&f_x, None);
+        assert!(proof.verify().is_valid());
+
+        let mut tampered = proof.clone();
+        tampered.fri_challenge.positive_evaluation += Goldilocks::ONE;
+        assert_eq!(tampered.verify().rejection_reason(), Some(RejectionReason::QueryValueMismatch { layer: 0 }));
+
+        let mut tampered = proof.clone();
+        tampered.fri_challenge.fold_queries[0] += Goldilocks::ONE;
+        assert_eq!(tampered.verify().rejection_reason(), Some(RejectionReason::QueryValueMismatch { layer: 1 }));
+
+        let mut tampered = proof.clone();
+        tampered.fri_challenge.commitment_vector[0] = proof.w_com.clone();
+        assert_eq!(tampered.verify().rejection_reason(), Some(RejectionReason::MerkleRootMismatch { layer: 1 }));
+
+        let mut tampered = proof.clone();
+        tampered.fri_challenge.fold_queries.pop();
+        assert!(matches!(tampered.verify().rejection_reason(), Some(RejectionReason::MalformedProof(_))));
+
+        let mut tampered = proof.clone();
+        tampered.fri_challenge.commitment_vector.clear();
+        assert_eq!(tampered.verify().rejection_reason(), Some(RejectionReason::MalformedProof("no fold commitments")));
+    }
+
+    #[test]
+    fn batch_opening() {
+        let polynomials = (0..3).map(|_| Polynomial::<Goldilocks>::random(8)).collect::<Vec<_>>();
+        let commitments = polynomials.iter().map(|p| SaltedCommitment::from(p.commitment())).collect::<Vec<_>>();
+        let roots = commitments.iter().map(|c| c.commitment().clone()).collect::<Vec<_>>();
+        let r = Goldilocks::from(12345);
+
+        let mut proof = BatchFriProof::prove(&polynomials, &commitments, r).unwrap();
+        assert_eq!(proof.evaluations()[1], polynomials[1].eval_single(&r));
+        assert!(proof.verify(&roots, &r).is_valid());
+
+        proof.evaluations[1] += Goldilocks::ONE;
+        assert_eq!(proof.verify(&roots, &r), VerificationResult::InvalidProof(RejectionReason::BatchingInconsistency));
+        assert!(matches!(proof.verify(&roots[1..], &r).rejection_reason(), Some(RejectionReason::MalformedProof(_))));
+    }
+
+    #[test]
+    fn compensating_evaluations_are_rejected() {
+        let polynomials = (0..2).map(|_| Polynomial::<Goldilocks>::random(8)).collect::<Vec<_>>();
+        let commitments = polynomials.iter().map(|p| SaltedCommitment::from(p.commitment())).collect::<Vec<_>>();
+        let roots = commitments.iter().map(|c| c.commitment().clone()).collect::<Vec<_>>();
+        let r = Goldilocks::from(4242);
+
+        // Shift the evaluations so that their combination with the honest challenge is unchanged
+        let mut proof = BatchFriProof::prove(&polynomials, &commitments, r).unwrap();
+        let alpha = BatchFriProof::batching_challenge(&roots, None, &r, proof.evaluations());
+        let delta = Goldilocks::from(5);
+        proof.evaluations[0] += alpha * delta;
+        proof.evaluations[1] -= delta;
+        assert_eq!(proof.verify(&roots, &r), VerificationResult::InvalidProof(RejectionReason::BatchingInconsistency));
+
+        // The same forgery through FriPcs, claiming the shifted evaluations
+        let evaluations = proof.evaluations().clone();
+        let result = FriPcs.batch_verify(&roots, &r, &evaluations, &proof);
+        assert_eq!(result, VerificationResult::InvalidProof(RejectionReason::BatchingInconsistency));
+    }
+
+    #[test]
+    fn queries_avoid_the_constraint_domain() {
+        let mut rng = rand::thread_rng();
+        let n = 8u64;
+        let f_x = Polynomial::<Goldilocks>::random(n as usize).blind(n as usize, 2, &mut rng);
+
+        // Every point the commitment is made of lies outside of H = <omega_n>
+        let size = f_x.commitment_domain_size() as u64;
+        let omega: Goldilocks = Domain::root_with_order_unchecked(size);
+        let mut x = domain_offset::<Goldilocks>(size as usize);
+        for _ in 0..size {
+            assert_ne!(x.pow([n]), Goldilocks::ONE);
+            x *= omega;
+        }
+
+        for _ in 0..8 {
+            let proof = FriProof::evaluation_proof(&f_x.blind(n as usize, 2, &mut rng), None);
+            assert_ne!(proof.query_root().pow([n]), Goldilocks::ONE);
+        }
+    }
+
+    #[test]
+    fn zk_batch_opening() {
+        let mut rng = rand::thread_rng();
+        let polynomials = (0..3).map(|_| Polynomial::<Goldilocks>::random(8).blind(8, 2, &mut rng)).collect::<Vec<_>>();
+        let commitments = polynomials.iter().map(|p| p.salted_commitment(&mut rng)).collect::<Vec<_>>();
+        let roots = commitments.iter().map(|c| c.commitment().clone()).collect::<Vec<_>>();
+        let r = Goldilocks::from(777);
+
+        let mut proof = BatchFriProof::prove_zk(&polynomials, &commitments, r, &mut rng).unwrap();
+        assert!(proof.verify(&roots, &r).is_valid());
+
+        assert!(matches!(BatchFriProof::prove_zk(&[], &[], r, &mut rng), Err(SynthesisError::EmptyBatch)));
+        assert!(matches!(BatchFriProof::prove_zk(&polynomials, &commitments[1..], r, &mut rng), Err(SynthesisError::LengthMismatch(3, 2))));
+        let uneven = vec![polynomials[0].clone(), Polynomial::<Goldilocks>::random(4)];
+        assert!(matches!(BatchFriProof::prove_zk(&uneven, &commitments[..2], r, &mut rng), Err(SynthesisError::LengthMismatch(10, 4))));
+
+        proof.masking.as_mut().unwrap().evaluation += Goldilocks::ONE;
+        assert!(!proof.verify(&roots, &r).is_valid());
+    }
+
+    #[test]
+    fn short_polynomials() {
+        // The quotient opened for a polynomial of length one or two has at most one coefficient,
+        // which is still folded once
+        let pcs = FriPcs;
+        let r = Goldilocks::from(99);
+        for len in 1..=3 {
+            let f_x = Polynomial::<Goldilocks>::random(len);
+            assert!(FriProof::evaluation_proof(&f_x, Some(r)).verify().is_valid(), "length {}", len);
+
+            let commitment = pcs.commit(&f_x).unwrap();
+            let (evaluations, proof) = pcs.open(&f_x, &[r]).unwrap();
+            assert_eq!(evaluations, vec![f_x.eval_single(&r)]);
+            assert!(pcs.verify(&commitment, &[r], &evaluations, &proof).is_valid(), "length {}", len);
+
+            let polynomials = vec![f_x.clone(), Polynomial::<Goldilocks>::random(len)];
+            let commitments = polynomials.iter().map(|p| pcs.commit(p).unwrap()).collect::<Vec<_>>();
+            let (evaluations, proof) = pcs.batch_open(&polynomials, r).unwrap();
+            assert!(pcs.batch_verify(&commitments, &r, &evaluations, &proof).is_valid(), "length {}", len);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use ff::{Field, PrimeField};
+use blake3::Hasher;
+use rand::RngCore;
+use bn::{G1, G2, Gt, Group, AffineG1, pairing_batch};
+
+use crate::{
+    pcs::PolynomialCommitmentScheme,
+    polynomial::Polynomial,
+    field::bn254::Fr,
+    utils::field_element_from_bytes,
+    error::{SynthesisError, VerificationResult, RejectionReason},
+};
+
+/*
+ * KZG polynomial commitments over BN254, the curve with pairing precompiles on Ethereum.
+ *
+ * Given the structured reference string [1]G1, [tau]G1, ..., [tau^d]G1 and [tau]G2, the commitment
+ * to f(x) is C = [f(tau)]G1. To open at z, the prover sends pi = [q(tau)]G1 for the quotient
+ * q(x) = (f(x) - f(z))/(x - z), and the verifier checks the quotient relation "in the exponent":
+ *
+ *     e(C - [f(z)]G1, G2) == e(pi, [tau]G2 - [z]G2)
+ *
+ * Commitments and proofs are a single group element each, independent of the degree.
+*/
+
+#[derive(Debug, Clone)]
+pub struct Kzg {
+    powers_of_tau: Vec<G1>,
+    g2: G2,
+    tau_g2: G2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KzgCommitment(G1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KzgProof(G1);
+
+fn to_bn(x: &Fr) -> bn::Fr {
+    let mut bytes = x.to_repr();
+    bytes.as_mut().reverse();
+    bn::Fr::from_slice(bytes.as_ref()).expect("Both fields have the same modulus.")
+}
+
+// Sum of scalars[i] * bases[i], PolynomialTooLarge if there are more scalars than bases.
+fn msm(bases: &[G1], scalars: &[Fr]) -> Result<G1, SynthesisError> {
+    if scalars.len() > bases.len() {
+        return Err(SynthesisError::PolynomialTooLarge(scalars.len(), bases.len()));
+    }
+    Ok(bases
+        .iter()
+        .zip(scalars)
+        .filter(|(_, s)| !bool::from(s.is_zero()))
+        .fold(G1::zero(), |acc, (b, s)| acc + (*b * to_bn(s))))
+}
+
+fn hash_point(hasher: &mut Hasher, point: &G1) {
+    let mut bytes = [0u8; 32];
+    match AffineG1::from_jacobian(*point) {
+        Some(affine) => {
+            affine.x().to_big_endian(&mut bytes).unwrap();
+            hasher.update(bytes.as_slice());
+            affine.y().to_big_endian(&mut bytes).unwrap();
+            hasher.update(bytes.as_slice());
+        }
+        // Point at infinity
+        None => { hasher.update(bytes.as_slice()); }
+    }
+}
+
+impl Kzg {
+
+    // Generates a reference string from a random tau. Whoever runs this knows tau and can forge
+    // openings, so this is only suitable for testing. Production use needs the output of a
+    // powers-of-tau ceremony, loaded with `from_srs`.
+    pub fn setup_with_rng<R: RngCore>(max_len: usize, rng: &mut R) -> Self {
+        let tau = Fr::random(&mut *rng);
+        let g1 = G1::one();
+
+        let mut powers_of_tau = Vec::with_capacity(max_len);
+        let mut power = Fr::ONE;
+        for _ in 0..max_len {
+            powers_of_tau.push(g1 * to_bn(&power));
+            power *= tau;
+        }
+
+        Self {
+            powers_of_tau,
+            g2: G2::one(),
+            tau_g2: G2::one() * to_bn(&tau),
+        }
+    }
+
+    pub fn from_srs(powers_of_tau: Vec<G1>, g2: G2, tau_g2: G2) -> Self {
+        Self {
+            powers_of_tau,
+            g2,
+            tau_g2,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.powers_of_tau.len()
+    }
+
+    fn commit_coefficients(&self, coefficients: &[Fr]) -> Result<G1, SynthesisError> {
+        msm(&self.powers_of_tau, coefficients)
+    }
+
+    // The quotient is one coefficient shorter than the polynomial, so the length is checked
+    // separately: a proof for a polynomial that cannot be committed to is of no use.
+    fn open_single(&self, polynomial: &Polynomial<Fr>, z: Fr) -> Result<(Fr, KzgProof), SynthesisError> {
+        if polynomial.len() > self.max_len() {
+            return Err(SynthesisError::PolynomialTooLarge(polynomial.len(), self.max_len()));
+        }
+        let q_x = polynomial.shift_polynomial(z);
+        Ok((polynomial.eval_single(&z), KzgProof(self.commit_coefficients(&q_x.coefficients())?)))
+    }
+
+    fn check_single(&self, commitment: &G1, z: &Fr, evaluation: &Fr, proof: &KzgProof) -> bool {
+        let g1 = G1::one();
+        let lhs = *commitment - (g1 * to_bn(evaluation));
+        let rhs = self.tau_g2 - (self.g2 * to_bn(z));
+
+        // e(C - [v]G1, G2) * e(-pi, [tau - z]G2) == 1
+        pairing_batch(&[(lhs, self.g2), (-proof.0, rhs)]) == Gt::one()
+    }
+
+    // Challenge used to combine the polynomials of a batched opening. It covers the claimed
+    // evaluations, otherwise a prover who knows gamma could move y_0 by gamma*d and y_1 by -d
+    // without changing the combined evaluation.
+    fn batching_challenge(commitments: &[KzgCommitment], point: &Fr, evaluations: &[Fr]) -> Fr {
+        let mut hasher = Hasher::new();
+        for commitment in commitments {
+            hash_point(&mut hasher, &commitment.0);
+        }
+        hasher.update(point.to_repr().as_ref());
+        for evaluation in evaluations {
+            hasher.update(evaluation.to_repr().as_ref());
+        }
+        field_element_from_bytes(hasher.finalize().as_bytes().as_slice())
+    }
+}
+
+impl PolynomialCommitmentScheme<Fr> for Kzg {
+    type Commitment = KzgCommitment;
+    type Proof = Vec<KzgProof>;
+    type BatchProof = KzgProof;
+
+    fn setup(max_len: usize) -> Self {
+        Self::setup_with_rng(max_len, &mut rand::thread_rng())
+    }
+
+    // PolynomialTooLarge for polynomials with more coefficients than the reference string has powers.
+    fn commit(&self, polynomial: &Polynomial<Fr>) -> Result<Self::Commitment, SynthesisError> {
+        Ok(KzgCommitment(self.commit_coefficients(&polynomial.coefficients())?))
+    }
+
+    fn challenge(commitments: &[Self::Commitment]) -> Fr {
+        let mut hasher = Hasher::new();
+        for commitment in commitments {
+            hash_point(&mut hasher, &commitment.0);
+        }
+        field_element_from_bytes(hasher.finalize().as_bytes().as_slice())
+    }
+
+    fn open(&self, polynomial: &Polynomial<Fr>, points: &[Fr]) -> Result<(Vec<Fr>, Self::Proof), SynthesisError> {
+        points.iter().map(|z| self.open_single(polynomial, *z)).collect::<Result<Vec<_>, _>>().map(|openings| openings.into_iter().unzip())
+    }
+
+    // Opens f_0 + g*f_1 + ... + g^k*f_k, where g is derived from the commitments, the point and
+    // the evaluations.
+    fn batch_open(&self, polynomials: &[Polynomial<Fr>], point: Fr) -> Result<(Vec<Fr>, Self::BatchProof), SynthesisError> {
+        let commitments = polynomials.iter().map(|p| self.commit(p)).collect::<Result<Vec<_>, _>>()?;
+        let evaluations = polynomials.iter().map(|p| p.eval_single(&point)).collect::<Vec<_>>();
+        let gamma = Self::batching_challenge(&commitments, &point, &evaluations);
+
+        let len = polynomials.iter().map(|p| p.len()).max().ok_or(SynthesisError::EmptyBatch)?;
+        let mut combined = vec![Fr::ZERO; len];
+        let mut power = Fr::ONE;
+        for polynomial in polynomials {
+            for (c, a) in combined.iter_mut().zip(polynomial.coefficients()) {
+                *c += power * a;
+            }
+            power *= gamma;
+        }
+
+        let (_, proof) = self.open_single(&Polynomial::from_vec(combined), point)?;
+        Ok((evaluations, proof))
+    }
+
+    fn verify(&self, commitment: &Self::Commitment, points: &[Fr], evaluations: &[Fr], proof: &Self::Proof) -> VerificationResult {
+        if points.len() != evaluations.len() || points.len() != proof.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation and proof per point"));
+        }
+        for (index, ((z, y), pi)) in points.iter().zip(evaluations).zip(proof).enumerate() {
+            if !self.check_single(&commitment.0, z, y, pi) { return VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index }); }
+        }
+        VerificationResult::ValidProof
+    }
+
+    fn batch_verify(&self, commitments: &[Self::Commitment], point: &Fr, evaluations: &[Fr], proof: &Self::BatchProof) -> VerificationResult {
+        if commitments.len() != evaluations.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation per commitment"));
+        }
+        let gamma = Self::batching_challenge(commitments, point, evaluations);
+
+        let mut combined_commitment = G1::zero();
+        let mut combined_evaluation = Fr::ZERO;
+        let mut power = Fr::ONE;
+        for (commitment, evaluation) in commitments.iter().zip(evaluations) {
+            combined_commitment = combined_commitment + (commitment.0 * to_bn(&power));
+            combined_evaluation += power * evaluation;
+            power *= gamma;
+        }
+
+        // The batch is opened with a single proof
+        if !self.check_single(&combined_commitment, point, &combined_evaluation, proof) {
+            return VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index: 0 });
+        }
+        VerificationResult::ValidProof
+    }
+}
+
+#[cfg(test)]
+mod kzg_tests {
+
+    use super::*;
+    use crate::domains::Domain;
+    use crate::polynomial::Evaluations;
+
+    #[test]
+    fn open_and_verify() {
+        let kzg = Kzg::setup(16);
+        let f_x = Polynomial::<Fr>::random(8);
+        let commitment = kzg.commit(&f_x).unwrap();
+        let points = [Fr::from(3), Fr::from(1234567)];
+
+        let (evaluations, proof) = kzg.open(&f_x, &points).unwrap();
+        assert_eq!(evaluations[1], f_x.eval_single(&points[1]));
+        assert!(kzg.verify(&commitment, &points, &evaluations, &proof).is_valid());
+
+        let wrong = [evaluations[0], evaluations[1] + Fr::ONE];
+        assert_eq!(kzg.verify(&commitment, &points, &wrong, &proof), VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index: 1 }));
+        assert_eq!(kzg.verify(&commitment, &[points[1], points[0]], &evaluations, &proof), VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index: 0 }));
+        assert!(matches!(kzg.verify(&commitment, &points[..1], &evaluations, &proof).rejection_reason(), Some(RejectionReason::MalformedProof(_))));
+
+        // More coefficients than powers of tau
+        let g_x = Polynomial::<Fr>::random(17);
+        assert_eq!(kzg.commit(&g_x), Err(SynthesisError::PolynomialTooLarge(17, 16)));
+        assert_eq!(kzg.open(&g_x, &points).map(|_| ()), Err(SynthesisError::PolynomialTooLarge(17, 16)));
+        assert!(matches!(kzg.batch_open(&[f_x, g_x], points[0]), Err(SynthesisError::PolynomialTooLarge(17, 16))));
+    }
+
+    #[test]
+    fn batch_open_and_verify() {
+        let kzg = Kzg::setup(16);
+        let polynomials = vec![Polynomial::<Fr>::random(8), Polynomial::<Fr>::random(4), Polynomial::<Fr>::random(16)];
+        let commitments = polynomials.iter().map(|p| kzg.commit(p).unwrap()).collect::<Vec<_>>();
+        let point = Fr::from(42);
+
+        let (evaluations, proof) = kzg.batch_open(&polynomials, point).unwrap();
+        assert!(kzg.batch_verify(&commitments, &point, &evaluations, &proof).is_valid());
+        assert!(matches!(kzg.batch_open(&[], point), Err(SynthesisError::EmptyBatch)));
+
+        let mut wrong = evaluations.clone();
+        wrong[2] += Fr::ONE;
+        assert!(!kzg.batch_verify(&commitments, &point, &wrong, &proof).is_valid());
+
+        // Shifts that cancel out in the combination under the honest challenge
+        let gamma = Kzg::batching_challenge(&commitments, &point, &evaluations);
+        let delta = Fr::from(11);
+        let mut shifted = evaluations.clone();
+        shifted[0] += gamma * delta;
+        shifted[1] -= delta;
+        assert_eq!(
+            kzg.batch_verify(&commitments, &point, &shifted, &proof),
+            VerificationResult::InvalidProof(RejectionReason::InvalidOpening { index: 0 })
+        );
+    }
+
+    #[test]
+    fn product_check_with_kzg() {
+        const SIZE: usize = 8;
+        let kzg = Kzg::setup(4 * SIZE * SIZE);
+
+        let rng = rand::thread_rng();
+        let mut evaluations = (0..SIZE).map(|_| Fr::random(rng.clone())).collect::<Vec<_>>();
+        let product = evaluations[..SIZE-1].iter().fold(Fr::ONE, |acc, e| acc * e);
+        evaluations[SIZE-1] = product.invert().unwrap();
+
+        let domain = Domain::new_for_size(SIZE as u64).unwrap();
+        let f_x = Evaluations::from_vec_and_domain(evaluations, domain).unwrap().interpolate();
+
+        let proof = f_x.product_check(&kzg).unwrap();
+        assert!(proof.verify(&kzg, &kzg.commit(&f_x).unwrap(), SIZE as u64).is_valid());
+    }
+}
@@ -4,14 +4,19 @@ use crate::{
     fri::{FriCommitment, FriChallenge}
 };
 
-mod fft;
-mod fri;
-mod polynomial;
-mod utils;
-mod field;
-mod domains;
-mod plonk;
-mod constants;
+pub mod fft;
+pub mod fri;
+pub mod polynomial;
+pub mod utils;
+pub mod field;
+pub mod domains;
+pub mod plonk;
+pub mod pcs;
+pub mod kzg;
+pub mod constants;
+pub mod error;
+
+pub use error::{SynthesisError, VerificationResult, RejectionReason};
 
 /*
 #[derive(Debug)]
@@ -19,7 +24,7 @@ pub struct PlonkyProof {
 }
 */
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FriProof<F: PrimeField> {
     w_com: FriCommitment<F>,
     fri_challenge: FriChallenge<F>
@@ -48,6 +53,7 @@ mod plonky2 {
         field::goldilocks::Goldilocks,
         FriChallenge,
         domains::Domain,
+        fri::domain_offset,
         constants::*,
     };
 
@@ -58,7 +64,7 @@ mod plonky2 {
     fn random_root_of_unity<R: RngCore, F: PrimeField>(rng: &mut R, size: u64) -> F {
         let random_exponent = rng.gen::<u64>();
         let root: F = Domain::root_with_order_unchecked(size);
-        root.pow([random_exponent])
+        domain_offset::<F>(size as usize) * root.pow([random_exponent])
     }
 
     #[test]
@@ -92,8 +98,8 @@ mod plonky2 {
 
         // ... (P) asks the prover for an authentication path for a random root of unity and its
         // negative counterpart for w_x.
-        let positive_authentication_path = w_x.authentication_path_for(&random_root_of_unity);
-        let negative_authentication_path = w_x.authentication_path_for(&-random_root_of_unity);
+        let positive_authentication_path = w_x.authentication_path_for(&random_root_of_unity).unwrap();
+        let negative_authentication_path = w_x.authentication_path_for(&-random_root_of_unity).unwrap();
         let positive_evaluation = w_x.eval_single(&random_root_of_unity);
         let negative_evaluation = w_x.eval_single(&-random_root_of_unity);
         
@@ -105,9 +111,9 @@ mod plonky2 {
         let mut auth_vec = Vec::with_capacity(polynomial_vector.len());
         let mut query_vec = Vec::with_capacity(polynomial_vector.len());
         let mut target = random_root_of_unity.square();
-        for i in 0..polynomial_vector.len()-1 {
-            auth_vec.push(polynomial_vector[i].authentication_path_for(&-target));
-            query_vec.push(polynomial_vector[i].eval_single(&-target));
+        for polynomial in polynomial_vector.iter().take(polynomial_vector.len()-1) {
+            auth_vec.push(polynomial.authentication_path_for(&-target).unwrap());
+            query_vec.push(polynomial.eval_single(&-target));
             target = target.square();
         }
         
@@ -0,0 +1,46 @@
+use ff::PrimeField;
+
+use core::fmt::Debug;
+
+use crate::{
+    polynomial::Polynomial,
+    error::{SynthesisError, VerificationResult},
+};
+
+pub mod trivial;
+
+/*
+ * Interface between the PLONK protocols and the polynomial commitment scheme underneath them.
+ * The protocols only ever commit to polynomials, derive challenges from commitments, and ask for
+ * evaluations together with a proof that they are consistent with a commitment, so any scheme
+ * providing these operations can be plugged in (FRI, KZG, or the trivial scheme that just sends
+ * the polynomial, which is useful to test protocol logic in isolation).
+ *
+ * Committing and opening fail for inputs the scheme cannot take, e.g. polynomials longer than the
+ * scheme was set up for. Verification does not fail, it accepts or rejects.
+*/
+pub trait PolynomialCommitmentScheme<F: PrimeField> {
+    type Commitment: Clone + Debug;
+
+    // Proof that a polynomial evaluates to the claimed values at a list of points.
+    type Proof: Debug;
+
+    // Proof that several polynomials evaluate to the claimed values at a single point.
+    type BatchProof: Debug;
+
+    // Prepares the scheme for polynomials with at most `max_len` coefficients.
+    fn setup(max_len: usize) -> Self;
+
+    fn commit(&self, polynomial: &Polynomial<F>) -> Result<Self::Commitment, SynthesisError>;
+
+    // Fiat-Shamir: a field element that depends on every commitment in the list.
+    fn challenge(commitments: &[Self::Commitment]) -> F;
+
+    fn open(&self, polynomial: &Polynomial<F>, points: &[F]) -> Result<(Vec<F>, Self::Proof), SynthesisError>;
+
+    fn batch_open(&self, polynomials: &[Polynomial<F>], point: F) -> Result<(Vec<F>, Self::BatchProof), SynthesisError>;
+
+    fn verify(&self, commitment: &Self::Commitment, points: &[F], evaluations: &[F], proof: &Self::Proof) -> VerificationResult;
+
+    fn batch_verify(&self, commitments: &[Self::Commitment], point: &F, evaluations: &[F], proof: &Self::BatchProof) -> VerificationResult;
+}
@@ -0,0 +1,124 @@
+use ff::PrimeField;
+use blake3::Hasher;
+
+use crate::{
+    pcs::PolynomialCommitmentScheme,
+    polynomial::Polynomial,
+    utils::field_element_from_bytes,
+    error::{SynthesisError, VerificationResult, RejectionReason},
+};
+
+// The "commitment" is the polynomial itself and openings carry no proof, the verifier simply
+// evaluates. Neither hiding nor succinct, but it lets the protocols be tested without any
+// cryptography in the way.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrivialPcs;
+
+impl<F: PrimeField> PolynomialCommitmentScheme<F> for TrivialPcs {
+    type Commitment = Polynomial<F>;
+    type Proof = ();
+    type BatchProof = ();
+
+    fn setup(_max_len: usize) -> Self {
+        TrivialPcs
+    }
+
+    fn commit(&self, polynomial: &Polynomial<F>) -> Result<Self::Commitment, SynthesisError> {
+        Ok(polynomial.clone())
+    }
+
+    fn challenge(commitments: &[Self::Commitment]) -> F {
+        let mut hasher = Hasher::new();
+        for commitment in commitments {
+            for coefficient in commitment.coefficients() {
+                hasher.update(coefficient.to_repr().as_ref());
+            }
+        }
+        field_element_from_bytes(hasher.finalize().as_bytes().as_slice())
+    }
+
+    fn open(&self, polynomial: &Polynomial<F>, points: &[F]) -> Result<(Vec<F>, Self::Proof), SynthesisError> {
+        Ok((points.iter().map(|z| polynomial.eval_single(z)).collect(), ()))
+    }
+
+    fn batch_open(&self, polynomials: &[Polynomial<F>], point: F) -> Result<(Vec<F>, Self::BatchProof), SynthesisError> {
+        Ok((polynomials.iter().map(|p| p.eval_single(&point)).collect(), ()))
+    }
+
+    fn verify(&self, commitment: &Self::Commitment, points: &[F], evaluations: &[F], _proof: &Self::Proof) -> VerificationResult {
+        if points.len() != evaluations.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation per point"));
+        }
+        for (index, (z, y)) in points.iter().zip(evaluations).enumerate() {
+            if commitment.eval_single(z) != *y { return VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index }); }
+        }
+        VerificationResult::ValidProof
+    }
+
+    fn batch_verify(&self, commitments: &[Self::Commitment], point: &F, evaluations: &[F], _proof: &Self::BatchProof) -> VerificationResult {
+        if commitments.len() != evaluations.len() {
+            return VerificationResult::InvalidProof(RejectionReason::MalformedProof("one evaluation per commitment"));
+        }
+        for (index, (commitment, y)) in commitments.iter().zip(evaluations).enumerate() {
+            if commitment.eval_single(point) != *y { return VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index }); }
+        }
+        VerificationResult::ValidProof
+    }
+}
+
+#[cfg(test)]
+mod trivial_tests {
+
+    use super::*;
+    use crate::field::goldilocks::Goldilocks;
+
+    use ff::Field;
+
+    fn polynomial(coefficients: &[u64]) -> Polynomial<Goldilocks> {
+        Polynomial::from_vec(coefficients.iter().map(|c| Goldilocks::from(*c)).collect())
+    }
+
+    #[test]
+    fn open_and_verify() {
+        let pcs = TrivialPcs;
+        let f_x = polynomial(&[1, 2, 3]);
+        let commitment = pcs.commit(&f_x).unwrap();
+        let points = [Goldilocks::from(2), Goldilocks::from(5)];
+
+        let (evaluations, proof) = pcs.open(&f_x, &points).unwrap();
+        assert_eq!(evaluations, vec![Goldilocks::from(17), Goldilocks::from(86)]);
+        assert!(pcs.verify(&commitment, &points, &evaluations, &proof).is_valid());
+
+        let wrong = [evaluations[0], evaluations[1] + Goldilocks::ONE];
+        assert_eq!(pcs.verify(&commitment, &points, &wrong, &proof), VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index: 1 }));
+        assert!(matches!(pcs.verify(&commitment, &points, &evaluations[..1], &proof).rejection_reason(), Some(RejectionReason::MalformedProof(_))));
+    }
+
+    #[test]
+    fn batch_open_and_verify() {
+        let pcs = TrivialPcs;
+        let polynomials = vec![polynomial(&[1, 2, 3]), polynomial(&[4]), polynomial(&[0, 0, 0, 1])];
+        let commitments = polynomials.iter().map(|p| pcs.commit(p).unwrap()).collect::<Vec<_>>();
+        let point = Goldilocks::from(3);
+
+        let (evaluations, proof) = pcs.batch_open(&polynomials, point).unwrap();
+        assert!(pcs.batch_verify(&commitments, &point, &evaluations, &proof).is_valid());
+
+        let mut wrong = evaluations.clone();
+        wrong[0] += Goldilocks::ONE;
+        assert_eq!(pcs.batch_verify(&commitments, &point, &wrong, &proof), VerificationResult::InvalidProof(RejectionReason::EvaluationMismatch { index: 0 }));
+        assert!(matches!(pcs.batch_verify(&commitments[1..], &point, &evaluations, &proof).rejection_reason(), Some(RejectionReason::MalformedProof(_))));
+    }
+
+    #[test]
+    fn challenge_binds_every_commitment() {
+        let commitments = vec![polynomial(&[1, 2, 3]), polynomial(&[4, 5])];
+        let challenge: Goldilocks = TrivialPcs::challenge(&commitments);
+        assert_eq!(challenge, TrivialPcs::challenge(&commitments));
+
+        let mut changed = commitments.clone();
+        changed[1] = polynomial(&[4, 6]);
+        assert_ne!(challenge, TrivialPcs::challenge(&changed));
+        assert_ne!(challenge, TrivialPcs::challenge(&commitments[..1]));
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
 
 impl<F: PrimeField> Circuit<F> {
 
-    pub fn new(gates: Vec<Gate<F>>) -> Self {
+    pub fn new(_gates: Vec<Gate<F>>) -> Self {
         unimplemented!();
     }
 
@@ -1,69 +1,88 @@
 use ff::PrimeField;
 
 use crate::{
-    FriProof,
     polynomial::Polynomial,
-    fri::FriCommitment,
+    pcs::PolynomialCommitmentScheme,
 };
 
 mod protocols;
 mod circuit;
 mod proofs;
 
-pub struct Evaluation<F: PrimeField> {
+// The protocols of this module, as named in rejection reasons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    ZeroTest,
+    ProductCheck,
+    RationalProductCheck,
+    PermutationCheck,
+    PrescribedPermutationCheck,
+}
+
+// A claimed evaluation of a committed polynomial together with the opening proof of the
+// commitment scheme P.
+pub struct Evaluation<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
     eval: F,
-    eval_proof: FriProof<F>,
+    eval_proof: P::Proof,
 }
 
+#[allow(dead_code)]
 pub struct Circuit<F: PrimeField> {
     selector: Polynomial<F>,
     gates: Vec<Gate<F>>,
 }
 
+#[allow(dead_code)]
 pub struct Gate<F: PrimeField> {
     wires: (F, F),
 }
 
-pub struct ZeroTestProof<F: PrimeField> {
-    f_r: Evaluation<F>,
-    q_r: Evaluation<F>,
+pub struct ZeroTestProof<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
+    q_com: P::Commitment,
+    f_r: Evaluation<F, P>, // Com(f)
+    q_r: Evaluation<F, P>, // Com(q)
 }
 
-pub struct ProductCheckProof<F: PrimeField> {
-    end_eval: Evaluation<F>, // Com(t)
-    t_r: Evaluation<F>, // Com(t)
-    t_wr: Evaluation<F>, // Com(t)
-    q_r: Evaluation<F>, // Com(q)
-    f_wr: Evaluation<F>, // Com(f)
+pub struct ProductCheckProof<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
+    t_com: P::Commitment,
+    q_com: P::Commitment,
+    end_eval: Evaluation<F, P>, // Com(t)
+    t_r: Evaluation<F, P>, // Com(t)
+    t_wr: Evaluation<F, P>, // Com(t)
+    q_r: Evaluation<F, P>, // Com(q)
+    f_wr: Evaluation<F, P>, // Com(f)
 }
 
-pub struct RationalProductCheckProof<F: PrimeField> {
-    end_eval: Evaluation<F>, // Com(t)
-    t_r: Evaluation<F>,
-    t_wr: Evaluation<F>,
-    q_r: Evaluation<F>, // Com(q)
-    g_wr: Evaluation<F>, // Com(g)
-    f_wr: Evaluation<F>, // Com(f)
+pub struct RationalProductCheckProof<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
+    t_com: P::Commitment,
+    q_com: P::Commitment,
+    end_eval: Evaluation<F, P>, // Com(t)
+    t_r: Evaluation<F, P>,
+    t_wr: Evaluation<F, P>,
+    q_r: Evaluation<F, P>, // Com(q)
+    g_wr: Evaluation<F, P>, // Com(g)
+    f_wr: Evaluation<F, P>, // Com(f)
 }
 
-pub struct PermutationCheckProof<F: PrimeField> {
-    f_com: FriCommitment<F>,
-    end_eval: Evaluation<F>, // Com(t)
-    t_r: Evaluation<F>,
-    t_wr: Evaluation<F>,
-    q_r: Evaluation<F>, // Com(q)
-    g_wr: Evaluation<F>, // Com(g)
-    f_wr: Evaluation<F>, // Com(f)
+pub struct PermutationCheckProof<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
+    t_com: P::Commitment,
+    q_com: P::Commitment,
+    end_eval: Evaluation<F, P>, // Com(t)
+    t_r: Evaluation<F, P>,
+    t_wr: Evaluation<F, P>,
+    q_r: Evaluation<F, P>, // Com(q)
+    g_wr: Evaluation<F, P>, // Com(g)
+    f_wr: Evaluation<F, P>, // Com(f)
 }
 
-pub struct PrescribedPermutationCheckProof<F: PrimeField> {
-    f_com: FriCommitment<F>,
-    g_com: FriCommitment<F>,
-    end_eval: Evaluation<F>, // Com(t)
-    t_r: Evaluation<F>,
-    t_wr: Evaluation<F>,
-    q_r: Evaluation<F>, // Com(q)
-    g_wr: Evaluation<F>, // Com(g)
-    f_wr: Evaluation<F>, // Com(f)
-    w_wr: Evaluation<F>, // Com(w)
+pub struct PrescribedPermutationCheckProof<F: PrimeField, P: PolynomialCommitmentScheme<F>> {
+    t_com: P::Commitment,
+    q_com: P::Commitment,
+    end_eval: Evaluation<F, P>, // Com(t)
+    t_r: Evaluation<F, P>,
+    t_wr: Evaluation<F, P>,
+    q_r: Evaluation<F, P>, // Com(q)
+    g_wr: Evaluation<F, P>, // Com(g)
+    f_wr: Evaluation<F, P>, // Com(f)
+    w_wr: Evaluation<F, P>, // Com(w)
 }
@@ -1,16 +1,19 @@
 use ff::PrimeField;
 
 use crate::{
-    FriProof,
     plonk::{
         ZeroTestProof, 
         ProductCheckProof, 
         Evaluation, 
         RationalProductCheckProof, 
         PermutationCheckProof, 
-        PrescribedPermutationCheckProof
+        PrescribedPermutationCheckProof,
+        Protocol,
     },
-    fri::{FriCommitment, VerificationResult}
+    pcs::PolynomialCommitmentScheme,
+    polynomial::Polynomial,
+    domains::Domain,
+    error::{SynthesisError, VerificationResult, RejectionReason},
 };
 
 /*
@@ -23,18 +26,23 @@ macro_rules! validate {
 }
 */
 
-impl<F: PrimeField> Evaluation<F> {
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> Evaluation<F, P> {
     
-    pub fn new(eval: F, eval_proof: FriProof<F>) -> Self {
+    pub fn new(eval: F, eval_proof: P::Proof) -> Self {
         Self {
             eval,
             eval_proof,
         }
     }
 
-    pub fn check(&self) -> VerificationResult {
-        if self.eval_proof.verify().is_valid() { return VerificationResult::ValidProof; }
-        VerificationResult::InvalidProof
+    // Opens the polynomial at a single point.
+    pub fn open(pcs: &P, polynomial: &Polynomial<F>, point: F) -> Result<Self, SynthesisError> {
+        let (evals, eval_proof) = pcs.open(polynomial, &[point])?;
+        Ok(Self::new(evals[0], eval_proof))
+    }
+
+    pub fn check(&self, pcs: &P, commitment: &P::Commitment, point: F) -> VerificationResult {
+        pcs.verify(commitment, &[point], &[self.eval], &self.eval_proof)
     }
 
     pub fn evaluation(&self) -> F {
@@ -42,36 +50,48 @@ impl<F: PrimeField> Evaluation<F> {
     }
 }
 
-impl<F: PrimeField> ZeroTestProof<F> {
+fn invalid_evaluation(protocol: Protocol, evaluation: &'static str) -> VerificationResult {
+    VerificationResult::InvalidProof(RejectionReason::InvalidEvaluation { protocol, evaluation })
+}
+
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> ZeroTestProof<F, P> {
 
-    pub fn new(f_r: Evaluation<F>, q_r: Evaluation<F>) -> Self {
+    pub fn new(q_com: P::Commitment, f_r: Evaluation<F, P>, q_r: Evaluation<F, P>) -> Self {
         Self {
+            q_com,
             f_r,
             q_r,
         }
     }
 
-    pub fn verify(&self) -> VerificationResult {
-        if !self.f_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.q_r.check().is_valid() { return VerificationResult::InvalidProof; }
+    pub fn verify(&self, pcs: &P, f_com: &P::Commitment, z: &Polynomial<F>) -> VerificationResult {
+        let r = P::challenge(&[f_com.clone(), self.q_com.clone()]);
+
+        if !self.f_r.check(pcs, f_com, r).is_valid() { return invalid_evaluation(Protocol::ZeroTest, "f_r"); }
+        if !self.q_r.check(pcs, &self.q_com, r).is_valid() { return invalid_evaluation(Protocol::ZeroTest, "q_r"); }
 
-        let vp = F::ONE;
+        let vp = z.eval_single(&r);
 
-        if self.f_r.evaluation() != self.q_r.evaluation() * vp { return VerificationResult::InvalidProof; }
+        if self.f_r.evaluation() != self.q_r.evaluation() * vp { return VerificationResult::InvalidProof(RejectionReason::IdentityCheckFailed(Protocol::ZeroTest)); }
         VerificationResult::ValidProof
     }
 }
 
-impl<F: PrimeField> ProductCheckProof<F> {
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> ProductCheckProof<F, P> {
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        end_eval: Evaluation<F>,
-        t_r: Evaluation<F>,
-        t_wr: Evaluation<F>,
-        q_r: Evaluation<F>,
-        f_wr: Evaluation<F>,
+        t_com: P::Commitment,
+        q_com: P::Commitment,
+        end_eval: Evaluation<F, P>,
+        t_r: Evaluation<F, P>,
+        t_wr: Evaluation<F, P>,
+        q_r: Evaluation<F, P>,
+        f_wr: Evaluation<F, P>,
     ) -> Self {
         Self {
+            t_com,
+            q_com,
             end_eval,
             t_r,
             t_wr,
@@ -80,35 +100,45 @@ impl<F: PrimeField> ProductCheckProof<F> {
         }
     }
 
-    pub fn verify(&self) -> VerificationResult {
-        if !self.end_eval.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.q_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.f_wr.check().is_valid() { return VerificationResult::InvalidProof; }
+    // `size` is the number of roots of unity the product is taken over.
+    pub fn verify(&self, pcs: &P, f_com: &P::Commitment, size: u64) -> VerificationResult {
+        let Ok(domain) = Domain::<F>::new_for_size(size) else { return VerificationResult::InvalidProof(RejectionReason::MalformedProof("no domain of the claimed size")); };
+        let omega = domain.generator;
+        let r = P::challenge(&[f_com.clone(), self.t_com.clone(), self.q_com.clone()]);
+
+        if !self.end_eval.check(pcs, &self.t_com, domain.element(domain.size - 1)).is_valid() { return invalid_evaluation(Protocol::ProductCheck, "end_eval"); }
+        if !self.t_r.check(pcs, &self.t_com, r).is_valid() { return invalid_evaluation(Protocol::ProductCheck, "t_r"); }
+        if !self.t_wr.check(pcs, &self.t_com, omega * r).is_valid() { return invalid_evaluation(Protocol::ProductCheck, "t_wr"); }
+        if !self.q_r.check(pcs, &self.q_com, r).is_valid() { return invalid_evaluation(Protocol::ProductCheck, "q_r"); }
+        if !self.f_wr.check(pcs, f_com, omega * r).is_valid() { return invalid_evaluation(Protocol::ProductCheck, "f_wr"); }
 
-        let vp = F::ONE;
+        let vp = domain.evaluate_vanishing(r);
 
-        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof; }
+        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof(RejectionReason::ProductNotOne(Protocol::ProductCheck)); }
         let lhs = self.t_wr.evaluation() - (self.t_r.evaluation() * self.f_wr.evaluation());
         let rhs = self.q_r.evaluation() * vp;
-        if lhs != rhs { return VerificationResult::InvalidProof; }
+        if lhs != rhs { return VerificationResult::InvalidProof(RejectionReason::IdentityCheckFailed(Protocol::ProductCheck)); }
 
         VerificationResult::ValidProof
     }
 }
 
-impl<F: PrimeField> RationalProductCheckProof<F> {
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> RationalProductCheckProof<F, P> {
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        end_eval: Evaluation<F>,
-        t_r: Evaluation<F>,
-        t_wr: Evaluation<F>,
-        q_r: Evaluation<F>,
-        g_wr: Evaluation<F>,
-        f_wr: Evaluation<F>,
+        t_com: P::Commitment,
+        q_com: P::Commitment,
+        end_eval: Evaluation<F, P>,
+        t_r: Evaluation<F, P>,
+        t_wr: Evaluation<F, P>,
+        q_r: Evaluation<F, P>,
+        g_wr: Evaluation<F, P>,
+        f_wr: Evaluation<F, P>,
     ) -> Self {
         Self {
+            t_com,
+            q_com,
             end_eval,
             t_r,
             t_wr,
@@ -118,38 +148,45 @@ impl<F: PrimeField> RationalProductCheckProof<F> {
         }
     }
 
-    pub fn verify(&self) -> VerificationResult {
-        if !self.end_eval.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.q_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.g_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.f_wr.check().is_valid() { return VerificationResult::InvalidProof; }
+    pub fn verify(&self, pcs: &P, f_com: &P::Commitment, g_com: &P::Commitment, size: u64) -> VerificationResult {
+        let Ok(domain) = Domain::<F>::new_for_size(size) else { return VerificationResult::InvalidProof(RejectionReason::MalformedProof("no domain of the claimed size")); };
+        let omega = domain.generator;
+        let r = P::challenge(&[f_com.clone(), g_com.clone(), self.t_com.clone(), self.q_com.clone()]);
 
-        let vp = F::ONE;
+        if !self.end_eval.check(pcs, &self.t_com, domain.element(domain.size - 1)).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "end_eval"); }
+        if !self.t_r.check(pcs, &self.t_com, r).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "t_r"); }
+        if !self.t_wr.check(pcs, &self.t_com, omega * r).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "t_wr"); }
+        if !self.q_r.check(pcs, &self.q_com, r).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "q_r"); }
+        if !self.g_wr.check(pcs, g_com, omega * r).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "g_wr"); }
+        if !self.f_wr.check(pcs, f_com, omega * r).is_valid() { return invalid_evaluation(Protocol::RationalProductCheck, "f_wr"); }
 
-        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof; }
+        let vp = domain.evaluate_vanishing(r);
+
+        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof(RejectionReason::ProductNotOne(Protocol::RationalProductCheck)); }
         let lhs = (self.t_wr.evaluation() * self.g_wr.evaluation()) - (self.t_r.evaluation() * self.f_wr.evaluation());
         let rhs = self.q_r.evaluation() * vp;
-        if lhs != rhs { return VerificationResult::InvalidProof; }
+        if lhs != rhs { return VerificationResult::InvalidProof(RejectionReason::IdentityCheckFailed(Protocol::RationalProductCheck)); }
 
         VerificationResult::ValidProof
     }
 }
 
-impl<F: PrimeField> PermutationCheckProof<F> {
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> PermutationCheckProof<F, P> {
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        f_com: FriCommitment<F>,
-        end_eval: Evaluation<F>,
-        t_r: Evaluation<F>,
-        t_wr: Evaluation<F>,
-        q_r: Evaluation<F>,
-        g_wr: Evaluation<F>,
-        f_wr: Evaluation<F>,
+        t_com: P::Commitment,
+        q_com: P::Commitment,
+        end_eval: Evaluation<F, P>,
+        t_r: Evaluation<F, P>,
+        t_wr: Evaluation<F, P>,
+        q_r: Evaluation<F, P>,
+        g_wr: Evaluation<F, P>,
+        f_wr: Evaluation<F, P>,
     ) -> Self {
         Self {
-            f_com,
+            t_com,
+            q_com,
             end_eval,
             t_r,
             t_wr,
@@ -159,80 +196,89 @@ impl<F: PrimeField> PermutationCheckProof<F> {
         }
     }
 
-    pub fn verify(&self) -> VerificationResult {
+    pub fn verify(&self, pcs: &P, f_com: &P::Commitment, g_com: &P::Commitment, size: u64) -> VerificationResult {
+        let Ok(domain) = Domain::<F>::new_for_size(size) else { return VerificationResult::InvalidProof(RejectionReason::MalformedProof("no domain of the claimed size")); };
+        let omega = domain.generator;
+
+        // r is the root of f_hat and g_hat, s is the point the product check is queried at.
+        let r = P::challenge(&[f_com.clone(), g_com.clone()]);
+        let s = P::challenge(&[f_com.clone(), g_com.clone(), self.t_com.clone(), self.q_com.clone()]);
 
-        if !self.end_eval.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.q_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.g_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.f_wr.check().is_valid() { return VerificationResult::InvalidProof; }
+        if !self.end_eval.check(pcs, &self.t_com, domain.element(domain.size - 1)).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "end_eval"); }
+        if !self.t_r.check(pcs, &self.t_com, s).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "t_r"); }
+        if !self.t_wr.check(pcs, &self.t_com, omega * s).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "t_wr"); }
+        if !self.q_r.check(pcs, &self.q_com, s).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "q_r"); }
+        if !self.g_wr.check(pcs, g_com, omega * s).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "g_wr"); }
+        if !self.f_wr.check(pcs, f_com, omega * s).is_valid() { return invalid_evaluation(Protocol::PermutationCheck, "f_wr"); }
 
-        let vp = F::ONE;
-        let r = self.f_com.interpret_as_element();
+        let vp = domain.evaluate_vanishing(s);
 
-        // I might need to change the evaluation in the "protocols" file to w^r instead of wr.
         let g = r - self.g_wr.evaluation();
         let f = r - self.f_wr.evaluation();
 
-        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof; }
+        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof(RejectionReason::ProductNotOne(Protocol::PermutationCheck)); }
         let lhs = (self.t_wr.evaluation() * g) - (self.t_r.evaluation() * f);
         let rhs = self.q_r.evaluation() * vp;
-        if lhs != rhs { return VerificationResult::InvalidProof; }
+        if lhs != rhs { return VerificationResult::InvalidProof(RejectionReason::IdentityCheckFailed(Protocol::PermutationCheck)); }
 
         VerificationResult::ValidProof
     }
 }
 
-impl<F: PrimeField> PrescribedPermutationCheckProof<F> {
+impl<F: PrimeField, P: PolynomialCommitmentScheme<F>> PrescribedPermutationCheckProof<F, P> {
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        f_com: FriCommitment<F>, 
-        g_com: FriCommitment<F>,
-        end_eval: Evaluation<F>,
-        t_r: Evaluation<F>,
-        t_wr: Evaluation<F>,
-        q_r: Evaluation<F>,
-        f_wr: Evaluation<F>,
-        g_wr: Evaluation<F>,
-        w_wr: Evaluation<F>
+        t_com: P::Commitment,
+        q_com: P::Commitment,
+        end_eval: Evaluation<F, P>,
+        t_r: Evaluation<F, P>,
+        t_wr: Evaluation<F, P>,
+        q_r: Evaluation<F, P>,
+        g_wr: Evaluation<F, P>,
+        f_wr: Evaluation<F, P>,
+        w_wr: Evaluation<F, P>
     ) -> Self {
         Self {
-            f_com,
-            g_com,
+            t_com,
+            q_com,
             end_eval,
             t_r,
             t_wr,
             q_r,
-            f_wr,
             g_wr,
+            f_wr,
             w_wr,
         }
     }
 
-    pub fn verify(&self) -> VerificationResult {
+    pub fn verify(&self, pcs: &P, f_com: &P::Commitment, g_com: &P::Commitment, w_com: &P::Commitment, size: u64) -> VerificationResult {
+        let Ok(domain) = Domain::<F>::new_for_size(size) else { return VerificationResult::InvalidProof(RejectionReason::MalformedProof("no domain of the claimed size")); };
+        let omega = domain.generator;
+
+        // r and s combine the tuples (W(a), f(a)) and (a, g(a)), z is the point the product
+        // check is queried at. The commitments are hashed in a different order for each.
+        let r = P::challenge(&[f_com.clone(), g_com.clone(), w_com.clone()]);
+        let s = P::challenge(&[w_com.clone(), g_com.clone(), f_com.clone()]);
+        let z = P::challenge(&[f_com.clone(), g_com.clone(), w_com.clone(), self.t_com.clone(), self.q_com.clone()]);
 
-        if !self.end_eval.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.t_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.q_r.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.g_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.f_wr.check().is_valid() { return VerificationResult::InvalidProof; }
-        if !self.w_wr.check().is_valid() { return VerificationResult::InvalidProof; }
+        if !self.end_eval.check(pcs, &self.t_com, domain.element(domain.size - 1)).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "end_eval"); }
+        if !self.t_r.check(pcs, &self.t_com, z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "t_r"); }
+        if !self.t_wr.check(pcs, &self.t_com, omega * z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "t_wr"); }
+        if !self.q_r.check(pcs, &self.q_com, z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "q_r"); }
+        if !self.g_wr.check(pcs, g_com, omega * z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "g_wr"); }
+        if !self.f_wr.check(pcs, f_com, omega * z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "f_wr"); }
+        if !self.w_wr.check(pcs, w_com, omega * z).is_valid() { return invalid_evaluation(Protocol::PrescribedPermutationCheck, "w_wr"); }
 
-        let vp = F::ONE;
-        let r = self.f_com.interpret_as_element();
-        let s = self.g_com.interpret_as_element();
-        let a = F::ONE;
+        let vp = domain.evaluate_vanishing(z);
 
-        // I might need to change the evaluation in the "protocols" file to w^r instead of wr.
         let f = r - (s * self.w_wr.evaluation()) - self.f_wr.evaluation();
-        let g = r - (s * a) - self.g_wr.evaluation();
+        let g = r - (s * omega * z) - self.g_wr.evaluation();
 
-        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof; }
+        if self.end_eval.evaluation() != F::ONE { return VerificationResult::InvalidProof(RejectionReason::ProductNotOne(Protocol::PrescribedPermutationCheck)); }
         let lhs = (self.t_wr.evaluation() * g) - (self.t_r.evaluation() * f);
         let rhs = self.q_r.evaluation() * vp;
-        if lhs != rhs { return VerificationResult::InvalidProof; }
+        if lhs != rhs { return VerificationResult::InvalidProof(RejectionReason::IdentityCheckFailed(Protocol::PrescribedPermutationCheck)); }
 
         VerificationResult::ValidProof
     }
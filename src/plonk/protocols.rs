@@ -1,7 +1,6 @@
 use ff::PrimeField;
 
 use crate::{
-    FriProof,
     plonk::{
         ZeroTestProof, 
         ProductCheckProof, 
@@ -10,13 +9,126 @@ use crate::{
         PermutationCheckProof,
         PrescribedPermutationCheckProof,
     },
-    polynomial::Polynomial,
-    domains::Domain,
-    fft::serial,
+    pcs::PolynomialCommitmentScheme,
+    polynomial::{Polynomial, Evaluations},
+    domains::{Domain, SynthesisError},
+    utils::batch_inverse,
+    field::packed::{self, Lanewise, PackedField, PackedKernel},
 };
 
+// Interpolates the accumulator t(x) of a product check over the roots of unity, i.e. t(w^i) =
+// f(1)/g(1) * f(w)/g(w) * ... * f(w^i)/g(w^i), from the evaluations of f and g. Without g, the
+// denominator is 1. The denominators are inverted together, and a zero among them is an error.
+fn accumulator<F: PrimeField>(numerator: &Evaluations<F>, denominator: Option<&Evaluations<F>>) -> Result<Polynomial<F>, SynthesisError> {
+    let quotient = match denominator {
+        Some(d) => numerator.checked_div(d)?,
+        None => numerator.clone(),
+    };
+    let mut target = F::ONE;
+    let t = quotient.evaluations().iter().map(|q| {
+        target *= q;
+        target
+    }).collect();
+
+    Ok(Evaluations::from_vec_and_domain(t, *quotient.domain())?.interpolate())
+}
+
+// q(x) = (t(wx)g(wx) - t(x)f(wx))/(x^n - 1). The numerator vanishes on the n-th roots of unity
+// (and the division is exact) if and only if t(x) was built honestly from f(x) and g(x).
+//
+// The numerator has degree below 2n, so it is computed from evaluations over the coset c * H_2n,
+// for c the multiplicative generator and H_2n the subgroup of size 2n. There x^n - 1 never
+// vanishes and only takes the two values c^n - 1 and -c^n - 1, alternating between consecutive
+// elements, so the division is a pointwise product. Only the n lowest coefficients of the result
+// are kept, which is all of q(x) for an honest t(x).
+fn accumulator_quotient<F: PrimeField>(t_x: &Polynomial<F>, f_x: &Polynomial<F>, g_x: Option<&Polynomial<F>>, domain: &Domain<F>) -> Result<Polynomial<F>, SynthesisError> {
+    let omega = domain.generator;
+    let coset = domain.superdomain(2)?.with_offset(F::MULTIPLICATIVE_GENERATOR)?;
+    let evaluate = |p: &Polynomial<F>| p.evaluate_over_domain(&coset);
+
+    let mut lhs = evaluate(&t_x.scale_argument(omega));
+    if let Some(g) = g_x {
+        lhs = (lhs * evaluate(&g.scale_argument(omega)))?;
+    }
+    let rhs = (evaluate(t_x) * evaluate(&f_x.scale_argument(omega)))?;
+
+    let vanishing = [domain.evaluate_vanishing(coset.element(0)), domain.evaluate_vanishing(coset.element(1))];
+    let vanishing_inverses = batch_inverse(&vanishing).expect("The coset is disjoint from the subgroup");
+    let vanishing_inverses = Evaluations::from_vec_and_domain((0..coset.size).map(|i| vanishing_inverses[i as usize % 2]).collect(), coset)?;
+
+    let mut q_x = ((lhs - rhs)? * vanishing_inverses)?.interpolate();
+    q_x.truncate(domain.size as usize);
+    Ok(q_x)
+}
+
+// out_i = r - s * x_i - y_i, or r - y_i without x, on packed Goldilocks when F is Goldilocks
+struct Offsets<'a, F: PrimeField> {
+    out: &'a mut [F],
+    inputs: OffsetInputs<'a, F>,
+}
+
+struct OffsetInputs<'a, F: PrimeField> {
+    r: F,
+    s: F,
+    x: Option<&'a [F]>,
+    y: &'a [F],
+}
+
+impl<F: PrimeField> Lanewise<F> for OffsetInputs<'_, F> {
+    #[inline(always)]
+    fn apply<P: PackedField<Scalar = F>>(&self, out: &mut [F], start: usize) {
+        let (r, s) = (P::broadcast(self.r), P::broadcast(self.s));
+        for (i, out) in out.chunks_exact_mut(P::WIDTH).enumerate() {
+            let j = start + i * P::WIDTH;
+            let offset = match self.x {
+                Some(x) => r - s * P::load(&x[j..]),
+                None => r,
+            };
+            (offset - P::load(&self.y[j..])).store(out);
+        }
+    }
+}
+
+impl<F: PrimeField> PackedKernel<F> for Offsets<'_, F> {
+    type Output = ();
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = F>>(self) {
+        packed::lanewise::<F, P, _>(self.out, &self.inputs);
+    }
+}
+
+fn offsets<F: PrimeField>(r: F, s: F, x: Option<&[F]>, y: &Evaluations<F>) -> Evaluations<F> {
+    let domain = *y.domain();
+    let y = y.evaluations();
+    let mut out = vec![F::ZERO; y.len()];
+    let kernel = Offsets { out: &mut out, inputs: OffsetInputs { r, s, x, y } };
+    match (packed::as_goldilocks_mut(kernel.out), packed::as_goldilocks_scalar(&r), packed::as_goldilocks_scalar(&s)) {
+        (Some(out), Some(r), Some(s)) => packed::dispatch(Offsets {
+            out,
+            inputs: OffsetInputs {
+                r,
+                s,
+                x: x.and_then(packed::as_goldilocks),
+                y: packed::as_goldilocks(y).unwrap(),
+            },
+        }),
+        _ => kernel.run::<F>(),
+    }
+    Evaluations::from_vec_and_domain(out, domain).expect("One offset per evaluation")
+}
+
 impl<F: PrimeField> Polynomial<F> {
 
+    // The domain the product and permutation checks run over, the subgroup of size self.len()
+    // padded to a power of two. The other polynomials of the check have to have the same length.
+    fn check_domain(&self, others: &[&Polynomial<F>]) -> Result<Domain<F>, SynthesisError> {
+        if let Some(other) = others.iter().find(|p| p.len() != self.len()) {
+            return Err(SynthesisError::LengthMismatch(self.len(), other.len()));
+        }
+        Domain::new_for_size(self.len().next_power_of_two() as u64)
+    }
+
     // <------------------------------------------------------------------------------------------->
     // For the zero test, a prover wants to convince the verifier that they have knowledge of some
     // polynomial p(x) which is zero for some subset omega in F_p (in practice, the subset is some 
@@ -26,23 +138,18 @@ impl<F: PrimeField> Polynomial<F> {
     // prover queries q(r) and p(r) and sends field elements to prover. KZG is just checking q(r)Z(r)
     // = p(r), FRI checks Merkle authentication paths, etc.
     // <------------------------------------------------------------------------------------------->
-    pub fn zero_test(&self, z: &Polynomial<F>) -> ZeroTestProof<F> {
-        let (q_x, _) = self.long_division(z);
-        
-        let r: F = q_x
-            .commitment()
-            .interpret_as_element();
-
-        let f_eval = self.eval_single(&r);
-        let q_eval = q_x.eval_single(&r);
+    pub fn zero_test<P: PolynomialCommitmentScheme<F>>(&self, pcs: &P, z: &Polynomial<F>) -> Result<ZeroTestProof<F, P>, SynthesisError> {
+        let (q_x, _) = self.long_division(z)?;
 
-        let f_eval_proof = FriProof::evaluation_proof(self, Some(r));
-        let q_eval_proof = FriProof::evaluation_proof(&q_x, Some(r));
+        let f_com = pcs.commit(self)?;
+        let q_com = pcs.commit(&q_x)?;
+        let r = P::challenge(&[f_com, q_com.clone()]);
 
-        ZeroTestProof::new(
-            Evaluation::new(f_eval, f_eval_proof),
-            Evaluation::new(q_eval, q_eval_proof)
-        )
+        Ok(ZeroTestProof::new(
+            q_com,
+            Evaluation::open(pcs, self, r)?,
+            Evaluation::open(pcs, &q_x, r)?
+        ))
 
     }
 
@@ -62,59 +169,29 @@ impl<F: PrimeField> Polynomial<F> {
     // t(x) at w^k-1, r, and w, and q(x) at r and f(x) at wr. The verifier will accept if and only if
     // t(w^k-1) == 1, t(wr) - t(r)f(wr) == q(r)(r^k - 1), and all the commitment checks are valid.
     // <------------------------------------------------------------------------------------------->
-    pub fn product_check(&self) -> ProductCheckProof<F> {
-
-        let mut evaluations = self.coefficients();
-
-        let size = self.len().next_power_of_two();
-        let log_n = {
-            let mut x = 0u32;
-            let mut y = size;
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
-
-        let omega = Domain::root_with_order_unchecked(size as u64);
-        serial::serial_fft(evaluations.as_mut_slice(), &omega, log_n);
-
-        let mut t_x = vec![F::ZERO; size];
-        let mut target = F::ONE;
-        for i in 0..size {
-            target *= evaluations[i];
-            t_x[i] = target;
-        }
-
-        let t_end = t_x.last().unwrap().clone();
-
-        serial::serial_ifft(t_x.as_mut_slice(), &omega, log_n);
-        let t_x = Polynomial::from_vec(t_x);
-        let t_end_proof = FriProof::evaluation_proof(&t_x, Some(omega.pow([size as u64 - 1])));
-
-        let r = t_x.commitment().interpret_as_element();
-
-        let t_r = t_x.eval_single(&r);
-        let t_r_proof = FriProof::evaluation_proof(&t_x, Some(r));
-        let t_wr = t_x.eval_single(&(omega * r));
-        let t_wr_proof = FriProof::evaluation_proof(&t_x, Some(omega * r));
-
-        let vanishing = Polynomial::vanishing_polynomial(size as u128);
-        let (q_x, _) = t_x.long_division(&vanishing);
-        let q_r = q_x.eval_single(&r);
-        let q_r_proof = FriProof::evaluation_proof(&q_x, Some(r));
-
-        let f_wr = self.eval_single(&(omega * r));
-        let f_wr_proof = FriProof::evaluation_proof(self, Some(omega * r));
-
-        ProductCheckProof::new(
-            Evaluation::new(t_end, t_end_proof),
-            Evaluation::new(t_r, t_r_proof),
-            Evaluation::new(t_wr, t_wr_proof),
-            Evaluation::new(q_r, q_r_proof),
-            Evaluation::new(f_wr, f_wr_proof)
-        )
+    pub fn product_check<P: PolynomialCommitmentScheme<F>>(&self, pcs: &P) -> Result<ProductCheckProof<F, P>, SynthesisError> {
+
+        let domain = self.check_domain(&[])?;
+        let omega = domain.generator;
+        let evaluations = self.evaluate_over_domain(&domain);
+
+        let t_x = accumulator(&evaluations, None)?;
+        let q_x = accumulator_quotient(&t_x, self, None, &domain)?;
+
+        let f_com = pcs.commit(self)?;
+        let t_com = pcs.commit(&t_x)?;
+        let q_com = pcs.commit(&q_x)?;
+        let r = P::challenge(&[f_com, t_com.clone(), q_com.clone()]);
+
+        Ok(ProductCheckProof::new(
+            t_com,
+            q_com,
+            Evaluation::open(pcs, &t_x, domain.element(domain.size - 1))?,
+            Evaluation::open(pcs, &t_x, r)?,
+            Evaluation::open(pcs, &t_x, omega * r)?,
+            Evaluation::open(pcs, &q_x, r)?,
+            Evaluation::open(pcs, self, omega * r)?
+        ))
     }
 
     // <------------------------------------------------------------------------------------------->
@@ -125,64 +202,32 @@ impl<F: PrimeField> Polynomial<F> {
     // = f(w^2)/g(w^2), ... , t(w^k-1) = f(w^k-1)/g(w^k-1). Like last time, if constructed honestly,
     // then t(w^k-1) = 1 and t(wx) * g(wx) = t(x) * f(wx) for all x in the subset omega.
     // <------------------------------------------------------------------------------------------->
-    pub fn product_check_rational(&self, denominator: &Polynomial<F>) -> RationalProductCheckProof<F> {
-
-        let mut numerator_evaluations = self.coefficients();
-        let mut denominator_evaluations = denominator.coefficients();
-
-        let size = self.len().next_power_of_two();
-        let log_n = {
-            let mut x = 0u32;
-            let mut y = size;
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
-
-        let omega = Domain::root_with_order_unchecked(size as u64);
-        serial::serial_fft(numerator_evaluations.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(denominator_evaluations.as_mut_slice(), &omega, log_n);
-
-        let mut t_x = vec![F::ZERO; size];
-        let mut target = F::ONE;
-        for i in 0..size {
-            target *= numerator_evaluations[i] * denominator_evaluations[i].invert().unwrap();
-            t_x[i] = target;
-        }
-
-        let t_end = t_x.last().unwrap().clone();
-
-        serial::serial_ifft(t_x.as_mut_slice(), &omega, log_n);
-        let t_x = Polynomial::from_vec(t_x);
-        let t_end_proof = FriProof::evaluation_proof(&t_x, Some(omega.pow([size as u64 - 1])));
-
-        let r = t_x.commitment().interpret_as_element();
-
-        let t_r = t_x.eval_single(&r);
-        let t_r_proof = FriProof::evaluation_proof(&t_x, Some(r));
-        let t_wr = t_x.eval_single(&(omega * r));
-        let t_wr_proof = FriProof::evaluation_proof(&t_x, Some(omega * r));
-
-        let vanishing = Polynomial::vanishing_polynomial(size as u128);
-        let (q_x, _) = t_x.long_division(&vanishing);
-        let q_r = q_x.eval_single(&r);
-        let q_r_proof = FriProof::evaluation_proof(&q_x, Some(r));
-
-        let g_wr = denominator.eval_single(&(omega * r));
-        let g_wr_proof = FriProof::evaluation_proof(denominator, Some(omega * r));
-        let f_wr = self.eval_single(&(omega * r));
-        let f_wr_proof = FriProof::evaluation_proof(self, Some(omega * r));
-
-        RationalProductCheckProof::new(
-            Evaluation::new(t_end, t_end_proof),
-            Evaluation::new(t_r, t_r_proof),
-            Evaluation::new(t_wr, t_wr_proof),
-            Evaluation::new(q_r, q_r_proof),
-            Evaluation::new(g_wr, g_wr_proof),
-            Evaluation::new(f_wr, f_wr_proof)
-        )
+    pub fn product_check_rational<P: PolynomialCommitmentScheme<F>>(&self, pcs: &P, denominator: &Polynomial<F>) -> Result<RationalProductCheckProof<F, P>, SynthesisError> {
+
+        let domain = self.check_domain(&[denominator])?;
+        let omega = domain.generator;
+        let numerator_evaluations = self.evaluate_over_domain(&domain);
+        let denominator_evaluations = denominator.evaluate_over_domain(&domain);
+
+        let t_x = accumulator(&numerator_evaluations, Some(&denominator_evaluations))?;
+        let q_x = accumulator_quotient(&t_x, self, Some(denominator), &domain)?;
+
+        let f_com = pcs.commit(self)?;
+        let g_com = pcs.commit(denominator)?;
+        let t_com = pcs.commit(&t_x)?;
+        let q_com = pcs.commit(&q_x)?;
+        let r = P::challenge(&[f_com, g_com, t_com.clone(), q_com.clone()]);
+
+        Ok(RationalProductCheckProof::new(
+            t_com,
+            q_com,
+            Evaluation::open(pcs, &t_x, domain.element(domain.size - 1))?,
+            Evaluation::open(pcs, &t_x, r)?,
+            Evaluation::open(pcs, &t_x, omega * r)?,
+            Evaluation::open(pcs, &q_x, r)?,
+            Evaluation::open(pcs, denominator, omega * r)?,
+            Evaluation::open(pcs, self, omega * r)?
+        ))
 
     }
 
@@ -197,68 +242,42 @@ impl<F: PrimeField> Polynomial<F> {
     // permutations of each other. Now, the prover and the verifier can engage in the product check
     // protocol and prove that f_hat(x)/g_hat(x) = 1 for all x in omega.
     // <------------------------------------------------------------------------------------------->
-    pub fn permutation_check(&self, permutation: &Polynomial<F>) -> PermutationCheckProof<F> {
-
-        let f_commitment = self.commitment();
-        let r = f_commitment.interpret_as_element();
-
-        let mut f_evals = self.coefficients();
-        let mut g_evals = permutation.coefficients();
-
-        let size = self.len().next_power_of_two();
-        let log_n = {
-            let mut x = 0u32;
-            let mut y = size;
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
-
-        let omega = Domain::root_with_order_unchecked(size as u64);
-        serial::serial_fft(f_evals.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(g_evals.as_mut_slice(), &omega, log_n);
-
-        let mut t_x = vec![F::ZERO; size];
-        let mut target = F::ONE;
-        for i in 0..size {
-            target *= (r - f_evals[i]) * (r - g_evals[i]).invert().unwrap();
-            t_x[i] = target;
-        }
-
-        let t_end = t_x.last().unwrap().clone();
-
-        serial::serial_ifft(t_x.as_mut_slice(), &omega, log_n);
-        let t_x = Polynomial::from_vec(t_x);
-        
-        let t_end_proof = FriProof::evaluation_proof(&t_x, Some(omega.pow([size as u64 - 1])));
-
-        let t_r = t_x.eval_single(&r);
-        let t_r_proof = FriProof::evaluation_proof(&t_x, Some(r));
-        let t_wr = t_x.eval_single(&(omega * r));
-        let t_wr_proof = FriProof::evaluation_proof(&t_x, Some(omega * r));
-
-        let vanishing = Polynomial::vanishing_polynomial(size as u128);
-        let (q_x, _) = t_x.long_division(&vanishing);
-        let q_r = q_x.eval_single(&r);
-        let q_r_proof = FriProof::evaluation_proof(&q_x, Some(r));
-
-        let g_wr = permutation.eval_single(&(r * omega));
-        let g_wr_proof = FriProof::evaluation_proof(permutation, Some(omega * r));
-
-        let f_wr = self.eval_single(&(r * omega));
-        let f_wr_proof = FriProof::evaluation_proof(self, Some(omega * r));
-
-        PermutationCheckProof::new(
-            f_commitment,
-            Evaluation::new(t_end, t_end_proof),
-            Evaluation::new(t_r, t_r_proof),
-            Evaluation::new(t_wr, t_wr_proof),
-            Evaluation::new(q_r, q_r_proof),
-            Evaluation::new(g_wr, g_wr_proof),
-            Evaluation::new(f_wr, f_wr_proof)
-        )
+    pub fn permutation_check<P: PolynomialCommitmentScheme<F>>(&self, pcs: &P, permutation: &Polynomial<F>) -> Result<PermutationCheckProof<F, P>, SynthesisError> {
+
+        let f_com = pcs.commit(self)?;
+        let g_com = pcs.commit(permutation)?;
+        let r = P::challenge(&[f_com.clone(), g_com.clone()]);
+
+        let domain = self.check_domain(&[permutation])?;
+        let omega = domain.generator;
+        let f_evals = self.evaluate_over_domain(&domain);
+        let g_evals = permutation.evaluate_over_domain(&domain);
+
+        // f_hat(x) = r - f(x) and g_hat(x) = r - g(x)
+        let f_hat_evals = offsets(r, F::ZERO, None, &f_evals);
+        let g_hat_evals = offsets(r, F::ZERO, None, &g_evals);
+        let mut f_hat = self.coefficients().iter().map(|c| -*c).collect::<Vec<F>>();
+        let mut g_hat = permutation.coefficients().iter().map(|c| -*c).collect::<Vec<F>>();
+        f_hat[0] += r;
+        g_hat[0] += r;
+
+        let t_x = accumulator(&f_hat_evals, Some(&g_hat_evals))?;
+        let q_x = accumulator_quotient(&t_x, &Polynomial::from_vec(f_hat), Some(&Polynomial::from_vec(g_hat)), &domain)?;
+
+        let t_com = pcs.commit(&t_x)?;
+        let q_com = pcs.commit(&q_x)?;
+        let s = P::challenge(&[f_com, g_com, t_com.clone(), q_com.clone()]);
+
+        Ok(PermutationCheckProof::new(
+            t_com,
+            q_com,
+            Evaluation::open(pcs, &t_x, domain.element(domain.size - 1))?,
+            Evaluation::open(pcs, &t_x, s)?,
+            Evaluation::open(pcs, &t_x, omega * s)?,
+            Evaluation::open(pcs, &q_x, s)?,
+            Evaluation::open(pcs, permutation, omega * s)?,
+            Evaluation::open(pcs, self, omega * s)?
+        ))
         
     }
 
@@ -288,78 +307,236 @@ impl<F: PrimeField> Polynomial<F> {
     // permutation of g(x) with high probability, since both polynomials are equal at this random
     // point.
     // <------------------------------------------------------------------------------------------->
-    pub fn prescribed_permutation_check(&self, permutation: &Polynomial<F>, rules: &Polynomial<F>) -> PrescribedPermutationCheckProof<F> {
-
-        let f_commitment = self.commitment();
-        let g_commitment = permutation.commitment();
-        let r = f_commitment.interpret_as_element();
-        let s = g_commitment.interpret_as_element();
-
-        let mut f_evals = self.coefficients();
-        let mut g_evals = permutation.coefficients();
-        let mut rules_evals = rules.coefficients();
-
-        let size = self.len().next_power_of_two();
-        let log_n = {
-            let mut x = 0u32;
-            let mut y = size;
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            x
-        };
-
-        let omega = Domain::root_with_order_unchecked(size as u64);
-        serial::serial_fft(f_evals.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(g_evals.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(rules_evals.as_mut_slice(), &omega, log_n);
-
-        let mut t_x = vec![F::ZERO; size];
-        let mut target = F::ONE;
-        let mut g = F::ONE;
-        for i in 0..size {
-            target *= (r - (s * rules_evals[i]) - f_evals[i]) * (r - (s * g) - g_evals[i]).invert().unwrap();
-            g *= omega;
-            t_x[i] = target;
+    pub fn prescribed_permutation_check<P: PolynomialCommitmentScheme<F>>(&self, pcs: &P, permutation: &Polynomial<F>, rules: &Polynomial<F>) -> Result<PrescribedPermutationCheckProof<F, P>, SynthesisError> {
+
+        let f_com = pcs.commit(self)?;
+        let g_com = pcs.commit(permutation)?;
+        let w_com = pcs.commit(rules)?;
+        let r = P::challenge(&[f_com.clone(), g_com.clone(), w_com.clone()]);
+        let s = P::challenge(&[w_com.clone(), g_com.clone(), f_com.clone()]);
+
+        let domain = self.check_domain(&[permutation, rules])?;
+        let omega = domain.generator;
+        let f_evals = self.evaluate_over_domain(&domain);
+        let g_evals = permutation.evaluate_over_domain(&domain);
+        let rules_evals = rules.evaluate_over_domain(&domain);
+
+        // f_hat(x) = r - s * W(x) - f(x) and g_hat(x) = r - s * x - g(x)
+        let elements = domain.elements().collect::<Vec<F>>();
+        let f_hat_evals = offsets(r, s, Some(rules_evals.evaluations()), &f_evals);
+        let g_hat_evals = offsets(r, s, Some(&elements), &g_evals);
+        let mut f_hat = self.coefficients().iter().zip(rules.coefficients()).map(|(c, w)| -*c - (s * w)).collect::<Vec<F>>();
+        let mut g_hat = permutation.coefficients().iter().map(|c| -*c).collect::<Vec<F>>();
+        // g_hat has a linear term even when g(x) is a constant
+        if g_hat.len() < 2 {
+            g_hat.resize(2, F::ZERO);
         }
+        f_hat[0] += r;
+        g_hat[0] += r;
+        g_hat[1] -= s;
+
+        let t_x = accumulator(&f_hat_evals, Some(&g_hat_evals))?;
+        let q_x = accumulator_quotient(&t_x, &Polynomial::from_vec(f_hat), Some(&Polynomial::from_vec(g_hat)), &domain)?;
+
+        let t_com = pcs.commit(&t_x)?;
+        let q_com = pcs.commit(&q_x)?;
+        let z = P::challenge(&[f_com, g_com, w_com, t_com.clone(), q_com.clone()]);
+
+        Ok(PrescribedPermutationCheckProof::new(
+            t_com,
+            q_com,
+            Evaluation::open(pcs, &t_x, domain.element(domain.size - 1))?,
+            Evaluation::open(pcs, &t_x, z)?,
+            Evaluation::open(pcs, &t_x, omega * z)?,
+            Evaluation::open(pcs, &q_x, z)?,
+            Evaluation::open(pcs, permutation, omega * z)?,
+            Evaluation::open(pcs, self, omega * z)?,
+            Evaluation::open(pcs, rules, omega * z)?
+        ))
+    }
+}
 
-        let t_end = t_x.last().unwrap().clone();
+#[cfg(test)]
+mod protocols_tests {
 
-        serial::serial_ifft(t_x.as_mut_slice(), &omega, log_n);
-        let t_x = Polynomial::from_vec(t_x);
-        
-        let t_end_proof = FriProof::evaluation_proof(&t_x, Some(omega.pow([size as u64 - 1])));
-
-        let t_r = t_x.eval_single(&r);
-        let t_r_proof = FriProof::evaluation_proof(&t_x, Some(r));
-        let t_wr = t_x.eval_single(&(omega * r));
-        let t_wr_proof = FriProof::evaluation_proof(&t_x, Some(omega * r));
-
-        let vanishing = Polynomial::vanishing_polynomial(size as u128);
-        let (q_x, _) = t_x.long_division(&vanishing);
-        let q_r = q_x.eval_single(&r);
-        let q_r_proof = FriProof::evaluation_proof(&q_x, Some(r));
-
-        let g_wr = permutation.eval_single(&(r * omega));
-        let g_wr_proof = FriProof::evaluation_proof(permutation, Some(omega * r));
-
-        let f_wr = self.eval_single(&(r * omega));
-        let f_wr_proof = FriProof::evaluation_proof(self, Some(omega * r));
-
-        let w_wr = rules.eval_single(&(r * omega));
-        let w_wr_proof = FriProof::evaluation_proof(rules, Some(omega * r));
-
-        PrescribedPermutationCheckProof::new(
-            f_commitment,
-            g_commitment,
-            Evaluation::new(t_end, t_end_proof),
-            Evaluation::new(t_r, t_r_proof),
-            Evaluation::new(t_wr, t_wr_proof),
-            Evaluation::new(q_r, q_r_proof),
-            Evaluation::new(g_wr, g_wr_proof),
-            Evaluation::new(f_wr, f_wr_proof),
-            Evaluation::new(w_wr, w_wr_proof)
-        )
+    use super::*;
+    use crate::{
+        field::goldilocks::Goldilocks,
+        pcs::trivial::TrivialPcs,
+        fri::FriPcs,
+        error::RejectionReason,
+        plonk::Protocol,
+        domains::Domain,
+    };
+
+    use ff::Field;
+
+    const SIZE: usize = 8;
+
+    fn interpolate(evaluations: Vec<Goldilocks>) -> Polynomial<Goldilocks> {
+        let domain = Domain::new_for_size(evaluations.len() as u64).unwrap();
+        Evaluations::from_vec_and_domain(evaluations, domain).unwrap().interpolate()
+    }
+
+    fn random_evaluations() -> Vec<Goldilocks> {
+        let rng = rand::thread_rng();
+        (0..SIZE).map(|_| Goldilocks::random(rng.clone())).collect()
+    }
+
+    #[test]
+    fn zero_test() {
+        let pcs = TrivialPcs;
+        let z = Polynomial::vanishing_polynomial(4);
+        let f_x = z.clone() * interpolate(random_evaluations());
+
+        let proof = f_x.zero_test(&pcs, &z).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &z).is_valid());
+
+        let g_x = f_x.clone() + Polynomial::from_vec(vec![Goldilocks::ONE]);
+        let proof = g_x.zero_test(&pcs, &z).unwrap();
+        let result = proof.verify(&pcs, &pcs.commit(&g_x).unwrap(), &z);
+        assert_eq!(result.rejection_reason(), Some(RejectionReason::IdentityCheckFailed(Protocol::ZeroTest)));
+
+        let zero = Polynomial::from_vec(vec![Goldilocks::ZERO; 4]);
+        assert!(matches!(f_x.zero_test(&pcs, &zero), Err(SynthesisError::DivisionByZero)));
+    }
+
+    #[test]
+    fn product_check() {
+        let pcs = TrivialPcs;
+        let mut evaluations = random_evaluations();
+        let product = evaluations[..SIZE-1].iter().fold(Goldilocks::ONE, |acc, e| acc * e);
+        evaluations[SIZE-1] = product.invert().unwrap();
+        let f_x = interpolate(evaluations.clone());
+
+        let proof = f_x.product_check(&pcs).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), SIZE as u64).is_valid());
+
+        evaluations[0] += Goldilocks::ONE;
+        let g_x = interpolate(evaluations);
+        let proof = g_x.product_check(&pcs).unwrap();
+        let result = proof.verify(&pcs, &pcs.commit(&g_x).unwrap(), SIZE as u64);
+        assert_eq!(result.rejection_reason(), Some(RejectionReason::ProductNotOne(Protocol::ProductCheck)));
+        assert_eq!(result.into_result(), Err(SynthesisError::VerificationFailed(RejectionReason::ProductNotOne(Protocol::ProductCheck))));
+    }
+
+    #[test]
+    fn product_check_rational() {
+        let pcs = TrivialPcs;
+        let numerator = random_evaluations();
+        let mut denominator = numerator.clone();
+        denominator.rotate_left(3);
+        let f_x = interpolate(numerator);
+        let g_x = interpolate(denominator.clone());
+
+        let proof = f_x.product_check_rational(&pcs, &g_x).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&g_x).unwrap(), SIZE as u64).is_valid());
+
+        denominator[2] += Goldilocks::ONE;
+        let h_x = interpolate(denominator.clone());
+        let proof = f_x.product_check_rational(&pcs, &h_x).unwrap();
+        assert!(!proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&h_x).unwrap(), SIZE as u64).is_valid());
+
+        // A denominator vanishing on the domain is rejected instead of panicking
+        denominator[5] = Goldilocks::ZERO;
+        let z_x = interpolate(denominator);
+        assert!(matches!(f_x.product_check_rational(&pcs, &z_x), Err(SynthesisError::DivisionByZero)));
+    }
+
+    #[test]
+    fn malformed_inputs() {
+        let pcs = TrivialPcs;
+        let f_x = interpolate(random_evaluations());
+        let odd = Polynomial::from_vec(vec![Goldilocks::ONE; 6]);
+        let short = Polynomial::from_vec(vec![Goldilocks::ONE; 4]);
+
+        assert!(matches!(f_x.permutation_check(&pcs, &short), Err(SynthesisError::LengthMismatch(8, 4))));
+        assert!(matches!(f_x.prescribed_permutation_check(&pcs, &f_x, &odd), Err(SynthesisError::LengthMismatch(8, 6))));
+
+        // Six coefficients are padded to the subgroup of size 8, over which c * x^5 multiplies to
+        // c^8 * (-1)^5 = 1 for c of order 16
+        let c = Domain::<Goldilocks>::new_for_size(16).unwrap().generator;
+        let mut coefficients = vec![Goldilocks::ZERO; 6];
+        coefficients[5] = c;
+        let padded = Polynomial::from_vec(coefficients);
+        let proof = padded.product_check(&pcs).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&padded).unwrap(), 8).is_valid());
+        let proof = odd.product_check(&pcs).unwrap();
+        assert_eq!(proof.verify(&pcs, &pcs.commit(&odd).unwrap(), 8).rejection_reason(), Some(RejectionReason::ProductNotOne(Protocol::ProductCheck)));
+    }
+
+    #[test]
+    fn permutation_check() {
+        let pcs = TrivialPcs;
+        let evaluations = random_evaluations();
+        let mut permuted = evaluations.clone();
+        permuted.swap(1, 6);
+        permuted.swap(2, 3);
+        let f_x = interpolate(evaluations);
+        let g_x = interpolate(permuted.clone());
+
+        let proof = f_x.permutation_check(&pcs, &g_x).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&g_x).unwrap(), SIZE as u64).is_valid());
+
+        permuted[0] = permuted[1];
+        let h_x = interpolate(permuted);
+        let proof = f_x.permutation_check(&pcs, &h_x).unwrap();
+        assert!(!proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&h_x).unwrap(), SIZE as u64).is_valid());
+    }
+
+    #[test]
+    fn prescribed_permutation_check() {
+        let pcs = TrivialPcs;
+        let omega: Goldilocks = Domain::root_with_order_unchecked(SIZE as u64);
+
+        // W(w^i) = w^(3i + 1), and g is chosen such that f(y) = g(W(y))
+        let rule = |i: usize| (3 * i + 1) % SIZE;
+        let f_evals = random_evaluations();
+        let mut g_evals = vec![Goldilocks::ZERO; SIZE];
+        let mut w_evals = vec![Goldilocks::ZERO; SIZE];
+        for i in 0..SIZE {
+            g_evals[rule(i)] = f_evals[i];
+            w_evals[i] = omega.pow([rule(i) as u64]);
+        }
+        let f_x = interpolate(f_evals);
+        let g_x = interpolate(g_evals.clone());
+        let w_x = interpolate(w_evals);
+
+        let proof = f_x.prescribed_permutation_check(&pcs, &g_x, &w_x).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&g_x).unwrap(), &pcs.commit(&w_x).unwrap(), SIZE as u64).is_valid());
+
+        // Still a permutation of f, but not the prescribed one
+        g_evals.swap(0, 1);
+        let h_x = interpolate(g_evals);
+        let proof = f_x.prescribed_permutation_check(&pcs, &h_x, &w_x).unwrap();
+        assert!(!proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&h_x).unwrap(), &pcs.commit(&w_x).unwrap(), SIZE as u64).is_valid());
+    }
+
+    #[test]
+    fn prescribed_permutation_check_of_constants() {
+        // Over a domain of size one W is the identity and g is f
+        let pcs = TrivialPcs;
+        let f_x = Polynomial::from_vec(vec![Goldilocks::from(9)]);
+        let w_x = Polynomial::from_vec(vec![Goldilocks::ONE]);
+
+        let proof = f_x.prescribed_permutation_check(&pcs, &f_x, &w_x).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&f_x).unwrap(), &pcs.commit(&w_x).unwrap(), 1).is_valid());
+
+        let g_x = Polynomial::from_vec(vec![Goldilocks::from(10)]);
+        let proof = f_x.prescribed_permutation_check(&pcs, &g_x, &w_x).unwrap();
+        assert!(!proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), &pcs.commit(&g_x).unwrap(), &pcs.commit(&w_x).unwrap(), 1).is_valid());
+    }
+
+    #[test]
+    fn product_check_with_fri() {
+        let pcs = FriPcs;
+        let mut evaluations = random_evaluations();
+        let product = evaluations[..SIZE-1].iter().fold(Goldilocks::ONE, |acc, e| acc * e);
+        evaluations[SIZE-1] = product.invert().unwrap();
+        let f_x = interpolate(evaluations);
+
+        let proof = f_x.product_check(&pcs).unwrap();
+        assert!(proof.verify(&pcs, &pcs.commit(&f_x).unwrap(), SIZE as u64).is_valid());
+        assert!(!proof.verify(&pcs, &pcs.commit(&f_x.scale_argument(Goldilocks::from(2))).unwrap(), SIZE as u64).is_valid());
     }
 }
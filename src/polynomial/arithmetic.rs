@@ -2,8 +2,9 @@ use ff::PrimeField;
 
 use crate::{
     polynomial::Polynomial, 
-    fft::serial,
-    domains::Domain,
+    fft::FftPlan,
+    domains::SynthesisError,
+    utils::batch_inverse,
 };
 
 use core::ops::{Add, Mul, Sub};
@@ -70,11 +71,16 @@ impl<F: PrimeField> Sub for Polynomial<F> {
 impl<F: PrimeField> Mul for Polynomial<F> {
     type Output = Self;
 
-    #[allow(clippy::suspicious_arithmetic_impl)]
     fn mul(self, other: Self) -> Self {
+        if self.len() == 0 || other.len() == 0 {
+            return Self::zero();
+        }
 
-        let log_n = self.log_n().add(other.log_n()) as u32;
-        let new_size = 1<<log_n;
+        // The product has len() - 1 + other.len() coefficients, which are padded to a power of
+        // two for the FFT and cut off again after it
+        let product_len = self.len() + other.len() - 1;
+        let new_size = product_len.next_power_of_two();
+        let log_n = new_size.trailing_zeros();
         
         let mut resized_one = self.coefficients();
         resized_one.resize(new_size, F::ZERO);
@@ -82,12 +88,14 @@ impl<F: PrimeField> Mul for Polynomial<F> {
         let mut resized_two = other.coefficients();
         resized_two.resize(new_size, F::ZERO);
 
-        let omega = Domain::<F>::new_for_size(new_size as u64).unwrap().generator;
-        serial::serial_fft(resized_one.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(resized_two.as_mut_slice(), &omega, log_n);
+        // The product is pointwise, so the evaluations are left in bit-reversed order
+        let plan = FftPlan::for_log_size(log_n).expect("Product is larger than the field's largest FFT domain.");
+        plan.fft_bit_reversed(resized_one.as_mut_slice());
+        plan.fft_bit_reversed(resized_two.as_mut_slice());
         
         let mut fourier = resized_one.iter().zip(&resized_two).map(|(a, b)| *a * *b).collect::<Vec<F>>();
-        serial::serial_ifft(fourier.as_mut_slice(), &omega, log_n);
+        plan.ifft_bit_reversed(fourier.as_mut_slice());
+        fourier.truncate(product_len);
         Self::from_vec(fourier)
     }
 }
@@ -97,7 +105,7 @@ impl<F: PrimeField> Polynomial<F> {
     // Fhis function assumes that "other" is the vanishing polynomial and will just perform
     // standard long division. If the other is the vanishing polynomial, then it will only take
     // O(n) time.
-    pub fn long_division(&self, divisor: &Polynomial<F>) -> (Self, Vec<F>) {
+    pub fn long_division(&self, divisor: &Polynomial<F>) -> Result<(Self, Vec<F>), SynthesisError> {
 
         let dividend = self.coefficients();
         let mut divisor = divisor.coefficients();
@@ -107,16 +115,22 @@ impl<F: PrimeField> Polynomial<F> {
             divisor.pop();
         }
         let divisor_len = divisor.len();
-        assert!(divisor_len > 0, "division by zero");
+        if divisor_len == 0 {
+            return Err(SynthesisError::DivisionByZero);
+        }
+        if dividend.len() < divisor_len {
+            return Ok((Self::zero(), dividend));
+        }
 
         // Initialize the quotient and remainder to zero.
         let mut quotient = vec![F::ZERO; dividend.len() - divisor_len + 1];
         let mut remainder = dividend;
 
         // Perform long division until the degree of the remainder is less than the degree of the divisor.
+        let leading_inverse = divisor.last().unwrap().invert().unwrap();
         while remainder.len() >= divisor_len {
             let i = remainder.len() - divisor_len;
-            let c = *remainder.last().unwrap() * divisor.last().unwrap().invert().unwrap();
+            let c = *remainder.last().unwrap() * leading_inverse;
             quotient[i] = c;
             for j in 0..divisor_len {
                 remainder[i + j] -= c * divisor[j];
@@ -129,53 +143,44 @@ impl<F: PrimeField> Polynomial<F> {
             remainder.pop();
         }
 
-        if !quotient.len().is_power_of_two() {
-            let mut resize = vec![F::ZERO; quotient.len().next_power_of_two() - quotient.len()];
-            quotient.append(&mut resize);
-        }
-
-        (Self::from_vec(quotient), remainder)
+        Ok((Self::from_vec(quotient), remainder))
     }
 
 
     // Fhis division function is O(n) and is theoretically used for standard division; HOWEVER, it
-    // will fail if any of the roots of the divisor are also log_n-th roots of unity.
-    // Fhis means if the divisor has root (x-1), then it will fail.
-    pub fn divide_fft(&self, other: &Self) -> Self {
+    // will fail if any of the roots of the divisor are also log_n-th roots of unity, for n the
+    // length of the longer of the two padded to a power of two.
+    // Fhis means if the divisor has root (x-1), then it will fail with DivisionByZero.
+    pub fn divide_fft(&self, other: &Self) -> Result<Self, SynthesisError> {
         let mut dividend = self.coefficients();
         let mut divisor = other.coefficients();
+        let size = dividend.len().max(divisor.len()).next_power_of_two();
 
-        // Append the 0 of the vanishing polynomial to the divisor
-        divisor.extend_from_slice(&vec![F::ZERO; dividend.len() - divisor.len()]);
-
-        let dividend_len = dividend.len();
-        let log_n = dividend_len.trailing_zeros();
+        // Pad both to the size of the FFT
+        dividend.resize(size, F::ZERO);
+        divisor.resize(size, F::ZERO);
 
-        let kth_roots = dividend_len as u64 * log_n as u64;
-        assert!(kth_roots.is_power_of_two());
-
-        // Calculate the log_n-kth roots of unity
-        let evaluation_domain: Domain<F> = Domain::new_for_size(kth_roots).unwrap();
-        let omega = evaluation_domain.generator;
-
-        // Evaluate the dividend and divisor at the log_n-th roots of unity using FFF
-        serial::serial_fft(dividend.as_mut_slice(), &omega, log_n);
-        serial::serial_fft(divisor.as_mut_slice(), &omega, log_n);
+        // Evaluate the dividend and divisor at the roots of unity of the dividend's size using FFT,
+        // in bit-reversed order since they are only divided pointwise
+        let plan = FftPlan::for_size(dividend.len())?;
+        plan.fft_bit_reversed(dividend.as_mut_slice());
+        plan.fft_bit_reversed(divisor.as_mut_slice());
 
         // Divide the evaluations of the dividend by the evaluations of the divisor
-        for i in 0..dividend_len {
-            dividend[i] *= divisor[i].invert().unwrap();
+        let divisor_inverses = batch_inverse(&divisor)?;
+        for (d, inverse) in dividend.iter_mut().zip(divisor_inverses) {
+            *d *= inverse;
         }
 
         // Interpolate the quotient polynomial using IFFT
-        serial::serial_ifft(dividend.as_mut_slice(), &omega, log_n);
+        plan.ifft_bit_reversed(dividend.as_mut_slice());
 
         // Remove any leading zero 0
         while !dividend.is_empty() && dividend.last().unwrap().is_zero().into() {
             dividend.pop();
         }
 
-        Self::from_vec(dividend)
+        Ok(Self::from_vec(dividend))
     }
 
     pub fn square(self) -> Self {
@@ -184,11 +189,25 @@ impl<F: PrimeField> Polynomial<F> {
 
     pub fn sub_constant(&self, w: F) -> Self {
         let mut ret = self.clone();
-        ret.0[0] -= w;
+        ret.add_constant(-w);
         ret
     }
 
     pub fn add_constant(&mut self, w: F) {
-        self.0[0] += w;
+        match self.0.first_mut() {
+            Some(c) => *c += w,
+            None => self.0.push(w),
+        }
+    }
+
+    // The polynomial p(c*x), i.e. the i-th coefficient is multiplied by c^i.
+    pub fn scale_argument(&self, c: F) -> Self {
+        let mut power = F::ONE;
+        let mut ret = self.coefficients();
+        for coefficient in ret.iter_mut() {
+            *coefficient *= power;
+            power *= c;
+        }
+        Self::from_vec(ret)
     }
 }
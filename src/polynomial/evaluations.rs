@@ -0,0 +1,233 @@
+use ff::PrimeField;
+
+use crate::{
+    polynomial::Polynomial,
+    domains::{Domain, SynthesisError},
+    utils::batch_inverse,
+    field::packed::{self, Lanewise, PackedField, PackedKernel},
+};
+
+use core::ops::{Add, Div, Mul, Sub};
+
+/*
+ * A polynomial in evaluation form: its values at the elements x_0, ..., x_(n-1) of a domain, in
+ * that order. The domain is part of the value, so evaluations over different domains cannot be
+ * combined by accident, and going back and forth with the coefficient form is always an explicit
+ * `interpolate` or `evaluate_over_domain`.
+ *
+ * Sums, differences, products and quotients are pointwise. They are exact as long as the result
+ * has degree below n; a product of two polynomials of degree close to n has to be evaluated over
+ * a larger domain first. Combining evaluations over different domains is a DomainMismatch error,
+ * so the operators return a Result.
+*/
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluations<F: PrimeField> {
+    evaluations: Vec<F>,
+    domain: Domain<F>,
+}
+
+impl<F: PrimeField> Evaluations<F> {
+
+    pub fn from_vec_and_domain(evaluations: Vec<F>, domain: Domain<F>) -> Result<Self, SynthesisError> {
+        if evaluations.len() as u64 != domain.size {
+            return Err(SynthesisError::LengthMismatch(domain.size as usize, evaluations.len()));
+        }
+        Ok(Self { evaluations, domain })
+    }
+
+    pub fn evaluations(&self) -> &[F] {
+        &self.evaluations
+    }
+
+    pub fn domain(&self) -> &Domain<F> {
+        &self.domain
+    }
+
+    // The polynomial of degree below n through the evaluations.
+    pub fn interpolate(self) -> Polynomial<F> {
+        let Self { mut evaluations, domain } = self;
+        domain.ifft(evaluations.as_mut_slice()).expect("Evaluations have the size of their domain.");
+        Polynomial::from_vec(evaluations)
+    }
+
+    // Pointwise quotient, or DivisionByZero if `other` vanishes anywhere on the domain. Same as `/`
+    // without taking the operands.
+    pub fn checked_div(&self, other: &Self) -> Result<Self, SynthesisError> {
+        self.check_same_domain(other)?;
+        let mut evaluations = self.evaluations.clone();
+        pointwise_product(&mut evaluations, &batch_inverse(&other.evaluations)?);
+        Ok(Self { evaluations, domain: self.domain })
+    }
+
+    fn check_same_domain(&self, other: &Self) -> Result<(), SynthesisError> {
+        if self.domain != other.domain {
+            return Err(SynthesisError::DomainMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> Polynomial<F> {
+
+    // The evaluations at every element of the domain. Coefficients past the domain size are first
+    // reduced modulo the vanishing polynomial x^n - offset^n, which does not change the values on
+    // the domain.
+    pub fn evaluate_over_domain(&self, domain: &Domain<F>) -> Evaluations<F> {
+        let size = domain.size as usize;
+        let mut evaluations = vec![F::ZERO; size];
+        let offset_to_size = domain.offset.pow([domain.size]);
+        let mut factor = F::ONE;
+        for chunk in self.coefficients().chunks(size) {
+            for (e, c) in evaluations.iter_mut().zip(chunk) {
+                *e += factor * c;
+            }
+            factor *= offset_to_size;
+        }
+        domain.fft(evaluations.as_mut_slice()).expect("Evaluations have the size of their domain.");
+        Evaluations { evaluations, domain: *domain }
+    }
+}
+
+impl<F: PrimeField> Add for Evaluations<F> {
+    type Output = Result<Self, SynthesisError>;
+
+    fn add(mut self, other: Self) -> Self::Output {
+        self.check_same_domain(&other)?;
+        for (a, b) in self.evaluations.iter_mut().zip(&other.evaluations) {
+            *a += b;
+        }
+        Ok(self)
+    }
+}
+
+impl<F: PrimeField> Sub for Evaluations<F> {
+    type Output = Result<Self, SynthesisError>;
+
+    fn sub(mut self, other: Self) -> Self::Output {
+        self.check_same_domain(&other)?;
+        for (a, b) in self.evaluations.iter_mut().zip(&other.evaluations) {
+            *a -= b;
+        }
+        Ok(self)
+    }
+}
+
+impl<F: PrimeField> Mul for Evaluations<F> {
+    type Output = Result<Self, SynthesisError>;
+
+    fn mul(mut self, other: Self) -> Self::Output {
+        self.check_same_domain(&other)?;
+        pointwise_product(&mut self.evaluations, &other.evaluations);
+        Ok(self)
+    }
+}
+
+impl<F: PrimeField> Div for Evaluations<F> {
+    type Output = Result<Self, SynthesisError>;
+
+    fn div(self, other: Self) -> Self::Output {
+        self.checked_div(&other)
+    }
+}
+
+// a_i = a_i * b_i, on packed Goldilocks when F is Goldilocks
+struct PointwiseProduct<'a, F: PrimeField> {
+    a: &'a mut [F],
+    b: Factors<'a, F>,
+}
+
+struct Factors<'a, F: PrimeField>(&'a [F]);
+
+impl<F: PrimeField> Lanewise<F> for Factors<'_, F> {
+    #[inline(always)]
+    fn apply<P: PackedField<Scalar = F>>(&self, a: &mut [F], start: usize) {
+        for (a, b) in a.chunks_exact_mut(P::WIDTH).zip(self.0[start..].chunks_exact(P::WIDTH)) {
+            (P::load(a) * P::load(b)).store(a);
+        }
+    }
+}
+
+impl<F: PrimeField> PackedKernel<F> for PointwiseProduct<'_, F> {
+    type Output = ();
+
+    #[inline(always)]
+    fn run<P: PackedField<Scalar = F>>(self) {
+        packed::lanewise::<F, P, _>(self.a, &self.b);
+    }
+}
+
+pub(crate) fn pointwise_product<F: PrimeField>(a: &mut [F], b: &[F]) {
+    let kernel = PointwiseProduct { a, b: Factors(b) };
+    match (packed::as_goldilocks_mut(kernel.a), packed::as_goldilocks(b)) {
+        (Some(a), Some(b)) => packed::dispatch(PointwiseProduct { a, b: Factors(b) }),
+        _ => kernel.run::<F>(),
+    }
+}
+
+#[cfg(test)]
+mod evaluations_tests {
+
+    use super::*;
+    use crate::field::{goldilocks::Goldilocks, babybear::BabyBear};
+
+    use ff::Field;
+
+    #[test]
+    fn conversions() {
+        let domain = Domain::<Goldilocks>::new_for_size(16).unwrap();
+        let f_x = Polynomial::<Goldilocks>::random(16);
+        let evaluations = f_x.evaluate_over_domain(&domain);
+        for (x, e) in domain.elements().zip(evaluations.evaluations()) {
+            assert_eq!(f_x.eval_single(&x), *e);
+        }
+        assert_eq!(evaluations.interpolate(), f_x);
+
+        // Over a coset and a domain smaller than the polynomial
+        let coset = Domain::<Goldilocks>::new_for_size(8).unwrap().with_offset(Goldilocks::MULTIPLICATIVE_GENERATOR).unwrap();
+        let evaluations = f_x.evaluate_over_domain(&coset);
+        for (x, e) in coset.elements().zip(evaluations.evaluations()) {
+            assert_eq!(f_x.eval_single(&x), *e);
+        }
+
+        // Over a domain whose size is not a power of two
+        let domain = Domain::<BabyBear>::new_for_smooth_size(12).unwrap();
+        let g_x = Polynomial::<BabyBear>::random(12);
+        assert_eq!(g_x.evaluate_over_domain(&domain).interpolate(), g_x);
+
+        assert_eq!(
+            Evaluations::from_vec_and_domain(vec![Goldilocks::ONE; 4], Domain::new_for_size(8).unwrap()),
+            Err(SynthesisError::LengthMismatch(8, 4))
+        );
+    }
+
+    #[test]
+    fn pointwise_arithmetic() {
+        let domain = Domain::<Goldilocks>::new_for_size(16).unwrap();
+        let f_x = Polynomial::<Goldilocks>::random(8);
+        let g_x = Polynomial::<Goldilocks>::random(8);
+        let (f, g) = (f_x.evaluate_over_domain(&domain), g_x.evaluate_over_domain(&domain));
+
+        assert_eq!((f.clone() + g.clone()).unwrap(), (f_x.clone() + g_x.clone()).evaluate_over_domain(&domain));
+        assert_eq!((f.clone() - g.clone()).unwrap(), (f_x.clone() - g_x.clone()).evaluate_over_domain(&domain));
+        let product = (f.clone() * g.clone()).unwrap();
+        assert_eq!(product, (f_x.clone() * g_x).evaluate_over_domain(&domain));
+        assert_eq!((product / g.clone()).unwrap(), f);
+
+        let zero = Evaluations::from_vec_and_domain(vec![Goldilocks::ZERO; 16], domain).unwrap();
+        assert_eq!(f.checked_div(&zero), Err(SynthesisError::DivisionByZero));
+        assert_eq!(f / zero, Err(SynthesisError::DivisionByZero));
+    }
+
+    #[test]
+    fn mixing_domains() {
+        let f_x = Polynomial::<Goldilocks>::random(8);
+        let domain = Domain::<Goldilocks>::new_for_size(8).unwrap();
+        let coset = domain.with_offset(Goldilocks::MULTIPLICATIVE_GENERATOR).unwrap();
+        let (f, g) = (f_x.evaluate_over_domain(&domain), f_x.evaluate_over_domain(&coset));
+        assert_eq!(f.clone() + g.clone(), Err(SynthesisError::DomainMismatch));
+        assert_eq!(f.clone() - g.clone(), Err(SynthesisError::DomainMismatch));
+        assert_eq!(f.clone() * g.clone(), Err(SynthesisError::DomainMismatch));
+        assert_eq!(f / g, Err(SynthesisError::DomainMismatch));
+    }
+}
@@ -2,69 +2,62 @@ use ff::PrimeField;
 
 use crate::{
     domains::Domain,
+    field::extension::ExtensionField,
 };
 
 pub mod arithmetic;
+pub mod evaluations;
 
-#[derive(Clone, Debug, PartialEq)]
+pub use evaluations::Evaluations;
+
+/*
+ * A polynomial as its coefficient vector, lowest degree first. The vector can have any length and
+ * may end in zero coefficients: `len` is the storage, `degree` the degree of the polynomial, and
+ * two polynomials are equal when they agree up to trailing zeros. The zero polynomial may be
+ * stored as an empty vector. Code that needs a power-of-two size, like the FFTs and the FRI
+ * commitments, pads a copy of the coefficients itself.
+*/
+
+#[derive(Clone, Debug)]
 pub struct Polynomial<F: PrimeField>(Vec<F>);
 
+impl<F: PrimeField> PartialEq for Polynomial<F> {
+    fn eq(&self, other: &Self) -> bool {
+        let (short, long) = if self.len() <= other.len() { (self, other) } else { (other, self) };
+        short.0 == long.0[..short.len()] && long.0[short.len()..].iter().all(|c| bool::from(c.is_zero()))
+    }
+}
+
+impl<F: PrimeField> Eq for Polynomial<F> {}
+
 // Construc impl block
 impl<F: PrimeField> Polynomial<F> {
 
-    // Assumes that v.len() is a power of 2. 
     pub(crate) fn from_vec(v: Vec<F>) -> Self {
         Self(v)
     }
+
+    pub fn zero() -> Self {
+        Self(Vec::new())
+    }
  
     pub fn eval_single(&self, point: &F) -> F {
-        let mut result: F = self.leading_coefficient();
-        for i in (0..self.len() - 1).rev() {
-            result = (result * *point) + self.0[i];
-        }
-        result
+        self.0.iter().rev().fold(F::ZERO, |result, c| (result * *point) + c)
     }
 
-    pub(crate) fn pop_zeros(&mut self) {
-        for i in (0..self.len()).rev() {
-            if self.0[i] == F::ZERO {
-                self.0.pop();
-            }
-            else {
-                break;
-            }
-        }
-        if !self.len().is_power_of_two() { 
-            let mut x = 0usize;
-            let mut y = self.len();
-            while y != 1 {
-                y >>= 1;
-                x += 1;
-            }
-            self.pad_to_base(x + 1); 
-        }
+    // Evaluates this base field polynomial at a point of an extension field, which is how
+    // verifier challenges drawn from the extension are applied to committed polynomials.
+    pub fn eval_extension<E: ExtensionField<F>>(&self, point: &E) -> E {
+        self.0.iter().rev().fold(E::ZERO, |result, c| (result * point) + E::from(*c))
     }
 
+    // x^roots - 1
     pub fn vanishing_polynomial(roots: u128) -> Self {
-        let mut v = Vec::new();
-        v.push(F::ZERO - F::ONE);
-        let mut len = vec![F::ZERO; (roots - 1) as usize];
-        v.append(&mut len);
-        v.push(F::ONE);
-
-        // Pad the vector to the next largest power of 2
-        let mut additional_zeros = vec![F::ZERO; v.len().next_power_of_two() - v.len()];
-        v.append(&mut additional_zeros);
-
+        let mut v = vec![F::ZERO; roots as usize + 1];
+        v[0] = -F::ONE;
+        v[roots as usize] = F::ONE;
         Self::from_vec(v)
     }
-
-    pub fn pad_to_base(&mut self, new_base: usize) {
-        let size = 1<<new_base;
-        assert!(size > self.len());
-        let mut extension = vec![F::ZERO; size - self.len()];
-        self.0.append(&mut extension);
-    }
 }
 
 // Utility/organizational functions
@@ -75,28 +68,36 @@ impl<F: PrimeField> Polynomial<F> {
         self.0.clone()
     }
 
-    pub(crate) fn leading_coefficient(&self) -> F {
-        let index = self.len();
-        self.0[index-1]
+    // The degree, or None for the zero polynomial, which has no degree (unlike a nonzero constant,
+    // of degree 0).
+    pub fn degree(&self) -> Option<usize> {
+        self.0.iter().rposition(|c| !bool::from(c.is_zero()))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|c| bool::from(c.is_zero()))
+    }
+
+    // Drops the trailing zero coefficients, so that len() is one more than the degree, or 0 for
+    // the zero polynomial.
+    pub fn normalize(&mut self) {
+        while self.0.last().is_some_and(|c| bool::from(c.is_zero())) {
+            self.0.pop();
+        }
+    }
+
+    // Keeps the `len` lowest coefficients, i.e. reduces the polynomial modulo x^len.
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
     }
 
     pub(crate) fn coefficient_at(&self, index: usize) -> F {
-        self.0[index]
+        self.0.get(index).copied().unwrap_or(F::ZERO)
     }
 
     pub(crate) fn len(&self) -> usize {
         self.0.len()
     }
-
-    pub(crate) fn log_n(&self) -> usize {
-        let mut x = 0usize;
-        let mut y = self.len().next_power_of_two();
-        while y != 1 {
-            y >>= 1;
-            x += 1;
-        }
-        x
-    }
 }
 
 // Testing impl block
@@ -104,10 +105,10 @@ impl<F: PrimeField> Polynomial<F> {
 
     // O(N^2). For testing only
     pub fn eval_at_naive(&self, x: &F) -> F {
-        let mut e = *x;
-        let mut ret = self.0[0];
-        for i in 1..self.len() {
-            ret += e * self.0[i];
+        let mut e = F::ONE;
+        let mut ret = F::ZERO;
+        for c in self.0.iter() {
+            ret += e * c;
             e *= *x;
         }
         ret
@@ -123,8 +124,79 @@ impl<F: PrimeField> Polynomial<F> {
             polynomial = polynomial * new_poly;
             gen *= gen_base;
         }
-        polynomial.pop_zeros();
+        polynomial.normalize();
         polynomial
     }
 
+    // Random coefficients from the thread rng, for the test modules
+    #[cfg(test)]
+    pub(crate) fn random(len: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        Self::from_vec((0..len).map(|_| F::random(&mut rng)).collect())
+    }
+
+}
+
+#[cfg(test)]
+mod polynomial_tests {
+
+    use super::*;
+    use crate::{field::goldilocks::Goldilocks, FriProof};
+
+    use ff::Field;
+
+    #[test]
+    fn degree_and_normalization() {
+        let mut f_x = Polynomial::from_vec(vec![Goldilocks::ONE, Goldilocks::from(2), Goldilocks::ZERO, Goldilocks::ZERO]);
+        assert_eq!((f_x.len(), f_x.degree()), (4, Some(1)));
+        assert_eq!(f_x, Polynomial::from_vec(vec![Goldilocks::ONE, Goldilocks::from(2)]));
+        f_x.normalize();
+        assert_eq!(f_x.len(), 2);
+        f_x.truncate(1);
+        assert_eq!(f_x, Polynomial::from_vec(vec![Goldilocks::ONE]));
+        assert_eq!(f_x.degree(), Some(0));
+
+        let mut zero = Polynomial::from_vec(vec![Goldilocks::ZERO; 3]);
+        assert!(zero.is_zero() && zero.degree().is_none());
+        zero.normalize();
+        assert_eq!(zero, Polynomial::zero());
+        assert_eq!(zero.eval_single(&Goldilocks::from(7)), Goldilocks::ZERO);
+
+        assert_eq!(Polynomial::<Goldilocks>::vanishing_polynomial(5).len(), 6);
+        assert_eq!(Polynomial::<Goldilocks>::divisor_polynomial(4), Polynomial::vanishing_polynomial(4));
+    }
+
+    #[test]
+    fn arbitrary_lengths() {
+        let f_x = Polynomial::<Goldilocks>::random(5);
+        let g_x = Polynomial::<Goldilocks>::random(7);
+        let x = Goldilocks::from(11);
+
+        let product = f_x.clone() * g_x.clone();
+        assert_eq!(product.len(), 11);
+        assert_eq!(product.eval_single(&x), f_x.eval_single(&x) * g_x.eval_single(&x));
+
+        let (quotient, remainder) = product.long_division(&g_x).unwrap();
+        assert!(remainder.iter().all(|c| bool::from(c.is_zero())));
+        assert_eq!(quotient.len(), 5);
+        assert_eq!(quotient, f_x);
+        assert_eq!(product.divide_fft(&g_x).unwrap(), f_x);
+
+        // FRI pads the polynomial internally
+        let h_x = Polynomial::<Goldilocks>::random(13);
+        assert!(FriProof::evaluation_proof(&h_x, None).verify().is_valid());
+    }
+
+    #[test]
+    fn divide_fft_sizes() {
+        // Products of length 8, 16 and 32. The FFT runs over the roots of unity of that size, not of
+        // size n * log2(n), which is no power of two for n = 8 and not a subgroup the FFT of length
+        // n can run over for n = 16 or 32.
+        for (f_len, g_len) in [(4, 5), (7, 10), (16, 17), (1, 2)] {
+            let f_x = Polynomial::<Goldilocks>::random(f_len);
+            let g_x = Polynomial::<Goldilocks>::random(g_len);
+            let product = f_x.clone() * g_x.clone();
+            assert_eq!(product.divide_fft(&g_x).unwrap(), f_x);
+        }
+    }
 }
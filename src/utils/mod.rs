@@ -1,6 +1,8 @@
-use ff::PrimeField;
+use ff::{Field, PrimeField};
 use primitive_types::U256;
 
+use crate::domains::SynthesisError;
+
 pub(crate) fn field_element_from_bytes<F: PrimeField>(bytes: &[u8]) -> F {
     let mut repr = F::Repr::default();
 
@@ -15,6 +17,30 @@ pub(crate) fn field_element_from_bytes<F: PrimeField>(bytes: &[u8]) -> F {
     F::from_repr(repr).unwrap()
 }
 
+// Montgomery's trick: inverts every element with a single field inversion and 3n multiplications.
+// With prefix products p_i = a_0 * ... * a_(i-1), walking back from (a_0 * ... * a_(n-1))^-1 gives
+// a_i^-1 = p_i * (a_0 * ... * a_i)^-1. A single zero would make the whole product zero, so it is
+// reported instead.
+pub fn batch_inverse<F: Field>(values: &[F]) -> Result<Vec<F>, SynthesisError> {
+    let mut prefix_products = Vec::with_capacity(values.len());
+    let mut product = F::ONE;
+    for value in values {
+        if bool::from(value.is_zero()) {
+            return Err(SynthesisError::DivisionByZero);
+        }
+        prefix_products.push(product);
+        product *= value;
+    }
+
+    let mut inverse = product.invert().unwrap();
+    let mut inverses = vec![F::ZERO; values.len()];
+    for i in (0..values.len()).rev() {
+        inverses[i] = prefix_products[i] * inverse;
+        inverse *= values[i];
+    }
+    Ok(inverses)
+}
+
 #[cfg(test)]
 mod utils_tests {
     use super::*;
@@ -47,4 +73,20 @@ mod utils_tests {
         let element_wraparound: Fp = field_element_from_bytes(wraparound.as_slice());
         assert_eq!(element_wraparound, Fp::from(14)); // f - 1 = e
     }
+
+    #[test]
+    fn batch_inversion() {
+        let rng = rand::thread_rng();
+        let values = (0..33).map(|_| Fp::random(rng.clone())).collect::<Vec<_>>();
+        let inverses = batch_inverse(&values).unwrap();
+        for (value, inverse) in values.iter().zip(&inverses) {
+            assert_eq!(value.invert().unwrap(), *inverse);
+        }
+
+        assert!(batch_inverse::<Fp>(&[]).unwrap().is_empty());
+
+        let mut with_zero = values;
+        with_zero[17] = Fp::ZERO;
+        assert!(matches!(batch_inverse(&with_zero), Err(SynthesisError::DivisionByZero)));
+    }
 }